@@ -0,0 +1,60 @@
+//! This bench compares random accesses on an [`EliasFano`] with selection
+//! structures and on an [`InterleavedEliasFano`].
+//!
+//! The interleaved layout touches fewer memory regions per access, but needs
+//! a sequential scan of the upper bits of a block, so the outcome depends on
+//! the hardware and on the size of the structure with respect to the caches.
+//! Cache misses can be measured running this bench under
+//! `perf stat -e cache-misses`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+const N: usize = 10_000_000;
+const U: usize = N * 64;
+const QUERIES: usize = 1_000_000;
+
+fn builder() -> EliasFanoBuilder {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut values = (0..N).map(|_| rng.gen_range(0..U)).collect::<Vec<_>>();
+    values.sort();
+    let mut efb = EliasFanoBuilder::new(N, U);
+    for value in values {
+        efb.push(value).unwrap();
+    }
+    efb
+}
+
+fn queries() -> Vec<usize> {
+    let mut rng = SmallRng::seed_from_u64(1);
+    (0..QUERIES).map(|_| rng.gen_range(0..N)).collect()
+}
+
+fn bench_get(c: &mut Criterion) {
+    let efb = builder();
+    let queries = queries();
+
+    let ef: EliasFano<SelectFixed2> = efb.clone().build().convert_to().unwrap();
+    c.bench_function("elias_fano_get", |b| {
+        b.iter(|| {
+            for &index in &queries {
+                black_box(unsafe { ef.get_unchecked(index) });
+            }
+        });
+    });
+
+    let ief = efb.build_interleaved();
+    c.bench_function("interleaved_elias_fano_get", |b| {
+        b.iter(|| {
+            for &index in &queries {
+                black_box(unsafe { ief.get_unchecked(index) });
+            }
+        });
+    });
+}
+
+criterion_group!(benches, bench_get);
+criterion_main!(benches);
@@ -13,6 +13,7 @@ use rand::Rng;
 use rand::SeedableRng;
 use std::hint::black_box;
 use sux::prelude::*;
+use sux::traits::bit_field_slice::BitFieldSliceApply;
 
 #[derive(Parser, Debug)]
 #[command(about = "Benchmarks compact arrays", long_about = None)]
@@ -84,6 +85,12 @@ pub fn main() -> Result<()> {
             black_box(unsafe { iter.next_unchecked() });
         }
         pl.done_with_count(args.n);
+
+        // Compare widths dividing the word size (e.g., 8, 16, 32)
+        // with other widths to measure the power-of-two fast path.
+        pl.start("Applying in place...");
+        unsafe { a.apply_inplace_unchecked(black_box) };
+        pl.done_with_count(a.len());
     }
 
     Ok(())
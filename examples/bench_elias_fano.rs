@@ -229,6 +229,18 @@ fn main() -> Result<()> {
             black_box(i);
         }
         pl.done_with_count(args.n);
+
+        pl.start("Benchmarking s.iter().for_each()...");
+        elias_fano_s.iter().for_each(|i| {
+            black_box(i);
+        });
+        pl.done_with_count(args.n);
+
+        pl.start("Benchmarking sequential s.get_unchecked()...");
+        for i in 0..args.n {
+            black_box(unsafe { elias_fano_s.get_unchecked(i) });
+        }
+        pl.done_with_count(args.n);
     }
 
     Ok(())
@@ -0,0 +1,116 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Arenas of [`BitFieldVec`]s sharing a single buffer.
+
+When building many small [`BitFieldVec`]s, allocating a separate
+vector of words for each one has a significant overhead, both in time and
+in space, and scatters the data in memory. A [`BitFieldArena`] hands out
+instead [`BitFieldVec`]s backed by consecutive, non-overlapping
+portions of a single preallocated buffer of words. Each vector is independent
+from the others, and can be read and written as usual.
+
+The number of words necessary for a batch of vectors can be computed in advance
+with [`BitFieldArena::words_for_all`], so that the buffer can be allocated
+with the exact capacity:
+
+```rust
+use sux::prelude::*;
+
+# fn main() -> anyhow::Result<()> {
+let shapes = [(5, 100), (12, 3), (1, 1000)];
+let mut buffer = vec![0_usize; BitFieldArena::<usize>::words_for_all(shapes)];
+let mut arena = BitFieldArena::new(&mut buffer);
+
+let mut vecs = shapes
+    .iter()
+    .map(|&(bit_width, len)| arena.alloc(bit_width, len))
+    .collect::<anyhow::Result<Vec<_>>>()?;
+vecs[0].set(42, 17);
+vecs[1].set(2, 4095);
+assert_eq!(vecs[0].get(42), 17);
+assert_eq!(vecs[1].get(2), 4095);
+assert_eq!(arena.remaining_words(), 0);
+# Ok(())
+# }
+```
+
+*/
+
+use crate::prelude::*;
+use anyhow::{ensure, Result};
+
+/// An arena handing out [`BitFieldVec`]s backed by portions of a single
+/// buffer of words.
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug)]
+pub struct BitFieldArena<'a, W: Word = usize> {
+    /// The part of the buffer that has not been handed out yet.
+    rest: &'a mut [W],
+}
+
+impl<'a, W: Word> BitFieldArena<'a, W> {
+    /// Return the number of words used by the arena to store `len` values
+    /// of bit width `bit_width`.
+    ///
+    /// Note that at least one word is always used, as the
+    /// implementation of [`BitFieldVec`] needs it to handle the case of
+    /// bit width zero.
+    pub fn words_for(bit_width: usize, len: usize) -> usize {
+        Ord::max(1, (len * bit_width).div_ceil(W::BITS))
+    }
+
+    /// Return the number of words used by the arena to store a batch of vectors
+    /// given by pairs of bit width and length.
+    pub fn words_for_all(shapes: impl IntoIterator<Item = (usize, usize)>) -> usize {
+        shapes
+            .into_iter()
+            .map(|(bit_width, len)| Self::words_for(bit_width, len))
+            .sum()
+    }
+
+    /// Create a new arena handing out portions of the given buffer.
+    pub fn new(buffer: &'a mut [W]) -> Self {
+        Self { rest: buffer }
+    }
+
+    /// Return the number of words of the buffer that have not been handed out yet.
+    pub fn remaining_words(&self) -> usize {
+        self.rest.len()
+    }
+
+    /// Return a new vector of zeros of given bit width and length backed
+    /// by the next [`words_for(bit_width, len)`](BitFieldArena::words_for)
+    /// words of the buffer.
+    ///
+    /// This method will return an error if the bit width is larger than the
+    /// number of bits of a word, or if the remaining part of the buffer
+    /// is not sufficient.
+    pub fn alloc(&mut self, bit_width: usize, len: usize) -> Result<BitFieldVec<W, &'a mut [W]>> {
+        ensure!(
+            bit_width <= W::BITS,
+            "Bit width {} is larger than the number of bits of a word ({})",
+            bit_width,
+            W::BITS
+        );
+        let num_words = Self::words_for(bit_width, len);
+        ensure!(
+            num_words <= self.rest.len(),
+            "Not enough words left in the arena: {} needed, {} available",
+            num_words,
+            self.rest.len()
+        );
+        let (data, rest) = core::mem::take(&mut self.rest).split_at_mut(num_words);
+        self.rest = rest;
+        data.fill(W::ZERO);
+        // SAFETY: data contains enough bits for len values of bit width bit_width
+        Ok(unsafe { BitFieldVec::from_raw_parts(data, bit_width, len) })
+    }
+}
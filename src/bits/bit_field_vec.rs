@@ -42,22 +42,44 @@ to this type. The are used, for example, to provide
 [successor](crate::traits::indexed_dict::Succ) primitives
 for [Elias-Fano](crate::dict::elias_fano::EliasFano).
 
+If the `rayon` feature is enabled, a reference to this type
+can also be turned into a parallel iterator, so, for example,
+`vec.par_iter().filter(|&x| x > threshold).count()` will count
+in parallel the values larger than `threshold`.
+
 ## Low-level support
 
 The methods [`address_of`](BitFieldVec::address_of)
 and [`get_unaligned`](BitFieldVec::get_unaligned) can be used to manually
 prefetch parts of the data structure, or read values using unaligned
-read, when the bit width makes it possible.
+read, when the bit width makes it possible; symmetrically,
+[`set_unaligned`](BitFieldVec::set_unaligned) writes values using unaligned
+writes.
 */
 
 use crate::prelude::*;
 use crate::traits::bit_field_slice::*;
-use anyhow::{bail, Result};
+use anyhow::{bail, ensure, Result};
 use common_traits::*;
 use epserde::*;
 use mem_dbg::*;
-use std::sync::atomic::*;
+use std::sync::atomic::{
+    compiler_fence, fence, AtomicU16, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering,
+};
+mod append;
+pub use append::{BitFieldVecReader, APPENDABLE_MAGIC, APPENDABLE_VERSION};
+mod map;
+pub(crate) use map::map_file_mut;
+pub use map::{map_bit_field_vec, map_bit_field_vec_mut, MappedBitFieldVec};
 mod ops;
+#[cfg(feature = "rayon")]
+mod par_iter;
+#[cfg(feature = "rayon")]
+pub use par_iter::BitFieldVecParIter;
+#[cfg(feature = "serde")]
+mod serde;
+mod view;
+pub use view::BitFieldVecView;
 
 /// A vector of bit fields of fixed width.
 #[derive(Epserde, Debug, Clone, Hash, MemDbg, MemSize)]
@@ -93,6 +115,20 @@ fn mask<W: Word>(bit_width: usize) -> W {
     }
 }
 
+/// Return the number of bits necessary to represent `value`, that is,
+/// zero for zero and `⌊log₂ value⌋ + 1` otherwise.
+///
+/// This is the minimum bit width of a [`BitFieldVec`] able to store `value`
+/// (except for zero, which can be stored using bit width zero).
+#[inline(always)]
+pub fn bit_width_of(value: usize) -> usize {
+    if value == 0 {
+        0
+    } else {
+        value.ilog2() as usize + 1
+    }
+}
+
 impl<W: Word> BitFieldVec<W, Vec<W>> {
     /// Returns a new BitFieldVec filled with `word`
     ///
@@ -324,9 +360,101 @@ impl<W: Word> BitFieldVec<W, Vec<W>> {
     /// Set len to 0
     pub fn clear(&mut self) {
         self.data.clear();
+        // We need at least one word to handle the case of bit width zero.
+        self.data.push(W::ZERO);
         self.len = 0;
     }
 
+    /// Reinitialize this vector with a new bit width and length, filling it with zeros.
+    ///
+    /// The underlying vector of words is reused, and it is reallocated only
+    /// if its capacity is not sufficient to store `len` values of width `bit_width`.
+    pub fn reinit(&mut self, bit_width: usize, len: usize) {
+        // We need at least one word to handle the case of bit width zero.
+        let n_of_words = Ord::max(1, (len * bit_width).div_ceil(W::BITS));
+        self.data.clear();
+        self.data.resize(n_of_words, W::ZERO);
+        self.bit_width = bit_width;
+        self.mask = mask(bit_width);
+        self.len = len;
+    }
+
+    /// Change the bit width of this vector, preserving its values.
+    ///
+    /// When the bit width decreases, the values are repacked in place in a
+    /// single forward pass, and the underlying vector of words is truncated to
+    /// the words that are still necessary. When the bit width increases,
+    /// a new vector of words is allocated once.
+    ///
+    /// # Panics
+    /// Panics if `new_bit_width` is larger than the number of bits of a word,
+    /// or if some value does not fit in `new_bit_width` bits (in which case
+    /// the vector is left unchanged).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut vec = BitFieldVec::<u64>::new(60, 0);
+    /// vec.extend([1, 100, 1000]);
+    /// vec.rescale(10);
+    /// assert_eq!(vec.bit_width(), 10);
+    /// assert_eq!(vec.iter().collect::<Vec<_>>(), [1, 100, 1000]);
+    /// ```
+    pub fn rescale(&mut self, new_bit_width: usize) {
+        assert!(
+            new_bit_width <= W::BITS,
+            "Bit width {} is larger than the number of bits of a word ({})",
+            new_bit_width,
+            W::BITS
+        );
+        let old_bit_width = self.bit_width;
+        if new_bit_width > old_bit_width {
+            let mut widened = Self::new(new_bit_width, self.len);
+            for (index, value) in self.iter().enumerate() {
+                // SAFETY: index is within bounds and the bit width has increased
+                unsafe { widened.set_unchecked(index, value) };
+            }
+            *self = widened;
+            return;
+        }
+        if new_bit_width == old_bit_width {
+            return;
+        }
+
+        let new_mask = mask::<W>(new_bit_width);
+        if let Some((index, value)) = self
+            .iter()
+            .enumerate()
+            .find(|&(_, value)| value & !new_mask != W::ZERO)
+        {
+            panic!(
+                "Value {} at index {} does not fit in {} bits",
+                value, index, new_bit_width
+            );
+        }
+
+        if new_bit_width != 0 {
+            for index in 0..self.len {
+                // SAFETY: index is within bounds
+                let value = unsafe { self.get_unchecked(index) };
+                // The new position is never after the old one, so we
+                // overwrite only values that have already been read
+                let pos = index * new_bit_width;
+                let (word, bit) = (pos / W::BITS, pos % W::BITS);
+                self.data[word] = (self.data[word] & !(new_mask << bit)) | (value << bit);
+                if bit + new_bit_width > W::BITS {
+                    self.data[word + 1] = (self.data[word + 1] & !(new_mask >> (W::BITS - bit)))
+                        | (value >> (W::BITS - bit));
+                }
+            }
+        }
+        // We need at least one word to handle the case of bit width zero.
+        self.data
+            .truncate(Ord::max(1, (self.len * new_bit_width).div_ceil(W::BITS)));
+        self.bit_width = new_bit_width;
+        self.mask = new_mask;
+    }
+
     /// Return the bit-width of the values inside this vector.
     pub fn bit_width(&self) -> usize {
         debug_assert!(self.bit_width <= W::BITS);
@@ -380,6 +508,21 @@ impl<W: Word> BitFieldVec<W, Vec<W>> {
         self.len += 1;
     }
 
+    /// Add a value at the end of the BitFieldVec, returning an error
+    /// instead of panicking if the value does not fit in the bit width.
+    ///
+    /// The vector is left unchanged if an error is returned.
+    pub fn try_push(&mut self, value: W) -> Result<()> {
+        ensure!(
+            value & self.mask == value,
+            "Value {} does not fit in {} bits",
+            value,
+            self.bit_width
+        );
+        self.push(value);
+        Ok(())
+    }
+
     /// Truncate or exted with `value` the BitFieldVec
     pub fn resize(&mut self, new_len: usize, value: W) {
         panic_if_value!(value, self.mask, self.bit_width);
@@ -407,6 +550,237 @@ impl<W: Word> BitFieldVec<W, Vec<W>> {
         self.len -= 1;
         Some(value)
     }
+
+    /// Insert a value at the specified index, shifting all values after it
+    /// to the right.
+    ///
+    /// The values after `index` are shifted a word at a time. Inserting at
+    /// index [`len`](BitFieldSliceCore::len) is equivalent to a
+    /// [`push`](BitFieldVec::push).
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the length of the vector, or if the
+    /// value does not fit the bit width of the vector.
+    pub fn insert(&mut self, index: usize, value: W) {
+        if index > self.len {
+            panic!("Insertion index out of bounds: {} > {}", index, self.len);
+        }
+        panic_if_value!(value, self.mask, self.bit_width);
+        if self.bit_width != 0 {
+            let num_words = ((self.len + 1) * self.bit_width).div_ceil(W::BITS);
+            if num_words > self.data.len() {
+                self.data.resize(num_words, W::ZERO);
+            }
+            shift_bits_up(
+                &mut self.data[..num_words],
+                index * self.bit_width,
+                self.bit_width,
+            );
+        }
+        self.len += 1;
+        // SAFETY: index is within bounds and the value has been checked
+        unsafe { self.set_unchecked(index, value) };
+    }
+
+    /// Remove and return the value at the specified index, shifting all
+    /// values after it to the left.
+    ///
+    /// The values after `index` are shifted a word at a time, and the
+    /// underlying vector of words is truncated to the words that are
+    /// still necessary.
+    ///
+    /// # Panics
+    /// Panics if `index` is not smaller than the length of the vector.
+    pub fn remove(&mut self, index: usize) -> W {
+        if index >= self.len {
+            panic!("Removal index out of bounds: {} >= {}", index, self.len);
+        }
+        // SAFETY: index is within bounds
+        let value = unsafe { self.get_unchecked(index) };
+        if self.bit_width != 0 {
+            let num_words = (self.len * self.bit_width).div_ceil(W::BITS);
+            shift_bits_down(
+                &mut self.data[..num_words],
+                index * self.bit_width,
+                self.bit_width,
+            );
+            // We need at least one word to handle the case of bit width zero.
+            self.data.truncate(Ord::max(
+                1,
+                ((self.len - 1) * self.bit_width).div_ceil(W::BITS),
+            ));
+        }
+        self.len -= 1;
+        value
+    }
+
+    /// Retain only the values satisfying the given predicate, preserving
+    /// their order, analogously to [`Vec::retain`].
+    ///
+    /// The values are compacted in place in a single pass, and the underlying
+    /// vector of words is truncated to the words that are still necessary.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut vec: BitFieldVec<u16> = (0..10).collect();
+    /// vec.retain(|x| x % 3 == 0);
+    /// assert_eq!(vec.iter().collect::<Vec<_>>(), [0, 3, 6, 9]);
+    /// ```
+    pub fn retain<F: FnMut(W) -> bool>(&mut self, mut f: F) {
+        let mut kept = 0;
+        for index in 0..self.len {
+            // SAFETY: kept <= index < len
+            unsafe {
+                let value = self.get_unchecked(index);
+                if f(value) {
+                    if kept != index {
+                        self.set_unchecked(kept, value);
+                    }
+                    kept += 1;
+                }
+            }
+        }
+        self.len = kept;
+        // We need at least one word to handle the case of bit width zero.
+        self.data
+            .truncate(Ord::max(1, (kept * self.bit_width).div_ceil(W::BITS)));
+    }
+}
+
+/// Shift by `shift` positions towards the most significant end the bits
+/// of `data` starting from position `pos`, leaving unchanged the bits before `pos`.
+///
+/// The bits shifted past the end of `data` are lost, and the content of the
+/// `shift` bits starting from position `pos` is unspecified.
+fn shift_bits_up<W: Word>(data: &mut [W], pos: usize, shift: usize) {
+    let (first, bit) = (pos / W::BITS, pos % W::BITS);
+    let (words, bits) = (shift / W::BITS, shift % W::BITS);
+    let low_mask = (W::ONE << bit) - W::ONE;
+    let low = data[first] & low_mask;
+    for i in (first + words..<[W]>::len(data)).rev() {
+        let src = i - words;
+        let mut word = data[src] << bits;
+        if bits != 0 && src > first {
+            word |= data[src - 1] >> (W::BITS - bits);
+        }
+        data[i] = word;
+    }
+    data[first] = (data[first] & !low_mask) | low;
+}
+
+/// Shift by `shift` positions towards the least significant end the bits
+/// of `data` starting from position `pos + shift`, overwriting the `shift`
+/// bits starting from position `pos` and leaving unchanged the bits before `pos`.
+///
+/// The content of the last `shift` bits of `data` is unspecified.
+fn shift_bits_down<W: Word>(data: &mut [W], pos: usize, shift: usize) {
+    let (first, bit) = (pos / W::BITS, pos % W::BITS);
+    let (words, bits) = (shift / W::BITS, shift % W::BITS);
+    let low_mask = (W::ONE << bit) - W::ONE;
+    let low = data[first] & low_mask;
+    let len = <[W]>::len(data);
+    for i in first..len {
+        let src = i + words;
+        let mut word = if src < len {
+            data[src] >> bits
+        } else {
+            W::ZERO
+        };
+        if bits != 0 && src + 1 < len {
+            word |= data[src + 1] << (W::BITS - bits);
+        }
+        data[i] = word;
+    }
+    data[first] = (data[first] & !low_mask) | low;
+}
+
+impl<W: Word> BitFieldVec<W, Vec<W>> {
+    /// Append all values of a [`BitFieldSlice`] to the vector.
+    ///
+    /// The underlying vector of words is grown at most once. If the slice has the same
+    /// bit width of this vector and [exposes its words](BitFieldSlice::as_words),
+    /// whole words are copied (shifting them if the current end of this vector
+    /// does not lie on a word boundary); otherwise, values are appended one by one.
+    ///
+    /// # Panics
+    /// Panics if the bit width of `other` is larger than the bit width of this vector.
+    pub fn extend_from_bit_field_slice(&mut self, other: &impl BitFieldSlice<W>) {
+        assert!(
+            other.bit_width() <= self.bit_width,
+            "Cannot extend a vector of bit width {} with a slice of larger bit width {}",
+            self.bit_width,
+            other.bit_width()
+        );
+        let (old_len, other_len) = (self.len, other.len());
+        let new_len = old_len + other_len;
+        let num_words = (new_len * self.bit_width).div_ceil(W::BITS);
+        if num_words > self.data.len() {
+            self.data.resize(num_words, W::ZERO);
+        }
+
+        let bit_offset = old_len * self.bit_width;
+        match other.as_words() {
+            Some(words) if other.bit_width() == self.bit_width && other_len != 0 => {
+                let start = bit_offset / W::BITS;
+                let shift = bit_offset % W::BITS;
+                let copied = (other_len * self.bit_width).div_ceil(W::BITS);
+                if shift == 0 {
+                    self.data[start..start + copied].copy_from_slice(&words[..copied]);
+                } else {
+                    self.data[start] &= (W::ONE << shift) - W::ONE;
+                    for (i, &word) in words[..copied].iter().enumerate() {
+                        self.data[start + i] |= word << shift;
+                        if start + i + 1 < num_words {
+                            self.data[start + i + 1] = word >> (W::BITS - shift);
+                        }
+                    }
+                }
+                // Clear the bits of the last word past the end of the vector
+                let used = (new_len * self.bit_width) % W::BITS;
+                if used != 0 {
+                    self.data[num_words - 1] &= (W::ONE << used) - W::ONE;
+                }
+            }
+            _ => {
+                for i in 0..other_len {
+                    // SAFETY: the words have been allocated and the value fits
+                    // as its bit width is at most that of the vector
+                    unsafe { self.set_unchecked(old_len + i, other.get_unchecked(i)) };
+                }
+            }
+        }
+        self.len = new_len;
+    }
+
+    /// Move all values of `other` to the end of this vector, leaving `other` empty.
+    ///
+    /// Whole words are moved, shifting them if the current end of this vector
+    /// does not lie on a word boundary.
+    ///
+    /// # Panics
+    /// Panics if the two vectors have different bit widths.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut a = BitFieldVec::<usize>::new(5, 0);
+    /// a.extend([1, 2, 3]);
+    /// let mut b = BitFieldVec::<usize>::new(5, 0);
+    /// b.extend([4, 5]);
+    /// a.append(&mut b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+    /// assert!(b.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut BitFieldVec<W, Vec<W>>) {
+        assert_eq!(
+            self.bit_width, other.bit_width,
+            "Cannot append a vector of bit width {} to a vector of bit width {}",
+            other.bit_width, self.bit_width
+        );
+        self.extend_from_bit_field_slice(other);
+        other.clear();
+    }
 }
 
 impl<W: Word> core::iter::Extend<W> for BitFieldVec<W, Vec<W>> {
@@ -418,6 +792,41 @@ impl<W: Word> core::iter::Extend<W> for BitFieldVec<W, Vec<W>> {
     }
 }
 
+/// Collect values in a vector whose bit width is the minimum necessary
+/// to represent the largest value, or one if all values are zero (or
+/// there are no values).
+///
+/// Values are buffered until the end of the iteration, as the bit
+/// width is not known in advance.
+///
+/// # Examples
+/// ```rust
+/// use sux::prelude::*;
+/// let v: BitFieldVec = [3, 10, 0].into_iter().collect();
+/// assert_eq!(v.bit_width(), 4);
+/// assert_eq!(v.get(1), 10);
+/// ```
+impl<W: Word> core::iter::FromIterator<W> for BitFieldVec<W, Vec<W>> {
+    fn from_iter<T: IntoIterator<Item = W>>(iter: T) -> Self {
+        let mut values = vec![];
+        let mut max = W::ZERO;
+        for value in iter {
+            if value > max {
+                max = value;
+            }
+            values.push(value);
+        }
+        // This is one for zero
+        let bit_width = UnsignedInt::len(max) as usize;
+        let mut result = Self::new(bit_width, values.len());
+        for (i, value) in values.into_iter().enumerate() {
+            // SAFETY: i is within bounds and the bit width is sufficient for max
+            unsafe { result.set_unchecked(i, value) };
+        }
+        result
+    }
+}
+
 impl<W: Word, B: AsRef<[W]>> BitFieldVec<W, B> {
     /// Get the address of the item storing (the first part of)
     /// the element of given index.
@@ -425,13 +834,65 @@ impl<W: Word, B: AsRef<[W]>> BitFieldVec<W, B> {
     /// This method is mainly useful for manually prefetching
     /// parts of the data structure.
     pub fn address_of(&self, index: usize) -> *const W {
-        let pos = index * W::BITS;
+        let pos = index * self.bit_width;
         let word_index = pos / W::BITS;
         (&self.data.as_ref()[word_index]) as *const _
     }
 
+    /// Return the number of consecutive elements, starting from the element
+    /// of index `start`, that are stored entirely within the word
+    /// containing the first bit of the element of index `start`.
+    ///
+    /// The result is never larger than the number of elements from `start`
+    /// to the end of the vector; in particular, it is zero if `start` is equal
+    /// to the length of the vector. If the bit width is zero, the result
+    /// is the number of elements from `start` to the end of the vector.
+    ///
+    /// This method is mainly useful, together with
+    /// [`address_of`](BitFieldVec::address_of), to write custom fast paths
+    /// that process without branches the elements within a word before
+    /// handling the element straddling the word boundary.
+    ///
+    /// # Panics
+    /// Panics if `start` is greater than the length of the vector.
+    pub fn elements_until_boundary(&self, start: usize) -> usize {
+        assert!(
+            start <= self.len,
+            "Start index out of bounds: {} > {}",
+            start,
+            self.len
+        );
+        let remaining = self.len - start;
+        if self.bit_width == 0 {
+            return remaining;
+        }
+        let bit_index = (start * self.bit_width) % W::BITS;
+        Ord::min((W::BITS - bit_index) / self.bit_width, remaining)
+    }
+
+    /// Return whether the given bit width makes unaligned accesses possible,
+    /// that is, if `bit_width % 8` is not 3, 5, 6, or 7.
+    ///
+    /// For such bit widths, the bit offset of an element within the byte
+    /// containing its first bit plus the bit width never exceeds `W::BITS`.
+    #[inline(always)]
+    fn is_unaligned_compatible(bit_width: usize) -> bool {
+        !matches!(bit_width % 8, 3 | 5 | 6 | 7)
+    }
+
+    /// Return whether the `W::BYTES` bytes starting from the byte containing
+    /// the first bit of the element of given index are within the underlying
+    /// storage.
+    #[inline(always)]
+    fn is_unaligned_in_storage(&self, index: usize) -> bool {
+        (index * self.bit_width) / 8 + W::BYTES <= self.data.as_ref().len() * W::BYTES
+    }
+
     /// Like [`BitFieldSlice::get`], but using unaligned reads.
     ///
+    /// Elements too close to the end of the underlying storage
+    /// are read using [`BitFieldSlice::get_unchecked`].
+    ///
     /// # Panic
     /// This methods will panic if the index is out of bounds
     /// or if the bit width is [incompatible with unaligned
@@ -439,30 +900,114 @@ impl<W: Word, B: AsRef<[W]>> BitFieldVec<W, B> {
     pub fn get_unaligned(&self, index: usize) -> W {
         panic_if_out_of_bounds!(index, self.len);
         assert!(
-            self.bit_width % 8 != 3
-                && self.bit_width % 8 != 5
-                && self.bit_width != 6
-                && self.bit_width != 7
+            Self::is_unaligned_compatible(self.bit_width),
+            "Bit width {} is incompatible with unaligned accesses",
+            self.bit_width
         );
-        unsafe { self.get_unaligned_unchecked(index) }
+        if self.is_unaligned_in_storage(index) {
+            unsafe { self.get_unaligned_unchecked(index) }
+        } else {
+            unsafe { self.get_unchecked(index) }
+        }
     }
 
     /// Like [`BitFieldSlice::get`], but using unaligned reads.
     ///
     /// # Safety
     /// This methods can be used only if the `bit width % 8` is not
-    /// 3, 5, 6, or 7.
+    /// 3, 5, 6, or 7, and if the `W::BYTES` bytes starting from the byte
+    /// containing the first bit of the element are within the underlying storage.
     pub unsafe fn get_unaligned_unchecked(&self, index: usize) -> W {
-        debug_assert!(
-            self.bit_width % 8 != 3
-                && self.bit_width % 8 != 5
-                && self.bit_width != 6
-                && self.bit_width != 7
-        );
+        debug_assert!(Self::is_unaligned_compatible(self.bit_width));
+        let pos = index * self.bit_width;
         let base_ptr = self.data.as_ref().as_ptr() as *const u8;
-        let ptr = base_ptr.add(index / W::BYTES) as *const W;
+        let ptr = base_ptr.add(pos / 8) as *const W;
         let word = core::ptr::read_unaligned(ptr);
-        (word >> (index % W::BITS)) & self.mask
+        (word >> (pos % 8)) & self.mask
+    }
+
+    /// Return the index of the first element that is greater than `value`,
+    /// or the length of the vector if there is no such element.
+    ///
+    /// This method assumes that the vector is monotone (e.g., it contains
+    /// a cumulative histogram), and uses a binary search: on a cumulative
+    /// histogram, the result is the bucket containing the quantile of
+    /// rank `value`. If the vector is not monotone, the result is unspecified.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// // Cumulative histogram of the counts 2, 0, 3, 1
+    /// let mut hist = BitFieldVec::<usize>::new(3, 4);
+    /// for (i, v) in [2, 2, 5, 6].into_iter().enumerate() {
+    ///     hist.set(i, v);
+    /// }
+    /// assert_eq!(hist.quantile_index(0), 0);
+    /// assert_eq!(hist.quantile_index(2), 2);
+    /// assert_eq!(hist.quantile_index(5), 3);
+    /// assert_eq!(hist.quantile_index(6), 4);
+    /// ```
+    pub fn quantile_index(&self, value: W) -> usize {
+        let mut low = 0;
+        let mut high = self.len;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            // SAFETY: mid is smaller than high, which is at most len.
+            if unsafe { self.get_unchecked(mid) } <= value {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// Search a value in the vector, with the same semantics of
+    /// [`slice::binary_search`].
+    ///
+    /// The vector must be sorted in non-decreasing order: this condition
+    /// is not checked, and if it is not satisfied the result is unspecified.
+    ///
+    /// Return `Ok(index)` if the element of index `index` is equal to `value`
+    /// (if there are multiple matches, any one of them can be returned), or
+    /// `Err(index)` if `value` is not present, where `index` is the position at
+    /// which `value` could be inserted while maintaining the order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let vec: BitFieldVec = [1, 3, 3, 7].into_iter().collect();
+    /// assert_eq!(vec.binary_search(7), Ok(3));
+    /// assert_eq!(vec.binary_search(5), Err(3));
+    /// assert_eq!(vec.binary_search(0), Err(0));
+    /// ```
+    pub fn binary_search(&self, value: W) -> core::result::Result<usize, usize> {
+        self.binary_search_by(|x| x.cmp(&value))
+    }
+
+    /// Search the vector using a comparator function, with the same semantics of
+    /// [`slice::binary_search_by`].
+    ///
+    /// The comparator receives the value of an element and must return
+    /// whether the element is less than, equal to or greater than the target.
+    /// The vector must be sorted consistently with the comparator: this condition
+    /// is not checked, and if it is not satisfied the result is unspecified.
+    pub fn binary_search_by<F>(&self, mut f: F) -> core::result::Result<usize, usize>
+    where
+        F: FnMut(W) -> core::cmp::Ordering,
+    {
+        let mut low = 0;
+        let mut high = self.len;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            // SAFETY: mid is smaller than high, which is at most len.
+            match f(unsafe { self.get_unchecked(mid) }) {
+                core::cmp::Ordering::Less => low = mid + 1,
+                core::cmp::Ordering::Greater => high = mid,
+                core::cmp::Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(low)
     }
 }
 
@@ -779,6 +1324,12 @@ impl<W: Word + IntoAtomic, B> AtomicBitFieldVec<W, B> {
     }
 }
 
+impl<W: Word, B: AsRef<[W]>> Advise for BitFieldVec<W, B> {
+    fn advise(&self, advice: Advice) -> Result<()> {
+        advise_slice(self.data.as_ref(), advice)
+    }
+}
+
 impl<W: Word, T> BitFieldSliceCore<W> for BitFieldVec<W, T> {
     #[inline(always)]
     fn bit_width(&self) -> usize {
@@ -820,10 +1371,48 @@ impl<W: Word, B: AsRef<[W]>> BitFieldSlice<W> for BitFieldVec<W, B> {
                 & self.mask
         }
     }
+
+    #[inline(always)]
+    fn as_words(&self) -> Option<&[W]> {
+        Some(self.data.as_ref())
+    }
 }
 
 // Support for unchecked iterators
 
+/// Return the next value of a forward scan of the words of a vector of bit
+/// fields.
+///
+/// `window` contains in its lowest bits the `fill` bits of the current word that
+/// have not been returned yet; if they are not sufficient, the next word is
+/// obtained from `next_word`. This logic is shared by
+/// [`BitFieldVectorUncheckedIterator`] and [`AtomicBitFieldVecIterator`].
+#[inline(always)]
+fn next_in_window<W: Word>(
+    window: &mut W,
+    fill: &mut usize,
+    bit_width: usize,
+    mask: W,
+    next_word: impl FnOnce() -> W,
+) -> W {
+    if *fill >= bit_width {
+        *fill -= bit_width;
+        let res = *window & mask;
+        // Two shifts, as the bit width might be W::BITS
+        *window = *window >> (bit_width / 2) >> (bit_width - bit_width / 2);
+        return res;
+    }
+
+    let res = *window;
+    *window = next_word();
+    let res = (res | (*window << *fill)) & mask;
+    let used = bit_width - *fill;
+    // Here used > 0, but it might be W::BITS
+    *window = *window >> (used - 1) >> 1;
+    *fill = W::BITS - used;
+    res
+}
+
 /// An [`UncheckedIterator`] over the values of a [`BitFieldVec`].
 #[derive(Debug, Clone, MemDbg, MemSize)]
 pub struct BitFieldVectorUncheckedIterator<'a, W, B>
@@ -871,23 +1460,18 @@ impl<'a, W: Word, B: AsRef<[W]>> crate::traits::UncheckedIterator
 {
     type Item = W;
     unsafe fn next_unchecked(&mut self) -> W {
-        let bit_width = self.vec.bit_width;
-
-        if self.fill >= bit_width {
-            self.fill -= bit_width;
-            let res = self.window & self.vec.mask;
-            self.window >>= bit_width;
-            return res;
-        }
-
-        let res = self.window;
-        self.word_index += 1;
-        self.window = *self.vec.data.as_ref().get_unchecked(self.word_index);
-        let res = (res | (self.window << self.fill)) & self.vec.mask;
-        let used = bit_width - self.fill;
-        self.window >>= used;
-        self.fill = W::BITS - used;
-        res
+        let data = self.vec.data.as_ref();
+        let word_index = &mut self.word_index;
+        next_in_window(
+            &mut self.window,
+            &mut self.fill,
+            self.vec.bit_width,
+            self.vec.mask,
+            || {
+                *word_index += 1;
+                *data.get_unchecked(*word_index)
+            },
+        )
     }
 }
 
@@ -957,8 +1541,9 @@ impl<'a, W: Word, B: AsRef<[W]>> crate::traits::UncheckedIterator
         self.word_index -= 1;
         self.window = *self.vec.data.as_ref().get_unchecked(self.word_index);
         let used = bit_width - self.fill;
-        res = ((res << used) | self.window >> (W::BITS - used)) & self.vec.mask;
-        self.window <<= used;
+        // Here used > 0, but it might be W::BITS
+        res = ((res << (used - 1) << 1) | self.window >> (W::BITS - used)) & self.vec.mask;
+        self.window = self.window << (used - 1) << 1;
         self.fill = W::BITS - used;
         res
     }
@@ -1058,6 +1643,26 @@ impl<'a, W: Word, B: AsRef<[W]>> Iterator for BitFieldVecIterator<'a, W, B> {
         self.start += 1;
         Some(res)
     }
+
+    /// Return the `n`-th next element, repositioning the underlying
+    /// unchecked iterator in constant time.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.end.saturating_sub(self.start) {
+            self.start = self.end;
+            return None;
+        }
+        if n != 0 {
+            self.start += n;
+            self.directed = BitFieldVectorUncheckedIterator::new(self.directed.vec, self.start);
+        }
+        self.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end.saturating_sub(self.start);
+        (len, Some(len))
+    }
 }
 
 impl<'a, W: Word, B: AsRef<[W]>> DoubleEndedIterator for BitFieldVecIterator<'a, W, B> {
@@ -1071,6 +1676,21 @@ impl<'a, W: Word, B: AsRef<[W]>> DoubleEndedIterator for BitFieldVecIterator<'a,
         self.end -= 1;
         Some(res)
     }
+
+    /// Return the `n`-th element from the end, repositioning the underlying
+    /// reverse unchecked iterator in constant time.
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.end.saturating_sub(self.start) {
+            self.end = self.start;
+            return None;
+        }
+        if n != 0 {
+            self.end -= n;
+            self.reversed =
+                BitFieldVectorReverseUncheckedIterator::new(self.reversed.vec, self.end);
+        }
+        self.next_back()
+    }
 }
 
 impl<'a, W: Word, B: AsRef<[W]>> ExactSizeIterator for BitFieldVecIterator<'a, W, B> {
@@ -1092,12 +1712,95 @@ impl<'a, W: Word, B: AsRef<[W]>> IntoIterator for &'a BitFieldVec<W, B> {
     }
 }
 
+/// An [`Iterator`] over the pairs of adjacent values of a [`BitFieldVec`].
+///
+/// This is the analogous of [`slice::windows`] with window size two:
+/// each value is decoded just once.
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct BitFieldVecWindows2<'a, W, B>
+where
+    W: Word,
+{
+    iter: BitFieldVectorUncheckedIterator<'a, W, B>,
+    /// The last value returned by `iter`.
+    prev: W,
+    /// The number of pairs still to be returned.
+    remaining: usize,
+}
+
+impl<'a, W: Word, B: AsRef<[W]>> BitFieldVecWindows2<'a, W, B> {
+    fn new(vec: &'a BitFieldVec<W, B>) -> Self {
+        if vec.len() < 2 {
+            return Self {
+                iter: BitFieldVectorUncheckedIterator::new(vec, vec.len()),
+                prev: W::ZERO,
+                remaining: 0,
+            };
+        }
+        let mut iter = BitFieldVectorUncheckedIterator::new(vec, 0);
+        // SAFETY: the vector is not empty.
+        let prev = unsafe { iter.next_unchecked() };
+        Self {
+            iter,
+            prev,
+            remaining: vec.len() - 1,
+        }
+    }
+}
+
+impl<'a, W: Word, B: AsRef<[W]>> Iterator for BitFieldVecWindows2<'a, W, B> {
+    type Item = (W, W);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        // SAFETY: there is at least one value left.
+        let cur = unsafe { self.iter.next_unchecked() };
+        let prev = core::mem::replace(&mut self.prev, cur);
+        self.remaining -= 1;
+        Some((prev, cur))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, W: Word, B: AsRef<[W]>> ExactSizeIterator for BitFieldVecWindows2<'a, W, B> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, W: Word, B: AsRef<[W]>> core::iter::FusedIterator for BitFieldVecWindows2<'a, W, B> {}
+
 impl<W: Word, B: AsRef<[W]>> BitFieldVec<W, B> {
     /// Create a new iterator over the values of a [`BitFieldVec`].
     pub fn iter(&self) -> BitFieldVecIterator<W, B> {
         BitFieldVecIterator::from(self)
     }
 
+    /// Create a new iterator over the pairs `(prev, cur)` of adjacent values
+    /// of a [`BitFieldVec`].
+    ///
+    /// The iterator is empty if the vector has less than two elements;
+    /// otherwise, it returns `len - 1` pairs, decoding each value once.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let vec: BitFieldVec = [1, 4, 9, 16].into_iter().collect();
+    /// let gaps = vec.windows2().map(|(prev, cur)| cur - prev).collect::<Vec<_>>();
+    /// assert_eq!(gaps, [3, 5, 7]);
+    /// ```
+    pub fn windows2(&self) -> BitFieldVecWindows2<'_, W, B> {
+        BitFieldVecWindows2::new(self)
+    }
+
     /// Create a new iterator over the values of a [`BitFieldVec`] within given range.
     ///
     /// # Arguments
@@ -1132,12 +1835,442 @@ impl<W: Word, B: AsRef<[W]>> BitFieldVec<W, B> {
     pub fn iter_from(&self, start: usize) -> BitFieldVecIterator<W, B> {
         BitFieldVecIterator::from_start(self, start)
     }
-}
 
-impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> BitFieldSliceMut<W> for BitFieldVec<W, B> {
-    // We reimplement set as we have the mask in the structure.
-    fn reset(&mut self) {
-        for idx in 0..self.len() {
+    /// Decode the elements in the given range into the first `range.len()`
+    /// elements of `out`.
+    ///
+    /// The values are decoded using the same logic of
+    /// [`BitFieldVectorUncheckedIterator`], so this method is
+    /// much faster than calling [`get`](BitFieldSlice::get) repeatedly.
+    ///
+    /// # Panics
+    /// Panics if the range is not within the bounds of the vector, or if `out`
+    /// is shorter than the range.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let vec: BitFieldVec = (0..100).collect();
+    /// let mut buffer = [0; 4];
+    /// vec.get_range(10..14, &mut buffer);
+    /// assert_eq!(buffer, [10, 11, 12, 13]);
+    /// ```
+    pub fn get_range(&self, range: core::ops::Range<usize>, out: &mut [W]) {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Range out of bounds: {:?} (length {})",
+            range,
+            self.len
+        );
+        let out = &mut out[..range.len()];
+        let mut iter = BitFieldVectorUncheckedIterator::new(self, range.start);
+        for value in out {
+            // SAFETY: the range has been checked
+            *value = unsafe { iter.next_unchecked() };
+        }
+    }
+
+    /// Return a vector containing the values of this vector.
+    ///
+    /// The values are decoded using the same logic of
+    /// [`BitFieldVectorUncheckedIterator`] into a preallocated vector.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let vec: BitFieldVec = (0..5).collect();
+    /// assert_eq!(vec.to_vec(), vec![0, 1, 2, 3, 4]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<W> {
+        self.to_vec_range(0..self.len)
+    }
+
+    /// Return a vector containing the values of this vector in the given range.
+    ///
+    /// # Panics
+    /// Panics if the range is not within the bounds of the vector.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let vec: BitFieldVec = (0..100).collect();
+    /// assert_eq!(vec.to_vec_range(10..14), vec![10, 11, 12, 13]);
+    /// ```
+    pub fn to_vec_range(&self, range: core::ops::Range<usize>) -> Vec<W> {
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Range out of bounds: {:?} (length {})",
+            range,
+            self.len
+        );
+        let mut iter = BitFieldVectorUncheckedIterator::new(self, range.start);
+        let mut result = Vec::with_capacity(range.len());
+        // SAFETY: the range has been checked
+        result.extend((0..range.len()).map(|_| unsafe { iter.next_unchecked() }));
+        result
+    }
+
+    /// Return the sum of the values of the vector, wrapping around
+    /// on overflow.
+    ///
+    /// The sum of an empty vector is zero.
+    pub fn sum(&self) -> W {
+        self.iter()
+            .fold(W::ZERO, |sum, value| sum.wrapping_add(value))
+    }
+
+    /// Return the minimum value of the vector, or `None` if the vector is empty.
+    pub fn min(&self) -> Option<W> {
+        self.iter()
+            .reduce(|min, value| if value < min { value } else { min })
+    }
+
+    /// Return the maximum value of the vector, or `None` if the vector is empty.
+    pub fn max(&self) -> Option<W> {
+        self.iter()
+            .reduce(|max, value| if value > max { value } else { max })
+    }
+
+    /// Return the index of the first element equal to `value`, or `None`
+    /// if there is no such element.
+    ///
+    /// If the bit width is a power of two smaller than the word size, the
+    /// search compares all elements in a word at once using broadword
+    /// operations; otherwise, the elements are scanned using
+    /// [`BitFieldVectorUncheckedIterator`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let vec: BitFieldVec = [5, 3, 8, 3].into_iter().collect();
+    /// assert_eq!(vec.position(3), Some(1));
+    /// assert_eq!(vec.position(4), None);
+    /// ```
+    pub fn position(&self, value: W) -> Option<usize> {
+        if value & self.mask != value {
+            return None;
+        }
+        let bit_width = self.bit_width;
+        if bit_width == 0 || !bit_width.is_power_of_two() || bit_width == W::BITS {
+            return self.iter().position(|x| x == value);
+        }
+
+        let per_word = W::BITS / bit_width;
+        // The lowest bit of each field, and the highest bit of each field
+        let lsbs = W::MAX / self.mask;
+        let msbs = lsbs << (bit_width - 1);
+        let pattern = lsbs * value;
+        let data = self.data.as_ref();
+        let full_words = self.len / per_word;
+        let last_fields = self.len % per_word;
+
+        let num_words = full_words + (last_fields != 0) as usize;
+        for (w, &word) in data[..num_words].iter().enumerate() {
+            let x = word ^ pattern;
+            // The highest bit of a field is set if and only if the field of x is zero
+            let mut zeros = !(((x & !msbs) + !msbs) | x | !msbs);
+            if w == full_words {
+                zeros &= (W::ONE << (last_fields * bit_width)) - W::ONE;
+            }
+            if zeros != W::ZERO {
+                return Some(w * per_word + zeros.trailing_zeros() as usize / bit_width);
+            }
+        }
+        None
+    }
+
+    /// Return whether the vector contains an element equal to `value`.
+    ///
+    /// See [`position`](BitFieldVec::position) for details on the search.
+    pub fn contains(&self, value: W) -> bool {
+        self.position(value).is_some()
+    }
+}
+
+impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> BitFieldVec<W, B> {
+    /// Set all elements in the given range to `value`.
+    ///
+    /// If the bit width divides the number of bits of a word, the part of the
+    /// range made of whole words is filled by writing words containing
+    /// repeated copies of `value`; the elements at the unaligned ends
+    /// are set one by one.
+    ///
+    /// # Panics
+    /// Panics if the range is not within the bounds of the vector,
+    /// or if the value does not fit in the bit width of the vector.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut vec = BitFieldVec::<u8>::fill(4, 10, 3);
+    /// vec.fill_range(2..7, 0);
+    /// assert_eq!(vec.iter().collect::<Vec<_>>(), [3, 3, 0, 0, 0, 0, 0, 3, 3, 3]);
+    /// ```
+    pub fn fill_range(&mut self, range: core::ops::Range<usize>, value: W) {
+        panic_if_value!(value, self.mask, self.bit_width);
+        assert!(
+            range.start <= range.end && range.end <= self.len,
+            "Range out of bounds: {:?} (length {})",
+            range,
+            self.len
+        );
+        let (start, end) = (range.start, range.end);
+        let bit_width = self.bit_width;
+
+        if bit_width == 0 || W::BITS % bit_width != 0 {
+            for index in start..end {
+                // SAFETY: the range and the value have been checked
+                unsafe { self.set_unchecked(index, value) };
+            }
+            return;
+        }
+
+        // Elements in first..last fill whole words
+        let per_word = W::BITS / bit_width;
+        let first = Ord::min(start.next_multiple_of(per_word), end);
+        let last = Ord::max(end - end % per_word, first);
+        for index in (start..first).chain(last..end) {
+            // SAFETY: the range and the value have been checked
+            unsafe { self.set_unchecked(index, value) };
+        }
+        let mut word = value;
+        for _ in 1..per_word {
+            word = (word << bit_width) | value;
+        }
+        self.data.as_mut()[first / per_word..last / per_word].fill(word);
+    }
+
+    /// Copy the values of a [`BitFieldSlice`] into the first `src.len()`
+    /// elements of this vector.
+    ///
+    /// The source can have any bit width, but all its values must fit in the
+    /// bit width of this vector. If the two bit widths are equal and the
+    /// source [exposes its words](BitFieldSlice::as_words), whole words are
+    /// copied.
+    ///
+    /// # Panics
+    /// Panics if the source is longer than this vector, or if some value of
+    /// the source does not fit in the bit width of this vector (in which case
+    /// the vector is left unchanged).
+    pub fn copy_from(&mut self, src: &impl BitFieldSlice<W>) {
+        let len = src.len();
+        assert!(
+            len <= self.len,
+            "Source is longer than the destination: {} > {}",
+            len,
+            self.len
+        );
+        let bit_width = self.bit_width;
+        let mut copied = 0;
+        if src.bit_width() == bit_width {
+            if let Some(words) = src.as_words() {
+                // Copy the words containing only source values
+                let num_words = len * bit_width / W::BITS;
+                self.data.as_mut()[..num_words].copy_from_slice(&words[..num_words]);
+                // With bit width zero there are no words to copy
+                copied = (num_words * W::BITS).checked_div(bit_width).unwrap_or(len);
+            }
+        } else if src.bit_width() > bit_width {
+            for index in 0..len {
+                // SAFETY: index is within bounds
+                let value = unsafe { src.get_unchecked(index) };
+                if value & self.mask != value {
+                    panic!(
+                        "Value {} at index {} does not fit in {} bits",
+                        value, index, bit_width
+                    );
+                }
+            }
+        }
+        for index in copied..len {
+            // SAFETY: index is within bounds and the value has been checked
+            unsafe { self.set_unchecked(index, src.get_unchecked(index)) };
+        }
+    }
+
+    /// Like [`BitFieldSliceMut::set`], but using unaligned reads and writes.
+    ///
+    /// Elements too close to the end of the underlying storage
+    /// are written using [`BitFieldSliceMut::set_unchecked`].
+    ///
+    /// # Panic
+    /// This methods will panic if the index is out of bounds, if the value
+    /// does not fit in the bit width, or if the bit width is [incompatible with
+    /// unaligned accesses](BitFieldVec::get_unaligned_unchecked).
+    pub fn set_unaligned(&mut self, index: usize, value: W) {
+        panic_if_out_of_bounds!(index, self.len);
+        panic_if_value!(value, self.mask, self.bit_width);
+        assert!(
+            Self::is_unaligned_compatible(self.bit_width),
+            "Bit width {} is incompatible with unaligned accesses",
+            self.bit_width
+        );
+        if self.is_unaligned_in_storage(index) {
+            unsafe { self.set_unaligned_unchecked(index, value) }
+        } else {
+            unsafe { self.set_unchecked(index, value) }
+        }
+    }
+
+    /// Like [`BitFieldSliceMut::set_unchecked`], but using unaligned
+    /// reads and writes.
+    ///
+    /// # Safety
+    /// The index must be within bounds and the value must fit in the bit width.
+    /// Moreover, the same restrictions of
+    /// [`get_unaligned_unchecked`](BitFieldVec::get_unaligned_unchecked) apply.
+    pub unsafe fn set_unaligned_unchecked(&mut self, index: usize, value: W) {
+        debug_assert!(Self::is_unaligned_compatible(self.bit_width));
+        let pos = index * self.bit_width;
+        let shift = pos % 8;
+        let base_ptr = self.data.as_mut().as_mut_ptr() as *mut u8;
+        let ptr = base_ptr.add(pos / 8) as *mut W;
+        let word = core::ptr::read_unaligned(ptr);
+        core::ptr::write_unaligned(ptr, (word & !(self.mask << shift)) | (value << shift));
+    }
+
+    /// Swap the elements of index `i` and `j`.
+    ///
+    /// If `i` and `j` are equal, the vector is left unchanged.
+    ///
+    /// # Panics
+    /// May panic if `i` or `j` is not in [0..[len](`BitFieldSliceCore::len`)).
+    pub fn swap(&mut self, i: usize, j: usize) {
+        panic_if_out_of_bounds!(i, self.len);
+        panic_if_out_of_bounds!(j, self.len);
+        unsafe { self.swap_unchecked(i, j) };
+    }
+
+    /// Swap the elements of index `i` and `j` without bounds checking.
+    ///
+    /// # Safety
+    /// `i` and `j` must be in [0..[len](`BitFieldSliceCore::len`)).
+    #[inline]
+    pub unsafe fn swap_unchecked(&mut self, i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        let x = self.get_unchecked(i);
+        let y = self.get_unchecked(j);
+        self.set_unchecked(i, y);
+        self.set_unchecked(j, x);
+    }
+
+    /// Reverse the order of the elements in the given range.
+    ///
+    /// # Safety
+    /// The range must be within the bounds of the vector.
+    unsafe fn reverse_range_unchecked(&mut self, mut start: usize, mut end: usize) {
+        while start + 1 < end {
+            end -= 1;
+            self.swap_unchecked(start, end);
+            start += 1;
+        }
+    }
+
+    /// Reverse the order of the elements of the vector.
+    ///
+    /// If the bit width is a power of two, the words are reversed and then
+    /// the elements within each word are reversed using word-level
+    /// operations; otherwise, elements are swapped one by one.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut vec: BitFieldVec = (0..5).collect();
+    /// vec.reverse();
+    /// assert_eq!(vec.iter().collect::<Vec<_>>(), [4, 3, 2, 1, 0]);
+    /// ```
+    pub fn reverse(&mut self) {
+        let bit_width = self.bit_width;
+        if self.len < 2 || bit_width == 0 {
+            return;
+        }
+        if !bit_width.is_power_of_two() {
+            // SAFETY: the range is the whole vector
+            unsafe { self.reverse_range_unchecked(0, self.len) };
+            return;
+        }
+
+        let per_word = W::BITS / bit_width;
+        let num_words = self.len.div_ceil(per_word);
+        let data = &mut self.data.as_mut()[..num_words];
+        data.reverse();
+        // Swap adjacent blocks of k bits, from half a word down to the bit width
+        let mut k = W::BITS / 2;
+        while k >= bit_width {
+            let mask = W::MAX / ((W::ONE << k) + W::ONE);
+            for word in data.iter_mut() {
+                *word = ((*word >> k) & mask) | ((*word & mask) << k);
+            }
+            k /= 2;
+        }
+        // The unused slots of the last word are now at the start
+        let pad = num_words * per_word - self.len;
+        if pad != 0 {
+            shift_bits_down(data, 0, pad * bit_width);
+        }
+    }
+
+    /// Rotate the vector in place so that the element of index `mid`
+    /// becomes the first element, with the same semantics of
+    /// [`slice::rotate_left`].
+    ///
+    /// Rotating by zero or by the length of the vector leaves the vector unchanged.
+    ///
+    /// # Panics
+    /// Panics if `mid` is greater than the length of the vector.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut vec: BitFieldVec = (0..6).collect();
+    /// vec.rotate_left(2);
+    /// assert_eq!(vec.iter().collect::<Vec<_>>(), [2, 3, 4, 5, 0, 1]);
+    /// vec.rotate_right(2);
+    /// assert_eq!(vec.iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+    /// ```
+    pub fn rotate_left(&mut self, mid: usize) {
+        assert!(
+            mid <= self.len,
+            "Rotation out of bounds: {} > {}",
+            mid,
+            self.len
+        );
+        if mid == 0 || mid == self.len {
+            return;
+        }
+        // SAFETY: all ranges are within bounds
+        unsafe {
+            self.reverse_range_unchecked(0, mid);
+            self.reverse_range_unchecked(mid, self.len);
+            self.reverse_range_unchecked(0, self.len);
+        }
+    }
+
+    /// Rotate the vector in place so that the last `k` elements
+    /// become the first ones, with the same semantics of
+    /// [`slice::rotate_right`].
+    ///
+    /// Rotating by zero or by the length of the vector leaves the vector unchanged.
+    ///
+    /// # Panics
+    /// Panics if `k` is greater than the length of the vector.
+    pub fn rotate_right(&mut self, k: usize) {
+        assert!(
+            k <= self.len,
+            "Rotation out of bounds: {} > {}",
+            k,
+            self.len
+        );
+        self.rotate_left(self.len - k);
+    }
+}
+
+impl<W: Word, B: AsRef<[W]> + AsMut<[W]>> BitFieldSliceMut<W> for BitFieldVec<W, B> {
+    // We reimplement set as we have the mask in the structure.
+    fn reset(&mut self) {
+        for idx in 0..self.len() {
             unsafe { self.set_unchecked(idx, W::ZERO) };
         }
     }
@@ -1400,12 +2533,522 @@ where
     }
 
     fn reset_atomic(&mut self, order: Ordering) {
-        for idx in 0..self.len() {
-            unsafe { self.set_atomic_unchecked(idx, W::ZERO, order) };
+        self.reset_with(order);
+    }
+}
+
+impl<W: Word + IntoAtomic, T: AsRef<[W::AtomicType]>> AtomicBitFieldVec<W, T>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+    /// Write zero to all bits in the vector, storing whole words with the
+    /// given ordering.
+    ///
+    /// Differently from [`reset`](AtomicBitFieldVec::reset), this method
+    /// does not require exclusive access to the vector.
+    ///
+    /// # Panics
+    /// Panics if `order` is not valid for a store operation
+    /// (i.e., it is [`Acquire`](Ordering::Acquire) or [`AcqRel`](Ordering::AcqRel)).
+    pub fn reset_with(&self, order: Ordering) {
+        self.data
+            .as_ref()
+            .iter()
+            .for_each(|word| word.store(W::ZERO, order));
+    }
+
+    /// Set all elements of the vector to `value`.
+    ///
+    /// If the bit width is a power of two, words containing repeated copies
+    /// of `value` are stored directly with the given ordering; otherwise,
+    /// the elements are set one by one using
+    /// [`set_atomic`](AtomicBitFieldSlice::set_atomic).
+    ///
+    /// # Panics
+    /// Panics if `value` does not fit in [`BitFieldSliceCore::bit_width`] bits,
+    /// or if `order` is not valid for a store operation
+    /// (i.e., it is [`Acquire`](Ordering::Acquire) or [`AcqRel`](Ordering::AcqRel)).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// use std::sync::atomic::Ordering;
+    /// let vec = AtomicBitFieldVec::<u32>::new(4, 10);
+    /// vec.fill_atomic(5, Ordering::Relaxed);
+    /// assert!((0..10).all(|i| vec.get_atomic(i, Ordering::Relaxed) == 5));
+    /// ```
+    pub fn fill_atomic(&self, value: W, order: Ordering) {
+        panic_if_value!(value, self.mask, self.bit_width);
+        let bit_width = self.bit_width;
+        if bit_width == 0 {
+            return;
+        }
+        if !bit_width.is_power_of_two() {
+            for index in 0..self.len {
+                // SAFETY: the index is in bounds and the value has been checked
+                unsafe { self.set_atomic_unchecked(index, value, order) };
+            }
+            return;
+        }
+        let mut word = value;
+        let mut filled = bit_width;
+        while filled < W::BITS {
+            word |= word << filled;
+            filled *= 2;
+        }
+        self.data.as_ref().iter().for_each(|w| w.store(word, order));
+    }
+
+    /// Set the element of the slice at the specified index without
+    /// using fences.
+    ///
+    /// This method is analogous to [`AtomicBitFieldSlice::set_atomic`] with
+    /// [relaxed ordering](Ordering::Relaxed), but when the value crosses a word
+    /// boundary the two words are updated with no acquire/release fences: thus,
+    /// it is faster, but other threads might observe the value in an inconsistent state.
+    /// Each word is still updated atomically, so concurrent calls setting
+    /// *different* elements sharing a word do not interfere with one another.
+    ///
+    /// # Panics
+    /// May panic if the index is not in in [0..[len](`BitFieldSliceCore::len`))
+    /// or the value does not fit in [`BitFieldSliceCore::bit_width`] bits.
+    ///
+    /// # Safety
+    /// The caller must guarantee that no other thread accesses the element
+    /// of given index until the threads synchronize, as it happens, for example,
+    /// when each thread initializes a disjoint set of elements (as during the
+    /// construction of a [`VFunc`]).
+    #[inline]
+    pub unsafe fn set_unfenced(&self, index: usize, value: W) {
+        panic_if_out_of_bounds!(index, self.len);
+        panic_if_value!(value, self.mask, self.bit_width);
+        let pos = index * self.bit_width;
+        let word_index = pos / W::BITS;
+        let bit_index = pos % W::BITS;
+        let data: &[W::AtomicType] = self.data.as_ref();
+
+        if bit_index + self.bit_width <= W::BITS {
+            Self::update_word(
+                data.get_unchecked(word_index),
+                !(self.mask << bit_index),
+                value << bit_index,
+            );
+        } else {
+            Self::update_word(
+                data.get_unchecked(word_index),
+                (W::ONE << bit_index) - W::ONE,
+                value << bit_index,
+            );
+            Self::update_word(
+                data.get_unchecked(word_index + 1),
+                !(self.mask >> (W::BITS - bit_index)),
+                value >> (W::BITS - bit_index),
+            );
+        }
+    }
+
+    /// Add `delta` to the element of given index, wrapping around modulo
+    /// 2<sup>[`bit_width`](BitFieldSliceCore::bit_width)</sup>, and return
+    /// the previous value.
+    ///
+    /// If the element is contained in a single word, the update is performed
+    /// by a compare-and-exchange loop on the word, and it is atomic. If the
+    /// element crosses a word boundary, the two words are updated separately,
+    /// and, as discussed in the [module documentation](crate::bits::bit_field_vec),
+    /// concurrent updates of the same element might leave it in an inconsistent
+    /// state. Atomicity is thus guaranteed only if the bit width is a power of two,
+    /// in which case no element crosses a word boundary.
+    ///
+    /// The failure ordering of the compare-and-exchange operations is the
+    /// strongest ordering allowed that is not stronger than `order`.
+    ///
+    /// # Panics
+    /// May panic if the index is not in in [0..[len](`BitFieldSliceCore::len`)).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// use std::sync::atomic::Ordering;
+    /// let vec = AtomicBitFieldVec::<usize>::new(4, 10);
+    /// assert_eq!(vec.fetch_add(3, 5, Ordering::Relaxed), 0);
+    /// assert_eq!(vec.fetch_add(3, 12, Ordering::Relaxed), 5);
+    /// // 5 + 12 wraps around to 1
+    /// assert_eq!(vec.get_atomic(3, Ordering::Relaxed), 1);
+    /// ```
+    #[inline]
+    pub fn fetch_add(&self, index: usize, delta: W, order: Ordering) -> W {
+        panic_if_out_of_bounds!(index, self.len);
+        self.fetch_update_element(index, order, |old| {
+            Some(old.wrapping_add(delta) & self.mask)
+        })
+    }
+
+    /// Store the maximum between `value` and the element of given index into
+    /// the element, and return the previous value.
+    ///
+    /// The element is written only if `value` is larger than its current value.
+    /// Atomicity is guaranteed under the same conditions of
+    /// [`fetch_add`](AtomicBitFieldVec::fetch_add).
+    ///
+    /// # Panics
+    /// May panic if the index is not in in [0..[len](`BitFieldSliceCore::len`))
+    /// or the value does not fit in [`BitFieldSliceCore::bit_width`] bits.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// use std::sync::atomic::Ordering;
+    /// let vec = AtomicBitFieldVec::<u32>::new(10, 4);
+    /// assert_eq!(vec.fetch_max(1, 100, Ordering::Relaxed), 0);
+    /// assert_eq!(vec.fetch_max(1, 50, Ordering::Relaxed), 100);
+    /// assert_eq!(vec.get_atomic(1, Ordering::Relaxed), 100);
+    /// ```
+    #[inline]
+    pub fn fetch_max(&self, index: usize, value: W, order: Ordering) -> W {
+        panic_if_out_of_bounds!(index, self.len);
+        panic_if_value!(value, self.mask, self.bit_width);
+        self.fetch_update_element(index, order, |old| (value > old).then_some(value))
+    }
+
+    /// Store the minimum between `value` and the element of given index into
+    /// the element, and return the previous value.
+    ///
+    /// The element is written only if `value` is smaller than its current value.
+    /// Atomicity is guaranteed under the same conditions of
+    /// [`fetch_add`](AtomicBitFieldVec::fetch_add).
+    ///
+    /// # Panics
+    /// May panic if the index is not in in [0..[len](`BitFieldSliceCore::len`))
+    /// or the value does not fit in [`BitFieldSliceCore::bit_width`] bits.
+    #[inline]
+    pub fn fetch_min(&self, index: usize, value: W, order: Ordering) -> W {
+        panic_if_out_of_bounds!(index, self.len);
+        panic_if_value!(value, self.mask, self.bit_width);
+        self.fetch_update_element(index, order, |old| (value < old).then_some(value))
+    }
+
+    /// Replace the element of given index with `f(old)`, where `old` is its
+    /// current value, unless `f` returns `None`, and return `old`.
+    ///
+    /// If the element crosses a word boundary, the two words are updated separately.
+    #[inline(always)]
+    fn fetch_update_element(&self, index: usize, order: Ordering, f: impl Fn(W) -> Option<W>) -> W {
+        let pos = index * self.bit_width;
+        let word_index = pos / W::BITS;
+        let bit_index = pos % W::BITS;
+        let data: &[W::AtomicType] = self.data.as_ref();
+        let failure = match order {
+            Ordering::Release => Ordering::Relaxed,
+            Ordering::AcqRel => Ordering::Acquire,
+            order => order,
+        };
+
+        if bit_index + self.bit_width <= W::BITS {
+            let word = &data[word_index];
+            let mut current = word.load(failure);
+            loop {
+                let old = (current >> bit_index) & self.mask;
+                let Some(new) = f(old) else {
+                    return old;
+                };
+                match word.compare_exchange_weak(
+                    current,
+                    (current & !(self.mask << bit_index)) | (new << bit_index),
+                    order,
+                    failure,
+                ) {
+                    Ok(_) => return old,
+                    Err(e) => current = e,
+                }
+            }
+        } else {
+            let (low, high) = (&data[word_index], &data[word_index + 1]);
+            let high_mask = self.mask >> (W::BITS - bit_index);
+            let mut current = low.load(failure);
+            loop {
+                let old = ((current >> bit_index) | (high.load(failure) << (W::BITS - bit_index)))
+                    & self.mask;
+                let Some(new) = f(old) else {
+                    return old;
+                };
+                match low.compare_exchange_weak(
+                    current,
+                    (current & ((W::ONE << bit_index) - W::ONE)) | (new << bit_index),
+                    order,
+                    failure,
+                ) {
+                    Ok(_) => {
+                        let mut current = high.load(failure);
+                        loop {
+                            match high.compare_exchange_weak(
+                                current,
+                                (current & !high_mask) | (new >> (W::BITS - bit_index)),
+                                order,
+                                failure,
+                            ) {
+                                Ok(_) => return old,
+                                Err(e) => current = e,
+                            }
+                        }
+                    }
+                    Err(e) => current = e,
+                }
+            }
+        }
+    }
+
+    /// Store `new` into the element of given index if its value is equal
+    /// to `current`.
+    ///
+    /// The return value is a result indicating whether the new value was
+    /// written and containing the previous value of the element, with the
+    /// same semantics of the `compare_exchange` method of atomic integers;
+    /// `success` and `failure` are used as orderings of the
+    /// compare-and-exchange operations on the word containing the element.
+    ///
+    /// Since the operation must be atomic, the element must be contained in a
+    /// single word, which is always true if the bit width is a power of two.
+    ///
+    /// # Panics
+    /// Panics if the element crosses a word boundary. May panic if the index is
+    /// not in in [0..[len](`BitFieldSliceCore::len`)) or `new` does not fit in
+    /// [`BitFieldSliceCore::bit_width`] bits.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// use std::sync::atomic::Ordering;
+    /// let vec = AtomicBitFieldVec::<usize>::new(8, 10);
+    /// let ord = Ordering::Relaxed;
+    /// assert_eq!(vec.compare_exchange(3, 0, 5, ord, ord), Ok(0));
+    /// assert_eq!(vec.compare_exchange(3, 0, 7, ord, ord), Err(5));
+    /// assert_eq!(vec.get_atomic(3, ord), 5);
+    /// ```
+    #[inline]
+    pub fn compare_exchange(
+        &self,
+        index: usize,
+        current: W,
+        new: W,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<W, W> {
+        panic_if_out_of_bounds!(index, self.len);
+        panic_if_value!(new, self.mask, self.bit_width);
+        let pos = index * self.bit_width;
+        let word_index = pos / W::BITS;
+        let bit_index = pos % W::BITS;
+        assert!(
+            bit_index + self.bit_width <= W::BITS,
+            "The element of index {} crosses a word boundary",
+            index
+        );
+
+        let word = &self.data.as_ref()[word_index];
+        let mut word_value = word.load(failure);
+        loop {
+            let old = (word_value >> bit_index) & self.mask;
+            if old != current {
+                return Err(old);
+            }
+            match word.compare_exchange_weak(
+                word_value,
+                (word_value & !(self.mask << bit_index)) | (new << bit_index),
+                success,
+                failure,
+            ) {
+                Ok(_) => return Ok(old),
+                // Either the element or other elements in the word changed
+                Err(e) => word_value = e,
+            }
+        }
+    }
+
+    /// Atomically replace `word` with `(word & keep) | bits` using relaxed ordering.
+    #[inline(always)]
+    fn update_word(word: &W::AtomicType, keep: W, bits: W) {
+        let mut current = word.load(Ordering::Relaxed);
+        loop {
+            match word.compare_exchange_weak(
+                current,
+                (current & keep) | bits,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(e) => current = e,
+            }
         }
     }
 }
 
+impl<W: Word + IntoAtomic, T: AsRef<[W::AtomicType]>> AtomicBitFieldVec<W, T> {
+    /// Return a non-atomic view of this vector borrowing its words.
+    ///
+    /// Differently from [`convert_to`](ConvertTo::convert_to), this method
+    /// does not consume, move or reallocate the vector: the atomic words are
+    /// simply reinterpreted as plain words, so reading from the view does not
+    /// incur in the cost of atomic loads.
+    ///
+    /// # Safety
+    /// The caller must guarantee that no thread writes to the vector while the
+    /// view is alive, and that all previous writes happen-before the call to this
+    /// method, as it happens, for example, when all writing threads have been
+    /// joined (e.g., at the end of a [scope](std::thread::scope)).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// use sux::traits::bit_field_slice::AtomicBitFieldSlice;
+    /// use std::sync::atomic::Ordering;
+    /// let vec = AtomicBitFieldVec::<usize>::new(5, 100);
+    /// std::thread::scope(|s| {
+    ///     for t in 0..4 {
+    ///         let vec = &vec;
+    ///         s.spawn(move || {
+    ///             for i in (t..100).step_by(4) {
+    ///                 vec.set_atomic(i, i % 32, Ordering::Relaxed);
+    ///             }
+    ///         });
+    ///     }
+    /// });
+    /// // SAFETY: all writing threads have been joined
+    /// let view = unsafe { vec.as_non_atomic() };
+    /// assert_eq!(view.get(42), 10);
+    /// ```
+    pub unsafe fn as_non_atomic(&self) -> BitFieldVec<W, &[W]> {
+        let data = self.data.as_ref();
+        BitFieldVec {
+            // SAFETY: atomic types have the same in-memory representation
+            // of the corresponding integer types.
+            data: core::slice::from_raw_parts(data.as_ptr() as *const W, data.len()),
+            bit_width: self.bit_width,
+            mask: self.mask,
+            len: self.len,
+        }
+    }
+}
+
+/// An iterator over the values of an [`AtomicBitFieldVec`] loading
+/// words atomically.
+///
+/// See [`AtomicBitFieldVec::iter`].
+#[derive(Debug, Clone)]
+pub struct AtomicBitFieldVecIterator<'a, W: Word + IntoAtomic, B> {
+    vec: &'a AtomicBitFieldVec<W, B>,
+    order: Ordering,
+    word_index: usize,
+    window: W,
+    fill: usize,
+    remaining: usize,
+}
+
+impl<'a, W: Word + IntoAtomic, B: AsRef<[W::AtomicType]>> AtomicBitFieldVecIterator<'a, W, B>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+    fn new(vec: &'a AtomicBitFieldVec<W, B>, order: Ordering) -> Self {
+        let (window, fill) = if vec.len == 0 {
+            (W::ZERO, 0)
+        } else {
+            (vec.data.as_ref()[0].load(order), W::BITS)
+        };
+        Self {
+            vec,
+            order,
+            word_index: 0,
+            window,
+            fill,
+            remaining: vec.len,
+        }
+    }
+}
+
+impl<'a, W: Word + IntoAtomic, B: AsRef<[W::AtomicType]>> Iterator
+    for AtomicBitFieldVecIterator<'a, W, B>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+    type Item = W;
+
+    #[inline]
+    fn next(&mut self) -> Option<W> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let data = self.vec.data.as_ref();
+        let word_index = &mut self.word_index;
+        let order = self.order;
+        Some(next_in_window(
+            &mut self.window,
+            &mut self.fill,
+            self.vec.bit_width,
+            self.vec.mask,
+            || {
+                *word_index += 1;
+                // SAFETY: the element ends within the words of the vector
+                unsafe { data.get_unchecked(*word_index).load(order) }
+            },
+        ))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, W: Word + IntoAtomic, B: AsRef<[W::AtomicType]>> ExactSizeIterator
+    for AtomicBitFieldVecIterator<'a, W, B>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, W: Word + IntoAtomic, B: AsRef<[W::AtomicType]>> core::iter::FusedIterator
+    for AtomicBitFieldVecIterator<'a, W, B>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+}
+
+impl<W: Word + IntoAtomic, B: AsRef<[W::AtomicType]>> AtomicBitFieldVec<W, B>
+where
+    W::AtomicType: AtomicUnsignedInt + AsBytes,
+{
+    /// Return an iterator over the values of the vector that loads each
+    /// word once, atomically, with the given ordering.
+    ///
+    /// The iterator can be used while other threads write to the vector,
+    /// but it does not return a snapshot of the whole vector: each value is
+    /// decoded from the words as they are loaded. If the bit width is a power
+    /// of two, no value crosses a word boundary, so each value returned was
+    /// stored in the vector at some point; otherwise, a value crossing a word
+    /// boundary might be decoded from the two words while another thread is
+    /// writing it.
+    ///
+    /// # Panics
+    /// Panics if `order` is not valid for a load operation
+    /// (i.e., it is [`Release`](Ordering::Release) or [`AcqRel`](Ordering::AcqRel)).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// use std::sync::atomic::Ordering;
+    /// let vec = AtomicBitFieldVec::<u64>::new(16, 4);
+    /// vec.fetch_add(2, 7, Ordering::Relaxed);
+    /// assert_eq!(vec.iter(Ordering::Relaxed).collect::<Vec<_>>(), [0, 0, 7, 0]);
+    /// ```
+    pub fn iter(&self, order: Ordering) -> AtomicBitFieldVecIterator<'_, W, B> {
+        AtomicBitFieldVecIterator::new(self, order)
+    }
+}
+
 /// Provide conversion from non-atomic to atomic bitfield vectors, provided their
 /// backends are [convertible](ConvertTo) into one another.
 ///
@@ -0,0 +1,327 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Submodule implementing an appendable on-disk format for `BitFieldVec`.
+//!
+//! The format is made of a header containing a magic cookie, a format version,
+//! the bit width and the length of the vector, followed by the words containing
+//! the values. All numbers are stored as little-endian 64-bit integers, and only
+//! the words that contain (possibly part of) a value are stored.
+//!
+//! Since the length is stored in the header, new values can be appended by
+//! rewriting just the last (partial) word, writing the new words, and
+//! patching the length in the header.
+//!
+//! The format can also be decoded sequentially from any [`Read`]
+//! using a [`BitFieldVecReader`].
+
+use super::BitFieldVec;
+use anyhow::{bail, ensure, Context, Result};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const BITS: usize = usize::BITS as usize;
+/// The magic cookie at the start of the header.
+pub const APPENDABLE_MAGIC: u64 = u64::from_le_bytes(*b"suxbfva\0");
+/// The version of the appendable format.
+pub const APPENDABLE_VERSION: u64 = 1;
+/// The size in bytes of the header (magic cookie, version, bit width, and length).
+const HEADER_BYTES: u64 = 32;
+/// The offset in bytes of the length in the header.
+const LEN_OFFSET: u64 = 24;
+
+fn read_usize(reader: &mut impl Read) -> Result<usize> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes) as usize)
+}
+
+fn write_usize(writer: &mut impl Write, value: usize) -> Result<()> {
+    writer.write_all(&(value as u64).to_le_bytes())?;
+    Ok(())
+}
+
+/// Read and check a header, returning the bit width, the length, and the
+/// number of words containing the values.
+fn read_header(reader: &mut impl Read) -> Result<(usize, usize, usize)> {
+    let mut bytes = [0; 8];
+    reader
+        .read_exact(&mut bytes)
+        .context("Cannot read the header")?;
+    let magic = u64::from_le_bytes(bytes);
+    ensure!(
+        magic == APPENDABLE_MAGIC,
+        "Wrong magic cookie {:#018x} (not written by store_appendable?)",
+        magic
+    );
+    reader.read_exact(&mut bytes)?;
+    let version = u64::from_le_bytes(bytes);
+    ensure!(
+        version == APPENDABLE_VERSION,
+        "Unsupported format version {} (expected {})",
+        version,
+        APPENDABLE_VERSION
+    );
+    let bit_width = read_usize(reader)?;
+    let len = read_usize(reader)?;
+    ensure!(bit_width <= BITS, "Invalid bit width: {}", bit_width);
+    let num_bits = len.checked_mul(bit_width).with_context(|| {
+        format!(
+            "Length {} and bit width {} overflow the number of bits",
+            len, bit_width
+        )
+    })?;
+    Ok((bit_width, len, num_bits.div_ceil(BITS)))
+}
+
+fn write_header(writer: &mut impl Write, bit_width: usize, len: usize) -> Result<()> {
+    writer.write_all(&APPENDABLE_MAGIC.to_le_bytes())?;
+    writer.write_all(&APPENDABLE_VERSION.to_le_bytes())?;
+    write_usize(writer, bit_width)?;
+    write_usize(writer, len)
+}
+
+impl BitFieldVec<usize, Vec<usize>> {
+    /// Store this vector in the appendable format.
+    ///
+    /// The resulting file can be extended using [`BitFieldVec::append_to_file`]
+    /// and loaded using [`BitFieldVec::load_appendable`].
+    pub fn store_appendable(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_header(&mut writer, self.bit_width, self.len)?;
+        let num_bits = self.len * self.bit_width;
+        let num_words = num_bits.div_ceil(BITS);
+        let residual = num_bits % BITS;
+        for (i, &word) in self.data[..num_words].iter().enumerate() {
+            // Clear the bits after the last value, as appending relies on them being zero.
+            let word = if i == num_words - 1 && residual != 0 {
+                word & ((1 << residual) - 1)
+            } else {
+                word
+            };
+            write_usize(&mut writer, word)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Load a vector stored in the appendable format.
+    ///
+    /// This method will return an error if the header is invalid, or
+    /// if the file is too short to contain all values.
+    pub fn load_appendable(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+        let (bit_width, len, num_words) = read_header(&mut reader)?;
+        // Check before allocating, as the length might be corrupted
+        ensure!(
+            file_len.saturating_sub(HEADER_BYTES) / 8 >= num_words as u64,
+            "The file is too short to contain {} values of bit width {}",
+            len,
+            bit_width
+        );
+        // We need at least one word to handle the case of bit width zero.
+        let mut data = Vec::with_capacity(num_words.max(1));
+        for _ in 0..num_words {
+            data.push(read_usize(&mut reader)?);
+        }
+        if data.is_empty() {
+            data.push(0);
+        }
+        // SAFETY: data contains len * bit_width bits.
+        Ok(unsafe { Self::from_raw_parts(data, bit_width, len) })
+    }
+
+    /// Append values to a vector stored in the appendable format.
+    ///
+    /// Only the last word of the file (if partially filled) is rewritten:
+    /// the remaining bits of the word are filled with the new values, the new
+    /// words are written at the end of the file, and finally the length in the
+    /// header is updated.
+    ///
+    /// This method will return an error if some value does not fit
+    /// the bit width of the stored vector; in this case, the file is not modified.
+    pub fn append_to_file(
+        path: impl AsRef<Path>,
+        new_values: impl IntoIterator<Item = usize>,
+    ) -> Result<()> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+        let (bit_width, mut len, num_words) = read_header(&mut file)?;
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            usize::MAX >> (BITS - bit_width)
+        };
+
+        let mut bit_pos = (len * bit_width) % BITS;
+        // The first word to write is the partial last word, if any.
+        let first_word = num_words - (bit_pos != 0) as usize;
+        let mut word = if bit_pos != 0 {
+            file.seek(SeekFrom::Start(HEADER_BYTES + first_word as u64 * 8))?;
+            read_usize(&mut file)? & ((1 << bit_pos) - 1)
+        } else {
+            0
+        };
+
+        let mut words = vec![];
+        for value in new_values {
+            if value & !mask != 0 {
+                bail!("Value {} does not fit in {} bits", value, bit_width);
+            }
+            word |= value << bit_pos;
+            bit_pos += bit_width;
+            if bit_pos >= BITS {
+                words.push(word);
+                bit_pos -= BITS;
+                word = if bit_pos == 0 {
+                    0
+                } else {
+                    value >> (bit_width - bit_pos)
+                };
+            }
+            len += 1;
+        }
+        ensure!(
+            len.checked_mul(bit_width).is_some(),
+            "Length {} and bit width {} overflow the number of bits",
+            len,
+            bit_width
+        );
+        if bit_pos != 0 {
+            words.push(word);
+        }
+
+        file.seek(SeekFrom::Start(HEADER_BYTES + first_word as u64 * 8))?;
+        let mut writer = BufWriter::new(&mut file);
+        for word in words {
+            write_usize(&mut writer, word)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        // The length is patched last, so that an interrupted append
+        // leaves the previous content readable.
+        file.seek(SeekFrom::Start(LEN_OFFSET))?;
+        write_usize(&mut file, len)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// A sequential decoder for a [`BitFieldVec`] stored in the appendable format.
+///
+/// After reading the header, the reader returns the values one at a time,
+/// reading words from the underlying [`Read`] only when needed, so
+/// the whole vector is never loaded in memory. If the underlying reader
+/// is not buffered, it is a good idea to wrap it in a [`BufReader`].
+///
+/// Items are of type [`Result<usize>`], as reading from the
+/// underlying reader might fail.
+///
+/// # Examples
+/// ```rust
+/// use sux::prelude::*;
+/// use sux::bits::bit_field_vec::BitFieldVecReader;
+/// # fn main() -> anyhow::Result<()> {
+/// let mut v = BitFieldVec::<usize>::new(5, 0);
+/// v.extend([1, 2, 31]);
+///
+/// let path = std::env::temp_dir().join("bit_field_vec_reader_doctest");
+/// v.store_appendable(&path)?;
+/// let reader = BitFieldVecReader::new(std::fs::File::open(&path)?)?;
+/// assert_eq!(reader.bit_width(), 5);
+/// assert_eq!(reader.collect::<anyhow::Result<Vec<_>>>()?, vec![1, 2, 31]);
+/// # std::fs::remove_file(&path)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BitFieldVecReader<R: Read> {
+    reader: R,
+    bit_width: usize,
+    mask: usize,
+    /// The number of values still to be returned.
+    remaining: usize,
+    /// The bits of the current word not yet returned, in the lowest positions.
+    word: usize,
+    /// The number of valid bits in `word`.
+    fill: usize,
+}
+
+impl<R: Read> BitFieldVecReader<R> {
+    /// Create a reader, reading the header from the given [`Read`].
+    ///
+    /// This method will return an error if the header cannot be read
+    /// or it is invalid.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let (bit_width, len, _) = read_header(&mut reader)?;
+        Ok(Self {
+            reader,
+            bit_width,
+            mask: if bit_width == 0 {
+                0
+            } else {
+                usize::MAX >> (BITS - bit_width)
+            },
+            remaining: len,
+            word: 0,
+            fill: 0,
+        })
+    }
+
+    /// Return the bit width of the values.
+    pub fn bit_width(&self) -> usize {
+        self.bit_width
+    }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn next_value(&mut self) -> Result<usize> {
+        if self.fill >= self.bit_width {
+            let value = self.word & self.mask;
+            // The shift might be by BITS when the bit width is BITS
+            self.word = self.word.checked_shr(self.bit_width as u32).unwrap_or(0);
+            self.fill -= self.bit_width;
+            Ok(value)
+        } else {
+            let next = read_usize(&mut self.reader)?;
+            let value = (self.word | next << self.fill) & self.mask;
+            let used = self.bit_width - self.fill;
+            self.word = next.checked_shr(used as u32).unwrap_or(0);
+            self.fill = BITS - used;
+            Ok(value)
+        }
+    }
+}
+
+impl<R: Read> Iterator for BitFieldVecReader<R> {
+    type Item = Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let value = self.next_value();
+        // Stop after an error
+        self.remaining = if value.is_ok() { self.remaining - 1 } else { 0 };
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<R: Read> ExactSizeIterator for BitFieldVecReader<R> {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
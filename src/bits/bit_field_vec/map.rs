@@ -0,0 +1,258 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Submodule implementing validated memory mapping of a serialized `BitFieldVec`,
+//! both read-only and writable in place.
+
+use super::{mask, AtomicBitFieldVec, BitFieldVec};
+use crate::traits::Word;
+use anyhow::{ensure, Context, Result};
+use common_traits::IntoAtomic;
+use epserde::prelude::*;
+use mmap_rs::{MmapFlags, MmapMut, MmapOptions};
+use std::path::Path;
+
+/// Memory-map a [`BitFieldVec`] serialized with [ε-serde](epserde),
+/// checking that its fields are consistent.
+///
+/// The result is a [`MemCase`] containing a read-only vector backed
+/// by the mapped words. Differently from [`Deserialize::mmap`], this function
+/// checks that the bit width fits the word type, that the cached mask
+/// agrees with the bit width, and that the mapped words
+/// are sufficient to contain all values, returning an error otherwise.
+///
+/// # Examples
+/// ```rust
+/// use epserde::prelude::*;
+/// use sux::prelude::*;
+/// # fn main() -> anyhow::Result<()> {
+/// let mut v = BitFieldVec::<u32>::new(5, 100);
+/// v.set(42, 17);
+///
+/// let path = std::env::temp_dir().join("map_bit_field_vec_doctest");
+/// v.store(&path)?;
+/// let mapped = map_bit_field_vec::<u32>(&path, Flags::empty())?;
+/// assert_eq!(mapped.len(), 100);
+/// assert_eq!(mapped.get(42), 17);
+/// # std::fs::remove_file(&path)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn map_bit_field_vec<W: Word>(
+    path: impl AsRef<Path>,
+    flags: Flags,
+) -> Result<MemCase<BitFieldVec<W, &'static [W]>>>
+where
+    BitFieldVec<W>:
+        TypeHash + ReprHash + for<'a> DeserializeInner<DeserType<'a> = BitFieldVec<W, &'a [W]>>,
+{
+    let mapped = <BitFieldVec<W>>::mmap(path, flags)?;
+    check(&mapped)?;
+    Ok(mapped)
+}
+
+/// Check that the fields of a deserialized [`BitFieldVec`] are consistent.
+fn check<W: Word>(mapped: &BitFieldVec<W, &[W]>) -> Result<()> {
+    ensure!(
+        mapped.bit_width <= W::BITS,
+        "Invalid bit width {} for {}-bit words",
+        mapped.bit_width,
+        W::BITS
+    );
+    ensure!(
+        mapped.mask == mask(mapped.bit_width),
+        "Mask {} is inconsistent with bit width {}",
+        mapped.mask,
+        mapped.bit_width
+    );
+    let needed_bits = mapped
+        .len
+        .checked_mul(mapped.bit_width)
+        .filter(|&bits| bits <= mapped.data.len().saturating_mul(W::BITS));
+    ensure!(
+        needed_bits.is_some(),
+        "{} words are not sufficient to store {} values of bit width {}",
+        mapped.data.len(),
+        mapped.len,
+        mapped.bit_width
+    );
+    Ok(())
+}
+
+/// Memory-map a file in writable, shared mode.
+///
+/// # Safety
+/// The file must not be modified or truncated by other processes or
+/// by other mappings while it is mapped.
+pub(crate) unsafe fn map_file_mut(path: &Path) -> Result<MmapMut> {
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Cannot open {} for writing", path.display()))?;
+    let file_len = file.metadata()?.len() as usize;
+    MmapOptions::new(file_len)?
+        .with_file(&file, 0)
+        .with_flags(MmapFlags::SHARED)
+        .map_mut()
+        .with_context(|| format!("Cannot map {}", path.display()))
+}
+
+/// A [`BitFieldVec`] serialized with [ε-serde](epserde) and memory-mapped
+/// in writable, shared mode, so that modifications are written to the file.
+///
+/// Instances are returned by [`map_bit_field_vec_mut`]. The bit width and the
+/// length of the vector are fixed, but values can be modified in place either
+/// through a [`BitFieldVec`] view returned by [`get_mut`](MappedBitFieldVec::get_mut),
+/// or concurrently through an [`AtomicBitFieldVec`] view returned by
+/// [`as_atomic`](MappedBitFieldVec::as_atomic). Views borrow the mapping,
+/// so they cannot outlive it.
+///
+/// Modifications are written to the file by [`flush`](MappedBitFieldVec::flush),
+/// and when the mapping is dropped.
+pub struct MappedBitFieldVec<W: Word> {
+    mmap: MmapMut,
+    /// The offset in bytes of the words of the vector in the mapping.
+    offset: usize,
+    /// The number of words of the vector.
+    num_words: usize,
+    bit_width: usize,
+    len: usize,
+    _marker: core::marker::PhantomData<W>,
+}
+
+impl<W: Word> MappedBitFieldVec<W> {
+    fn words(&self) -> &[W] {
+        // SAFETY: the words have been validated by ε-serde at construction
+        // time, and the mapping is aligned to a page boundary
+        unsafe {
+            core::slice::from_raw_parts(
+                self.mmap.as_ptr().add(self.offset) as *const W,
+                self.num_words,
+            )
+        }
+    }
+
+    /// Return a read-only view of the vector.
+    pub fn get(&self) -> BitFieldVec<W, &[W]> {
+        // SAFETY: the fields have been checked at construction time
+        unsafe { BitFieldVec::from_raw_parts(self.words(), self.bit_width, self.len) }
+    }
+
+    /// Return a mutable view of the vector.
+    pub fn get_mut(&mut self) -> BitFieldVec<W, &mut [W]> {
+        let (offset, num_words) = (self.offset, self.num_words);
+        // SAFETY: see words()
+        let words = unsafe {
+            core::slice::from_raw_parts_mut(self.mmap.as_mut_ptr().add(offset) as *mut W, num_words)
+        };
+        // SAFETY: the fields have been checked at construction time
+        unsafe { BitFieldVec::from_raw_parts(words, self.bit_width, self.len) }
+    }
+
+    /// Return an atomic view of the vector, which can be shared among threads.
+    ///
+    /// The view borrows mutably the mapping, so no non-atomic view can
+    /// exist at the same time.
+    pub fn as_atomic(&mut self) -> AtomicBitFieldVec<W, &[W::AtomicType]>
+    where
+        W: IntoAtomic,
+    {
+        // SAFETY: atomic types have the same size and alignment of the
+        // corresponding non-atomic types, and see words()
+        let words = unsafe {
+            core::slice::from_raw_parts(
+                self.mmap.as_mut_ptr().add(self.offset) as *const W::AtomicType,
+                self.num_words,
+            )
+        };
+        // SAFETY: the fields have been checked at construction time
+        unsafe { AtomicBitFieldVec::from_raw_parts(words, self.bit_width, self.len) }
+    }
+
+    /// Write synchronously to the file the modifications to the vector.
+    pub fn flush(&self) -> Result<()> {
+        self.mmap
+            .flush(0..self.mmap.len())
+            .context("Cannot flush the mapping")
+    }
+}
+
+/// Flush the modifications, ignoring errors: call
+/// [`flush`](MappedBitFieldVec::flush) explicitly to handle them.
+impl<W: Word> Drop for MappedBitFieldVec<W> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Memory-map in writable, shared mode a [`BitFieldVec`] serialized with
+/// [ε-serde](epserde), checking that its fields are consistent.
+///
+/// The file is deserialized and checked as in [`map_bit_field_vec`], and the
+/// result is a [`MappedBitFieldVec`] providing views that modify the
+/// values of the vector directly in the file.
+///
+/// This function replaces a generic writable version of
+/// [`Deserialize::mmap`]: ε-serde deserializes only immutable views, so
+/// mutable views must be rebuilt, knowing the structure, on the
+/// portion of the mapping containing its data. For this reason, writable
+/// mappings are available only for [`BitFieldVec`] and, through
+/// [`map_bit_vec_mut`](crate::bits::map_bit_vec_mut), for
+/// [`BitVec`](crate::bits::BitVec); other structures must be loaded,
+/// modified, and stored again.
+///
+/// # Safety
+/// The file must not be modified or truncated by other processes or
+/// by other mappings while it is mapped.
+///
+/// # Examples
+/// ```rust
+/// use epserde::prelude::*;
+/// use sux::prelude::*;
+/// # fn main() -> anyhow::Result<()> {
+/// let v = BitFieldVec::<u32>::new(5, 100);
+/// let path = std::env::temp_dir().join("map_bit_field_vec_mut_doctest");
+/// v.store(&path)?;
+///
+/// let mut mapped = unsafe { map_bit_field_vec_mut::<u32>(&path)? };
+/// mapped.get_mut().set(42, 17);
+/// mapped.flush()?;
+/// drop(mapped);
+///
+/// let loaded = <BitFieldVec<u32>>::load_full(&path)?;
+/// assert_eq!(loaded.get(42), 17);
+/// # std::fs::remove_file(&path)?;
+/// # Ok(())
+/// # }
+/// ```
+pub unsafe fn map_bit_field_vec_mut<W: Word>(path: impl AsRef<Path>) -> Result<MappedBitFieldVec<W>>
+where
+    BitFieldVec<W>:
+        TypeHash + ReprHash + for<'a> DeserializeInner<DeserType<'a> = BitFieldVec<W, &'a [W]>>,
+{
+    let mmap = map_file_mut(path.as_ref())?;
+    let (offset, num_words, bit_width, len) = {
+        let mapped = <BitFieldVec<W>>::deserialize_eps(mmap.as_slice())?;
+        check(&mapped)?;
+        let (data, bit_width, len) = mapped.into_raw_parts();
+        (
+            data.as_ptr() as usize - mmap.as_ptr() as usize,
+            data.len(),
+            bit_width,
+            len,
+        )
+    };
+    Ok(MappedBitFieldVec {
+        mmap,
+        offset,
+        num_words,
+        bit_width,
+        len,
+        _marker: core::marker::PhantomData,
+    })
+}
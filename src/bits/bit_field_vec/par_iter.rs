@@ -0,0 +1,105 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Submodule implementing [Rayon](rayon) parallel iteration on `BitFieldVec`.
+//!
+//! The values are split among tasks in contiguous ranges starting at a word
+//! boundary: if the bit width is `w`, every range starts at a multiple of
+//! `W::BITS / gcd(w, W::BITS)`, so that a value crossing a word boundary
+//! never needs to be decoded by two tasks, and each task can scan its
+//! range sequentially using an [`UncheckedIterator`](crate::traits::UncheckedIterator).
+
+use super::BitFieldVec;
+use crate::prelude::*;
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+use rayon::prelude::*;
+
+/// A [parallel iterator](ParallelIterator) over the values of a [`BitFieldVec`].
+///
+/// The values are returned in the same order as
+/// [`BitFieldVec::iter`] when collected. The iterator is not
+/// [indexed](IndexedParallelIterator), as split points must lie on word
+/// boundaries.
+#[derive(Debug, Clone)]
+pub struct BitFieldVecParIter<'a, W: Word, B> {
+    vec: &'a BitFieldVec<W, B>,
+}
+
+/// The producer of [`BitFieldVecParIter`], handling the values in `start..end`.
+struct BitFieldVecProducer<'a, W: Word, B> {
+    vec: &'a BitFieldVec<W, B>,
+    start: usize,
+    end: usize,
+}
+
+impl<'a, W: Word, B: AsRef<[W]>> BitFieldVecProducer<'a, W, B> {
+    /// Return the number of values spanning a whole number of words,
+    /// that is, `W::BITS / gcd(bit_width, W::BITS)`.
+    fn period(&self) -> usize {
+        // W::BITS is a power of two
+        let shift = (self.vec.bit_width.trailing_zeros() as usize).min(W::BITS.ilog2() as usize);
+        W::BITS >> shift
+    }
+}
+
+impl<'a, W: Word + Send + Sync, B: AsRef<[W]> + Sync> UnindexedProducer
+    for BitFieldVecProducer<'a, W, B>
+{
+    type Item = W;
+
+    fn split(self) -> (Self, Option<Self>) {
+        // start is a multiple of the period, so mid is, too
+        let period = self.period();
+        let mid = self.start + (self.end - self.start) / 2;
+        let mid = mid - mid % period;
+        if mid <= self.start {
+            return (self, None);
+        }
+        (
+            Self {
+                vec: self.vec,
+                start: self.start,
+                end: mid,
+            },
+            Some(Self {
+                vec: self.vec,
+                start: mid,
+                end: self.end,
+            }),
+        )
+    }
+
+    fn fold_with<F: Folder<W>>(self, folder: F) -> F {
+        folder.consume_iter(self.vec.iter_range(self.start, self.end))
+    }
+}
+
+impl<'a, W: Word + Send + Sync, B: AsRef<[W]> + Sync> ParallelIterator
+    for BitFieldVecParIter<'a, W, B>
+{
+    type Item = W;
+
+    fn drive_unindexed<C: UnindexedConsumer<W>>(self, consumer: C) -> C::Result {
+        let producer = BitFieldVecProducer {
+            vec: self.vec,
+            start: 0,
+            end: self.vec.len(),
+        };
+        bridge_unindexed(producer, consumer)
+    }
+}
+
+impl<'a, W: Word + Send + Sync, B: AsRef<[W]> + Sync> IntoParallelIterator
+    for &'a BitFieldVec<W, B>
+{
+    type Item = W;
+    type Iter = BitFieldVecParIter<'a, W, B>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        BitFieldVecParIter { vec: self }
+    }
+}
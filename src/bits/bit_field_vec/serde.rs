@@ -0,0 +1,90 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Submodule implementing [Serde](::serde) serialization for `BitFieldVec`.
+//!
+//! A vector is serialized as a structure containing the bit width, the length,
+//! and the words necessary to store the values, that is, `⌈len · bit_width /
+//! W::BITS⌉` words. Deserialization checks that the bit width is not larger
+//! than the number of bits of a word and that the number of words is
+//! consistent with the length and the bit width.
+//!
+//! This is unrelated to the zero-copy serialization provided by [ε-serde](epserde),
+//! and it is meant for interoperability with existing Serde pipelines.
+
+use super::{mask, BitFieldVec};
+use crate::prelude::*;
+use ::serde::de::Error;
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize)]
+#[serde(rename = "BitFieldVec")]
+struct SerializedBitFieldVec<'a, W> {
+    bit_width: usize,
+    len: usize,
+    data: &'a [W],
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "BitFieldVec")]
+struct DeserializedBitFieldVec<W> {
+    bit_width: usize,
+    len: usize,
+    data: Vec<W>,
+}
+
+impl<W: Word + Serialize, B: AsRef<[W]>> Serialize for BitFieldVec<W, B> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let num_words = (self.len * self.bit_width).div_ceil(W::BITS);
+        SerializedBitFieldVec {
+            bit_width: self.bit_width,
+            len: self.len,
+            data: &self.data.as_ref()[..num_words],
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, W: Word + Deserialize<'de>> Deserialize<'de> for BitFieldVec<W, Vec<W>> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let DeserializedBitFieldVec {
+            bit_width,
+            len,
+            mut data,
+        } = DeserializedBitFieldVec::deserialize(deserializer)?;
+        if bit_width > W::BITS {
+            return Err(D::Error::custom(format!(
+                "Bit width {} is larger than the number of bits of a word ({})",
+                bit_width,
+                W::BITS
+            )));
+        }
+        let num_words = len
+            .checked_mul(bit_width)
+            .map(|num_bits| num_bits.div_ceil(W::BITS))
+            .ok_or_else(|| D::Error::custom("The number of bits overflows"))?;
+        if data.len() != num_words {
+            return Err(D::Error::custom(format!(
+                "Wrong number of words for {} values of bit width {}: expected {}, found {}",
+                len,
+                bit_width,
+                num_words,
+                data.len()
+            )));
+        }
+        // We need at least one word to handle the case of bit width zero.
+        if data.is_empty() {
+            data.push(W::ZERO);
+        }
+        Ok(BitFieldVec {
+            data,
+            bit_width,
+            mask: mask(bit_width),
+            len,
+        })
+    }
+}
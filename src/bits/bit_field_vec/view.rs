@@ -0,0 +1,156 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Submodule implementing borrowed views on a `BitFieldVec` starting at an arbitrary element.
+
+use super::{mask, BitFieldVec};
+use crate::traits::bit_field_slice::*;
+use mem_dbg::*;
+
+/// An immutable view on a contiguous range of elements of a [`BitFieldVec`].
+///
+/// Since the first element of the range might not start on a word boundary,
+/// besides the underlying words the view stores the bit offset of its first
+/// element in the first word. Views are returned by
+/// [`BitFieldVec::split_at`] and can be further split using
+/// [`BitFieldVecView::split_at`].
+#[derive(Debug, Clone, Copy, MemDbg, MemSize)]
+pub struct BitFieldVecView<'a, W: Word> {
+    /// The words containing the elements of the view.
+    data: &'a [W],
+    /// The position of the first bit of the first element in `data[0]`.
+    bit_offset: usize,
+    /// The bit width of the values stored in the view.
+    bit_width: usize,
+    /// A mask with its lowest `bit_width` bits set to one.
+    mask: W,
+    /// The length of the view.
+    len: usize,
+}
+
+impl<'a, W: Word> BitFieldVecView<'a, W> {
+    /// Return the view on `len` elements of width `bit_width` starting at
+    /// bit position `pos` of `data`.
+    fn new(data: &'a [W], pos: usize, bit_width: usize, len: usize) -> Self {
+        Self {
+            data: &data[pos / W::BITS..],
+            bit_offset: pos % W::BITS,
+            bit_width,
+            mask: mask(bit_width),
+            len,
+        }
+    }
+
+    /// Return the position of the first bit of the first element of the view
+    /// in the first word of the view.
+    pub fn bit_offset(&self) -> usize {
+        self.bit_offset
+    }
+
+    /// Divide the view into two views at the element of index `mid`.
+    ///
+    /// The first view contains the elements of index in `[0..mid)`, the
+    /// second the elements of index in `[mid..len)`.
+    ///
+    /// # Panics
+    /// Panics if `mid` is greater than the length of the view.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        assert!(
+            mid <= self.len,
+            "Split index out of bounds: {} > {}",
+            mid,
+            self.len
+        );
+        (
+            Self::new(self.data, self.bit_offset, self.bit_width, mid),
+            Self::new(
+                self.data,
+                self.bit_offset + mid * self.bit_width,
+                self.bit_width,
+                self.len - mid,
+            ),
+        )
+    }
+
+    /// Return the view as a [`BitFieldVec`] borrowing the underlying words,
+    /// or `None` if the first element of the view does not start on a word boundary.
+    pub fn as_bit_field_vec(&self) -> Option<BitFieldVec<W, &'a [W]>> {
+        if self.bit_offset != 0 {
+            return None;
+        }
+        // SAFETY: the words contain len elements of width bit_width
+        Some(unsafe { BitFieldVec::from_raw_parts(self.data, self.bit_width, self.len) })
+    }
+}
+
+impl<W: Word, B: AsRef<[W]>> BitFieldVec<W, B> {
+    /// Divide the vector into two borrowed [views](BitFieldVecView) at the
+    /// element of index `mid`.
+    ///
+    /// The first view contains the elements of index in `[0..mid)`, the
+    /// second the elements of index in `[mid..len)`. Both views share the
+    /// underlying words of the vector: if `mid * bit_width` is not a multiple
+    /// of the word size, the first element of the second view does not
+    /// start on a word boundary, and the view keeps track of its
+    /// [bit offset](BitFieldVecView::bit_offset).
+    ///
+    /// # Panics
+    /// Panics if `mid` is greater than the length of the vector.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let vec: BitFieldVec = (0..10).collect();
+    /// let (left, right) = vec.split_at(3);
+    /// assert_eq!(left.len(), 3);
+    /// assert_eq!(right.len(), 7);
+    /// assert_eq!(left.get(2), 2);
+    /// assert_eq!(right.get(0), 3);
+    /// ```
+    pub fn split_at(&self, mid: usize) -> (BitFieldVecView<'_, W>, BitFieldVecView<'_, W>) {
+        BitFieldVecView::new(self.data.as_ref(), 0, self.bit_width, self.len).split_at(mid)
+    }
+}
+
+impl<W: Word> BitFieldSliceCore<W> for BitFieldVecView<'_, W> {
+    #[inline(always)]
+    fn bit_width(&self) -> usize {
+        debug_assert!(self.bit_width <= W::BITS);
+        self.bit_width
+    }
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<W: Word> BitFieldSlice<W> for BitFieldVecView<'_, W> {
+    #[inline]
+    unsafe fn get_unchecked(&self, index: usize) -> W {
+        let pos = self.bit_offset + index * self.bit_width;
+        let word_index = pos / W::BITS;
+        let bit_index = pos % W::BITS;
+
+        if bit_index + self.bit_width <= W::BITS {
+            (*self.data.get_unchecked(word_index) >> bit_index) & self.mask
+        } else {
+            (*self.data.get_unchecked(word_index) >> bit_index
+                | *self.data.get_unchecked(word_index + 1) << (W::BITS - bit_index))
+                & self.mask
+        }
+    }
+}
+
+impl<'a, 'b, W: Word> IntoIterator for &'b BitFieldVecView<'a, W> {
+    type Item = W;
+    type IntoIter = BitFieldSliceIterator<'b, W, BitFieldVecView<'a, W>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        BitFieldSliceIterator::new(self, 0)
+    }
+}
@@ -16,36 +16,195 @@ There are three flavors:
 
 These flavors depends on a backend, and presently we provide:
 
-- `BitVec<Vec<usize>>`: a mutable, growable and resizable bit vector;
-- `BitVec<AsRef<[usize]>>`: an immutable bit vector mainly useful for [`epserde`];
-- `BitVec<AsRef<[usize]> + AsMut<[usize]>>`: a mutable (but not resizable) bit
+- `BitVec<W, Vec<W>>`: a mutable, growable and resizable bit vector;
+- `BitVec<W, AsRef<[W]>>`: an immutable bit vector mainly useful for [`epserde`];
+- `BitVec<W, AsRef<[W]> + AsMut<[W]>>`: a mutable (but not resizable) bit
    vector;
-- `CountBitVec<AsRef<[usize]>>`: an immutable bit vector;
+- `CountBitVec<W, AsRef<[W]>>`: an immutable bit vector;
 - `AtomicBitVec<AsRef<[AtomicUsize]>>`: a thread-safe, mutable (but not resizable) bit vector.
 
+The word type `W` of [`BitVec`] and [`CountBitVec`] defaults to `usize`, but any
+unsigned integer type implementing [`Word`] can be used (e.g., `u32` or `u128`).
+Selection requires additionally that `W` implements [`SelectInWord`].
+Note that [`AtomicBitVec`] is available only with `usize` words, and that
+structures building on the `AsRef<[usize]>` view of a bit vector (e.g., the selection
+structures in [`rank_sel`](crate::rank_sel)) require `usize` words.
+
 It is possible to juggle between the three flavors using [`From`].
+
+## Bitwise operations
+
+Bit vectors and counting bit vectors of the same length can be combined
+word by word using the bitwise operators `&`, `|`, `^` (and their assignment
+variants), and complemented using `!`; the number of ones of a
+[`CountBitVec`] is recomputed accordingly:
+
+```rust
+use sux::prelude::*;
+
+let a: BitVec = [true, true, false, false].into_iter().collect();
+let b: BitVec = [true, false, true, false].into_iter().collect();
+assert_eq!((&a & &b).iter_ones().collect::<Vec<_>>(), vec![0]);
+assert_eq!((&a | &b).iter_ones().collect::<Vec<_>>(), vec![0, 1, 2]);
+assert_eq!((&a ^ &b).iter_ones().collect::<Vec<_>>(), vec![1, 2]);
+assert_eq!((!a).iter_ones().collect::<Vec<_>>(), vec![2, 3]);
+```
+
+## Bit order
+
+By default, the bits of a [`BitVec`] are numbered within each word starting
+from the least significant bit ([`Lsb0`]). To read (or write) bit vectors
+coming from systems using the opposite convention, it is possible to specify
+the [`Msb0`] [bit order](BitOrder) as third type parameter, which affects
+[`get`](BitVec::get), [`set`](BitVec::set), [`iter_ones`](BitVec::iter_ones),
+and all other methods accessing single bits:
+
+```rust
+use sux::bits::{BitVec, Msb0};
+
+let lsb = unsafe { BitVec::<u8>::from_raw_parts(vec![0b0000_0110], 8) };
+let msb = unsafe { BitVec::<u8, _, Msb0>::from_raw_parts(vec![0b0000_0110], 8) };
+assert_eq!(lsb.iter_ones().collect::<Vec<_>>(), vec![1, 2]);
+assert_eq!(msb.iter_ones().collect::<Vec<_>>(), vec![5, 6]);
+```
+
+Rank and selection, as well as the conversions to other flavors, are available
+only for the default [`Lsb0`] order. For the same reason, only bit vectors with
+the default order give access to their words through [`AsRef`], so they cannot
+be wrapped in structures for ranking and selection, such as
+[`Rank9`](crate::rank_sel::Rank9), which assume the [`Lsb0`] order:
+
+```compile_fail
+use sux::prelude::*;
+use sux::bits::Msb0;
+
+let msb = unsafe { BitVec::<usize, _, Msb0>::from_raw_parts(vec![0b1001], 4) };
+let rank9 = Rank9::new(msb);
+```
 */
 use anyhow::Result;
-use common_traits::SelectInWord;
+use common_traits::{FromBytes, SelectInWord};
 use core::fmt;
+use epserde::traits::{ReprHash, TypeHash};
 use epserde::*;
 use mem_dbg::*;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 use std::{
-    ops::Index,
+    marker::PhantomData,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, Not, Range},
     sync::atomic::{AtomicUsize, Ordering},
 };
 
-use crate::{prelude::ConvertTo, traits::rank_sel::*};
+use crate::{
+    prelude::ConvertTo,
+    traits::{rank_sel::*, Word},
+};
+mod map;
+pub use map::{map_bit_vec_mut, MappedBitVec};
 
 const BITS: usize = usize::BITS as usize;
 
+/// The order of the bits within the words of a [`BitVec`].
+pub trait BitOrder: TypeHash + ReprHash + fmt::Debug + Clone + Copy + Default + 'static {
+    /// Return the shift of the bit of given index within a word of type `W`.
+    fn shift<W: Word>(index: usize) -> usize;
+    /// Return a word of type `W` in which exactly the first `len` bits are set.
+    ///
+    /// `len` must be smaller than the number of bits of `W`.
+    fn prefix_mask<W: Word>(len: usize) -> W;
+    /// Return the index of the first one in a nonzero word.
+    fn first_one<W: Word>(word: W) -> usize;
+    /// Return the index of the last one in a nonzero word.
+    fn last_one<W: Word>(word: W) -> usize;
+    /// Return the word whose bit of index `i` is the bit of index `i % 8`
+    /// of the byte of index `i / 8`, where the bits of a byte are
+    /// indexed in this order, too.
+    fn word_from_bytes<W: Word + FromBytes>(bytes: W::Bytes) -> W;
+}
+
+/// The least-significant-bit-first [bit order](BitOrder), which is the default.
+#[derive(Debug, Clone, Copy, Default, MemDbg, MemSize)]
+pub struct Lsb0;
+
+/// The most-significant-bit-first [bit order](BitOrder).
+#[derive(Debug, Clone, Copy, Default, MemDbg, MemSize)]
+pub struct Msb0;
+
+impl TypeHash for Lsb0 {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        core::hash::Hash::hash("Lsb0", hasher);
+    }
+}
+
+impl ReprHash for Lsb0 {
+    fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+}
+
+impl TypeHash for Msb0 {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        core::hash::Hash::hash("Msb0", hasher);
+    }
+}
+
+impl ReprHash for Msb0 {
+    fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+}
+
+impl BitOrder for Lsb0 {
+    #[inline(always)]
+    fn shift<W: Word>(index: usize) -> usize {
+        index
+    }
+    #[inline(always)]
+    fn prefix_mask<W: Word>(len: usize) -> W {
+        (W::ONE << len) - W::ONE
+    }
+    #[inline(always)]
+    fn first_one<W: Word>(word: W) -> usize {
+        word.trailing_zeros() as usize
+    }
+    #[inline(always)]
+    fn last_one<W: Word>(word: W) -> usize {
+        W::BITS - 1 - word.leading_zeros() as usize
+    }
+    #[inline(always)]
+    fn word_from_bytes<W: Word + FromBytes>(bytes: W::Bytes) -> W {
+        W::from_le_bytes(bytes)
+    }
+}
+
+impl BitOrder for Msb0 {
+    #[inline(always)]
+    fn shift<W: Word>(index: usize) -> usize {
+        W::BITS - 1 - index
+    }
+    #[inline(always)]
+    fn prefix_mask<W: Word>(len: usize) -> W {
+        !(W::MAX >> len)
+    }
+    #[inline(always)]
+    fn first_one<W: Word>(word: W) -> usize {
+        word.leading_zeros() as usize
+    }
+    #[inline(always)]
+    fn last_one<W: Word>(word: W) -> usize {
+        W::BITS - 1 - word.trailing_zeros() as usize
+    }
+    #[inline(always)]
+    fn word_from_bytes<W: Word + FromBytes>(bytes: W::Bytes) -> W {
+        W::from_be_bytes(bytes)
+    }
+}
+
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
 /// A bit vector.
-pub struct BitVec<B = Vec<usize>> {
+///
+/// The [bit order](BitOrder) `O` defaults to [`Lsb0`].
+pub struct BitVec<W: Word = usize, B = Vec<W>, O: BitOrder = Lsb0> {
     data: B,
     len: usize,
+    _marker: PhantomData<(W, O)>,
 }
 
 #[derive(Debug, Clone, MemDbg, MemSize)]
@@ -55,6 +214,7 @@ pub struct AtomicBitVec<B = Vec<AtomicUsize>> {
     len: usize,
 }
 
+#[cfg(not(feature = "panic_free"))]
 macro_rules! panic_if_out_of_bounds {
     ($index: expr, $len: expr) => {
         if $index >= $len {
@@ -63,14 +223,23 @@ macro_rules! panic_if_out_of_bounds {
     };
 }
 
-impl<B> BitLength for BitVec<B> {
+#[cfg(feature = "panic_free")]
+macro_rules! panic_if_out_of_bounds {
+    ($index: expr, $len: expr) => {
+        if $index >= $len {
+            panic!("Bit index out of bounds")
+        }
+    };
+}
+
+impl<W: Word, B, O: BitOrder> BitLength for BitVec<W, B, O> {
     #[inline(always)]
     fn len(&self) -> usize {
         self.len()
     }
 }
 
-impl<B: AsRef<[usize]>> Index<usize> for BitVec<B> {
+impl<W: Word, B: AsRef<[W]>, O: BitOrder> Index<usize> for BitVec<W, B, O> {
     type Output = bool;
 
     fn index(&self, index: usize) -> &Self::Output {
@@ -81,27 +250,109 @@ impl<B: AsRef<[usize]>> Index<usize> for BitVec<B> {
     }
 }
 
-impl BitVec<Vec<usize>> {
-    /// Create a new bit vector of length `len`.
+impl BitVec {
+    /// Create a new bit vector of length `len` with the default word type
+    /// and bit order.
+    ///
+    /// Use [`BitVec::with_value`] to create a bit vector with a different
+    /// word type or bit order.
     pub fn new(len: usize) -> Self {
-        let n_of_words = (len + BITS - 1) / BITS;
+        Self::with_value(len, false)
+    }
+}
+
+impl<W: Word, O: BitOrder> BitVec<W, Vec<W>, O> {
+    /// Create a new bit vector of length `len` with all bits set to `value`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let bits = BitVec::<u8, _, Msb0>::with_value(10, true);
+    /// assert_eq!(bits.count_ones(), 10);
+    /// ```
+    pub fn with_value(len: usize, value: bool) -> Self {
+        let fill = if value { !W::ZERO } else { W::ZERO };
+        let mut data = vec![fill; len.div_ceil(W::BITS)];
+        let residual = len % W::BITS;
+        if value && residual != 0 {
+            // Bits past the end must be zero
+            data[len / W::BITS] &= O::prefix_mask::<W>(residual);
+        }
         Self {
-            data: vec![0; n_of_words],
+            data,
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create a new bit vector of length `len` from a slice of bytes.
+    ///
+    /// The bit of index `i` is the bit of index `i % 8` of the byte of index
+    /// `i / 8`, where the bits of a byte are indexed following the
+    /// [bit order](BitOrder): with the default [`Lsb0`] order, the bit of index
+    /// `i % 8` is the one of value `1 << (i % 8)`, whereas with [`Msb0`] it is
+    /// the one of value `0x80 >> (i % 8)`. The bytes are copied, so there
+    /// is no alignment requirement; bytes beyond those containing the first
+    /// `len` bits are ignored, and the bits beyond `len` in the last word are
+    /// set to zero.
+    ///
+    /// # Errors
+    /// Returns an error if `bytes` contains less than `len` bits.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let bits = BitVec::<u64>::from_bytes(&[0b0000_0101, 0b1000_0000], 16)?;
+    /// assert_eq!(bits.iter_ones().collect::<Vec<_>>(), vec![0, 2, 15]);
+    /// let bits = BitVec::<u64>::from_bytes(&[0b0000_0101, 0b1000_0000], 10)?;
+    /// assert_eq!(bits.iter_ones().collect::<Vec<_>>(), vec![0, 2]);
+    /// let bits = BitVec::<u64, Vec<u64>, Msb0>::from_bytes(&[0b0000_0101, 0b1000_0000], 10)?;
+    /// assert_eq!(bits.iter_ones().collect::<Vec<_>>(), vec![5, 7, 8]);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn from_bytes(bytes: &[u8], len: usize) -> Result<Self>
+    where
+        W: FromBytes,
+    {
+        anyhow::ensure!(
+            len <= bytes.len() * 8,
+            "Not enough bytes for {} bits: {} bytes provided",
             len,
+            bytes.len()
+        );
+        let mut data = bytes[..len.div_ceil(8)]
+            .chunks(W::BYTES)
+            .map(|chunk| {
+                let mut word_bytes = W::Bytes::default();
+                word_bytes.as_mut()[..chunk.len()].copy_from_slice(chunk);
+                O::word_from_bytes::<W>(word_bytes)
+            })
+            .collect::<Vec<W>>();
+        let residual = len % W::BITS;
+        if residual != 0 {
+            let last = data.len() - 1;
+            data[last] &= O::prefix_mask::<W>(residual);
         }
+        Ok(Self {
+            data,
+            len,
+            _marker: PhantomData,
+        })
     }
 
     pub fn capacity(&self) -> usize {
-        self.data.capacity() * BITS
+        self.data.capacity() * W::BITS
     }
 
     pub fn push(&mut self, b: bool) {
-        if self.data.len() * usize::BITS as usize == self.len {
-            self.data.push(0);
+        if self.data.len() * W::BITS == self.len {
+            self.data.push(W::ZERO);
+        }
+        let word_index = self.len / W::BITS;
+        let bit_index = self.len % W::BITS;
+        if b {
+            self.data[word_index] |= W::ONE << O::shift::<W>(bit_index);
         }
-        let word_index = self.len / BITS;
-        let bit_index = self.len % BITS;
-        self.data[word_index] |= (b as usize) << bit_index;
         self.len += 1;
     }
 
@@ -111,13 +362,42 @@ impl BitVec<Vec<usize>> {
         }
     }
 
+    /// Append `n` zeros, growing the bit vector as needed.
+    pub fn push_zeros(&mut self, n: usize) {
+        self.push_run(n, false);
+    }
+
+    /// Append `n` ones, growing the bit vector as needed.
+    pub fn push_ones(&mut self, n: usize) {
+        self.push_run(n, true);
+    }
+
+    /// Append `n` copies of `value`, filling whole words at a time.
+    fn push_run(&mut self, n: usize, value: bool) {
+        let new_len = self.len + n;
+        let fill = if value { !W::ZERO } else { W::ZERO };
+        let first_full_word = self.len.div_ceil(W::BITS);
+        self.data.resize(new_len.div_ceil(W::BITS), W::ZERO);
+        self.data[first_full_word..].fill(fill);
+
+        let bit_index = self.len % W::BITS;
+        if bit_index != 0 {
+            let keep = O::prefix_mask::<W>(bit_index);
+            let word = &mut self.data[self.len / W::BITS];
+            *word = (*word & keep) | (fill & !keep);
+        }
+        let last_bits = new_len % W::BITS;
+        if last_bits != 0 {
+            // Bits past the end must be zero
+            self.data[new_len / W::BITS] &= O::prefix_mask::<W>(last_bits);
+        }
+        self.len = new_len;
+    }
+
     pub fn resize(&mut self, new_len: usize, value: bool) {
         if new_len > self.len {
-            if new_len > self.data.len() * usize::BITS as usize {
-                self.data.resize(
-                    (new_len + usize::BITS as usize - 1) / usize::BITS as usize,
-                    0,
-                );
+            if new_len > self.data.len() * W::BITS {
+                self.data.resize(new_len.div_ceil(W::BITS), W::ZERO);
             }
             for i in self.len..new_len {
                 unsafe {
@@ -140,7 +420,7 @@ impl AtomicBitVec<Vec<AtomicUsize>> {
     }
 }
 
-impl<B> BitVec<B> {
+impl<W: Word, B, O: BitOrder> BitVec<W, B, O> {
     #[inline(always)]
     #[allow(clippy::len_without_is_empty)]
     /// Return the number of bits in this bit vector.
@@ -152,7 +432,11 @@ impl<B> BitVec<B> {
     /// bits in `data` (included).
     #[inline(always)]
     pub unsafe fn from_raw_parts(data: B, len: usize) -> Self {
-        Self { data, len }
+        Self {
+            data,
+            len,
+            _marker: PhantomData,
+        }
     }
     #[inline(always)]
     pub fn into_raw_parts(self) -> (B, usize) {
@@ -187,7 +471,7 @@ impl<B> AtomicBitVec<B> {
     }
 }
 
-impl<B: AsRef<[usize]>> BitVec<B> {
+impl<W: Word, B: AsRef<[W]>, O: BitOrder> BitVec<W, B, O> {
     /// Return the number of bits set to 1 in this bit vector.
     ///
     /// If the feature "rayon" is enabled, this function is parallelized.
@@ -203,10 +487,176 @@ impl<B: AsRef<[usize]>> BitVec<B> {
 
         #[cfg(not(feature = "rayon"))]
         {
-            self.data.iter().map(|x| x.count_ones() as usize).sum()
+            self.data
+                .as_ref()
+                .iter()
+                .map(|x| x.count_ones() as usize)
+                .sum()
+        }
+    }
+
+    /// Return the number of bits set to 1 in the given range of positions.
+    ///
+    /// Only the words containing bits in the range are accessed: the
+    /// boundary words are masked, and the words in between are counted
+    /// directly. An empty range (including one whose start is greater
+    /// than its end) contains no ones.
+    ///
+    /// # Panics
+    /// Panics if the end of the range is greater than the length of the bit vector.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let bits: BitVec = (0..200).map(|i| i % 3 == 0).collect();
+    /// assert_eq!(bits.count_ones_in_range(0..3), 1);
+    /// assert_eq!(bits.count_ones_in_range(60..130), 24);
+    /// assert_eq!(bits.count_ones_in_range(10..10), 0);
+    /// ```
+    pub fn count_ones_in_range(&self, range: Range<usize>) -> usize {
+        assert!(
+            range.end <= self.len,
+            "Range end out of bounds: {} > {}",
+            range.end,
+            self.len
+        );
+        count_ones_range::<W, O>(self.data.as_ref(), range.start, range.end)
+    }
+
+    /// Return the position of the first one at or after position `from`,
+    /// or `None` if there is no such one.
+    ///
+    /// The bit vector is scanned word by word, so the cost is proportional
+    /// to the number of words between `from` and the returned position.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let bits: BitVec = (0..1000).map(|i| i == 10 || i == 700).collect();
+    /// assert_eq!(bits.next_set(0), Some(10));
+    /// assert_eq!(bits.next_set(10), Some(10));
+    /// assert_eq!(bits.next_set(11), Some(700));
+    /// assert_eq!(bits.next_set(701), None);
+    /// ```
+    pub fn next_set(&self, from: usize) -> Option<usize> {
+        if from >= self.len {
+            return None;
+        }
+        let words = self.data.as_ref();
+        let num_words = self.len.div_ceil(W::BITS);
+        let mut word_index = from / W::BITS;
+        let mut word = words[word_index] & !O::prefix_mask::<W>(from % W::BITS);
+        loop {
+            if word != W::ZERO {
+                let pos = word_index * W::BITS + O::first_one(word);
+                // Bits beyond the length are not guaranteed to be zero
+                return (pos < self.len).then_some(pos);
+            }
+            word_index += 1;
+            if word_index == num_words {
+                return None;
+            }
+            word = words[word_index];
         }
     }
 
+    /// Return the position of the last one at or before position `from`,
+    /// or `None` if there is no such one.
+    ///
+    /// If `from` is greater than or equal to the length of the bit vector,
+    /// the last one of the bit vector is returned. The bit vector is scanned
+    /// word by word, so the cost is proportional to the number of words
+    /// between the returned position and `from`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let bits: BitVec = (0..1000).map(|i| i == 10 || i == 700).collect();
+    /// assert_eq!(bits.prev_set(999), Some(700));
+    /// assert_eq!(bits.prev_set(700), Some(700));
+    /// assert_eq!(bits.prev_set(699), Some(10));
+    /// assert_eq!(bits.prev_set(9), None);
+    /// ```
+    pub fn prev_set(&self, from: usize) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let from = from.min(self.len - 1);
+        let words = self.data.as_ref();
+        let mut word_index = from / W::BITS;
+        let mask = match from % W::BITS {
+            bit if bit == W::BITS - 1 => W::MAX,
+            bit => O::prefix_mask::<W>(bit + 1),
+        };
+        let mut word = words[word_index] & mask;
+        loop {
+            if word != W::ZERO {
+                return Some(word_index * W::BITS + O::last_one(word));
+            }
+            if word_index == 0 {
+                return None;
+            }
+            word_index -= 1;
+            word = words[word_index];
+        }
+    }
+
+    /// Return an iterator over the positions of the ones in this bit vector.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        ones::<W, O>(self.data.as_ref().iter().copied(), self.len)
+    }
+
+    /// Return an iterator over the positions at which this bit vector
+    /// and `other` differ.
+    ///
+    /// The positions are computed on the fly from the XOR of the
+    /// words of the two bit vectors.
+    ///
+    /// # Panics
+    /// Panics if the two bit vectors have different lengths.
+    pub fn changed_positions<'a, C: AsRef<[W]>>(
+        &'a self,
+        other: &'a BitVec<W, C, O>,
+    ) -> impl Iterator<Item = usize> + 'a {
+        assert_eq!(
+            self.len, other.len,
+            "The bit vectors have different lengths"
+        );
+        let words = self
+            .data
+            .as_ref()
+            .iter()
+            .zip(other.data.as_ref())
+            .map(|(&a, &b)| a ^ b);
+        ones::<W, O>(words, self.len)
+    }
+}
+
+/// Return an iterator over the positions of the ones in the first
+/// `len` bits of a sequence of words, numbered using the bit order `O`.
+fn ones<W: Word, O: BitOrder>(
+    words: impl Iterator<Item = W>,
+    len: usize,
+) -> impl Iterator<Item = usize> {
+    words
+        .take(len.div_ceil(W::BITS))
+        .enumerate()
+        .flat_map(move |(word_index, word)| {
+            let mut word = word;
+            core::iter::from_fn(move || {
+                if word == W::ZERO {
+                    return None;
+                }
+                let bit_index = O::first_one(word);
+                word &= !(W::ONE << O::shift::<W>(bit_index));
+                Some(word_index * W::BITS + bit_index)
+            })
+        })
+        // Bits beyond the length are not guaranteed to be zero
+        .take_while(move |&pos| pos < len)
+}
+
+impl<W: Word, B: AsRef<[W]>> BitVec<W, B> {
     /// Return a [`CountBitVec`] with the same data as this
     /// bit vector and the assuming the given number of ones.
     ///
@@ -214,13 +664,14 @@ impl<B: AsRef<[usize]>> BitVec<B> {
     /// No control is performed on the number of ones, unless
     /// debug assertions are enabled.
     #[inline(always)]
-    pub fn with_count(self, number_of_ones: usize) -> CountBitVec<B> {
+    pub fn with_count(self, number_of_ones: usize) -> CountBitVec<W, B> {
         debug_assert!(number_of_ones <= self.len);
         debug_assert_eq!(number_of_ones, self.count_ones());
         CountBitVec {
             data: self.data,
             len: self.len,
             number_of_ones,
+            _marker: PhantomData,
         }
     }
 }
@@ -253,7 +704,12 @@ impl<B: AsRef<[AtomicUsize]>> AtomicBitVec<B> {
     }
 }
 
-impl<B: AsRef<[usize]>> BitVec<B> {
+impl<W: Word, B: AsRef<[W]>, O: BitOrder> BitVec<W, B, O> {
+    /// Return the underlying words, independently of the bit order.
+    pub(crate) fn words(&self) -> &[W] {
+        self.data.as_ref()
+    }
+
     pub fn get(&self, index: usize) -> bool {
         panic_if_out_of_bounds!(index, self.len);
         unsafe { self.get_unchecked(index) }
@@ -263,13 +719,13 @@ impl<B: AsRef<[usize]>> BitVec<B> {
     ///
     /// `index` must be between 0 (included) and [`BitVec::len`] (excluded).
     pub unsafe fn get_unchecked(&self, index: usize) -> bool {
-        let word_index = index / BITS;
-        let word = self.data.as_ref().get_unchecked(word_index);
-        (word >> (index % BITS)) & 1 != 0
+        let word_index = index / W::BITS;
+        let word = *self.data.as_ref().get_unchecked(word_index);
+        (word >> O::shift::<W>(index % W::BITS)) & W::ONE != W::ZERO
     }
 }
 
-impl<B: AsRef<[usize]> + AsMut<[usize]>> BitVec<B> {
+impl<W: Word, B: AsRef<[W]> + AsMut<[W]>, O: BitOrder> BitVec<W, B, O> {
     pub fn set(&mut self, index: usize, value: bool) {
         panic_if_out_of_bounds!(index, self.len);
         unsafe { self.set_unchecked(index, value) }
@@ -280,38 +736,40 @@ impl<B: AsRef<[usize]> + AsMut<[usize]>> BitVec<B> {
     /// `index` must be between 0 (included) and [`BitVec::len`] (excluded).
     #[inline(always)]
     pub unsafe fn set_unchecked(&mut self, index: usize, value: bool) {
-        let word_index = index / BITS;
-        let bit_index = index % BITS;
-        let data: &mut [usize] = self.data.as_mut();
+        let word_index = index / W::BITS;
+        let bit_index = O::shift::<W>(index % W::BITS);
+        let data: &mut [W] = self.data.as_mut();
         // For constant values, this should be inlined with no test.
         if value {
-            *data.get_unchecked_mut(word_index) |= 1 << bit_index;
+            *data.get_unchecked_mut(word_index) |= W::ONE << bit_index;
         } else {
-            *data.get_unchecked_mut(word_index) &= !(1 << bit_index);
+            *data.get_unchecked_mut(word_index) &= !(W::ONE << bit_index);
         }
     }
 
     pub fn fill(&mut self, value: bool) {
-        let data: &mut [usize] = self.data.as_mut();
+        let len = self.len;
+        let data: &mut [W] = self.data.as_mut();
         if value {
-            let end = self.len / BITS;
-            let residual = self.len % BITS;
-            data[0..end].fill(!0);
+            let end = len / W::BITS;
+            let residual = len % W::BITS;
+            data[0..end].fill(W::MAX);
             if residual != 0 {
-                data[end] = !0 >> (BITS - residual);
+                data[end] = O::prefix_mask(residual);
             }
         } else {
-            data[0..self.len.div_ceil(BITS)].fill(0);
+            data[0..len.div_ceil(W::BITS)].fill(W::ZERO);
         }
     }
 
     pub fn flip(&mut self) {
-        let data: &mut [usize] = self.data.as_mut();
-        let end = self.len / BITS;
-        let residual = self.len % BITS;
-        data[0..end].iter_mut().for_each(|x| *x ^= !0);
+        let len = self.len;
+        let data: &mut [W] = self.data.as_mut();
+        let end = len / W::BITS;
+        let residual = len % W::BITS;
+        data[0..end].iter_mut().for_each(|x| *x ^= W::MAX);
         if residual != 0 {
-            data[end] ^= (1 << residual) - 1;
+            data[end] ^= O::prefix_mask::<W>(residual);
         }
     }
 }
@@ -398,7 +856,7 @@ impl<B: AsRef<[AtomicUsize]>> AtomicBitVec<B> {
     }
 }
 
-impl<B: AsRef<[usize]>> BitCount for BitVec<B> {
+impl<W: Word, B: AsRef<[W]>, O: BitOrder> BitCount for BitVec<W, B, O> {
     fn count(&self) -> usize {
         self.data
             .as_ref()
@@ -408,23 +866,35 @@ impl<B: AsRef<[usize]>> BitCount for BitVec<B> {
     }
 }
 
-impl<B: AsRef<[usize]>> Select for BitVec<B> {
+/// Rank by counting the ones preceding the position: the cost is linear
+/// in the position. Use a ranking structure such as [`Rank9`](crate::rank_sel::Rank9)
+/// for constant-time ranking.
+impl<W: Word, B: AsRef<[W]>, O: BitOrder> Rank for BitVec<W, B, O> {
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        count_ones_range::<W, O>(self.data.as_ref(), 0, pos)
+    }
+}
+
+impl<W: Word, B: AsRef<[W]>, O: BitOrder> RankZero for BitVec<W, B, O> {}
+
+impl<W: Word + SelectInWord, B: AsRef<[W]>> Select for BitVec<W, B> {
     #[inline(always)]
     unsafe fn select_unchecked(&self, rank: usize) -> usize {
         self.select_hinted_unchecked(rank, 0, 0)
     }
 }
 
-unsafe fn select_hinted_unchecked(
-    data: impl AsRef<[usize]>,
+unsafe fn select_hinted_unchecked<W: Word + SelectInWord>(
+    data: impl AsRef<[W]>,
     rank: usize,
     pos: usize,
     rank_at_pos: usize,
 ) -> usize {
-    let mut word_index = pos / BITS;
-    let bit_index = pos % BITS;
+    let mut word_index = pos / W::BITS;
+    let bit_index = pos % W::BITS;
     let mut residual = rank - rank_at_pos;
-    let mut word = (data.as_ref().get_unchecked(word_index) >> bit_index) << bit_index;
+    let mut word = (*data.as_ref().get_unchecked(word_index) >> bit_index) << bit_index;
     loop {
         let bit_count = word.count_ones() as usize;
         if residual < bit_count {
@@ -435,19 +905,19 @@ unsafe fn select_hinted_unchecked(
         residual -= bit_count;
     }
 
-    word_index * BITS + word.select_in_word(residual)
+    word_index * W::BITS + word.select_in_word(residual)
 }
 
-fn select_hinted(
-    data: impl AsRef<[usize]>,
+fn select_hinted<W: Word + SelectInWord>(
+    data: impl AsRef<[W]>,
     rank: usize,
     pos: usize,
     rank_at_pos: usize,
 ) -> Option<usize> {
-    let mut word_index = pos / BITS;
-    let bit_index = pos % BITS;
+    let mut word_index = pos / W::BITS;
+    let bit_index = pos % W::BITS;
     let mut residual = rank - rank_at_pos;
-    let mut word = (data.as_ref().get(word_index)? >> bit_index) << bit_index;
+    let mut word = (*data.as_ref().get(word_index)? >> bit_index) << bit_index;
     loop {
         let bit_count = word.count_ones() as usize;
         if residual < bit_count {
@@ -458,17 +928,17 @@ fn select_hinted(
         residual -= bit_count;
     }
 
-    Some(word_index * BITS + word.select_in_word(residual))
+    Some(word_index * W::BITS + word.select_in_word(residual))
 }
 
-unsafe fn select_zero_hinted_unchecked(
-    data: impl AsRef<[usize]>,
+unsafe fn select_zero_hinted_unchecked<W: Word + SelectInWord>(
+    data: impl AsRef<[W]>,
     rank: usize,
     hint_pos: usize,
     hint_rank: usize,
 ) -> usize {
-    let mut word_index = hint_pos / BITS;
-    let bit_index = hint_pos % BITS;
+    let mut word_index = hint_pos / W::BITS;
+    let bit_index = hint_pos % W::BITS;
     let mut residual = rank - hint_rank;
     let mut word = (!*data.as_ref().get_unchecked(word_index) >> bit_index) << bit_index;
     loop {
@@ -477,24 +947,24 @@ unsafe fn select_zero_hinted_unchecked(
             break;
         }
         word_index += 1;
-        word = !data.as_ref().get_unchecked(word_index);
+        word = !*data.as_ref().get_unchecked(word_index);
         residual -= bit_count;
     }
 
-    word_index * BITS + word.select_in_word(residual)
+    word_index * W::BITS + word.select_in_word(residual)
 }
 
-fn select_zero_hinted(
-    data: impl AsRef<[usize]>,
+fn select_zero_hinted<W: Word + SelectInWord>(
+    data: impl AsRef<[W]>,
     len: usize,
     rank: usize,
     hint_pos: usize,
     hint_rank: usize,
 ) -> Option<usize> {
-    let mut word_index = hint_pos / BITS;
-    let bit_index = hint_pos % BITS;
+    let mut word_index = hint_pos / W::BITS;
+    let bit_index = hint_pos % W::BITS;
     let mut residual = rank - hint_rank;
-    let mut word = (!data.as_ref().get(word_index)? >> bit_index) << bit_index;
+    let mut word = (!*data.as_ref().get(word_index)? >> bit_index) << bit_index;
     loop {
         let bit_count = word.count_ones() as usize;
         if residual < bit_count {
@@ -505,7 +975,7 @@ fn select_zero_hinted(
         residual -= bit_count;
     }
 
-    let result = word_index * BITS + word.select_in_word(residual);
+    let result = word_index * W::BITS + word.select_in_word(residual);
     if result >= len {
         None
     } else {
@@ -513,7 +983,7 @@ fn select_zero_hinted(
     }
 }
 
-impl<B: AsRef<[usize]>> SelectHinted for BitVec<B> {
+impl<W: Word + SelectInWord, B: AsRef<[W]>> SelectHinted for BitVec<W, B> {
     unsafe fn select_hinted_unchecked(
         &self,
         rank: usize,
@@ -528,14 +998,14 @@ impl<B: AsRef<[usize]>> SelectHinted for BitVec<B> {
     }
 }
 
-impl<B: AsRef<[usize]>> SelectZero for BitVec<B> {
+impl<W: Word + SelectInWord, B: AsRef<[W]>> SelectZero for BitVec<W, B> {
     #[inline(always)]
     unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
         self.select_zero_hinted_unchecked(rank, 0, 0)
     }
 }
 
-impl<B: AsRef<[usize]>> SelectZeroHinted for BitVec<B> {
+impl<W: Word + SelectInWord, B: AsRef<[W]>> SelectZeroHinted for BitVec<W, B> {
     unsafe fn select_zero_hinted_unchecked(
         &self,
         rank: usize,
@@ -552,34 +1022,35 @@ impl<B: AsRef<[usize]>> SelectZeroHinted for BitVec<B> {
 
 /// An immutable bit vector with a constant-time implementation of [`BitCount`].
 #[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
-pub struct CountBitVec<B = Vec<usize>> {
+pub struct CountBitVec<W: Word = usize, B = Vec<W>> {
     data: B,
     len: usize,
     number_of_ones: usize,
+    _marker: PhantomData<W>,
 }
 
-impl<B> CountBitVec<B> {
+impl<W: Word, B> CountBitVec<W, B> {
     #[inline(always)]
     fn len(&self) -> usize {
         self.len
     }
 }
 
-impl<B> BitLength for CountBitVec<B> {
+impl<W: Word, B> BitLength for CountBitVec<W, B> {
     #[inline(always)]
     fn len(&self) -> usize {
         self.len()
     }
 }
 
-impl<B> BitCount for CountBitVec<B> {
+impl<W: Word, B> BitCount for CountBitVec<W, B> {
     #[inline(always)]
     fn count(&self) -> usize {
         self.number_of_ones
     }
 }
 
-impl<B: AsRef<[usize]>> Index<usize> for CountBitVec<B> {
+impl<W: Word, B: AsRef<[W]>> Index<usize> for CountBitVec<W, B> {
     type Output = bool;
 
     fn index(&self, index: usize) -> &Self::Output {
@@ -590,7 +1061,7 @@ impl<B: AsRef<[usize]>> Index<usize> for CountBitVec<B> {
     }
 }
 
-impl<B> CountBitVec<B> {
+impl<W: Word, B> CountBitVec<W, B> {
     /// # Safety
     /// `len` must be between 0 (included) the number of
     /// bits in `data` (included). No test is performed
@@ -601,6 +1072,7 @@ impl<B> CountBitVec<B> {
             data,
             len,
             number_of_ones,
+            _marker: PhantomData,
         }
     }
     #[inline(always)]
@@ -609,7 +1081,23 @@ impl<B> CountBitVec<B> {
     }
 }
 
-impl<B: AsRef<[usize]>> CountBitVec<B> {
+impl<W: Word, B: AsRef<[W]>> CountBitVec<W, B> {
+    /// Return the number of bits set to 1 in the given range of positions.
+    ///
+    /// See [`BitVec::count_ones_in_range`].
+    ///
+    /// # Panics
+    /// Panics if the end of the range is greater than the length of the bit vector.
+    pub fn count_ones_in_range(&self, range: Range<usize>) -> usize {
+        assert!(
+            range.end <= self.len,
+            "Range end out of bounds: {} > {}",
+            range.end,
+            self.len
+        );
+        count_ones_range::<W, Lsb0>(self.data.as_ref(), range.start, range.end)
+    }
+
     pub fn get(&self, index: usize) -> bool {
         panic_if_out_of_bounds!(index, self.len);
         unsafe { self.get_unchecked(index) }
@@ -617,20 +1105,77 @@ impl<B: AsRef<[usize]>> CountBitVec<B> {
 
     #[inline(always)]
     unsafe fn get_unchecked(&self, index: usize) -> bool {
-        let word_index = index / BITS;
-        let word = self.data.as_ref().get_unchecked(word_index);
-        (word >> (index % BITS)) & 1 != 0
+        let word_index = index / W::BITS;
+        let word = *self.data.as_ref().get_unchecked(word_index);
+        (word >> (index % W::BITS)) & W::ONE != W::ZERO
+    }
+}
+
+impl<W: Word + SelectInWord, B: AsRef<[W]>> CountBitVec<W, B> {
+    /// Return `parts - 1` increasing bit positions dividing the bit vector
+    /// into `parts` segments containing approximately the same number of ones.
+    ///
+    /// The `k`-th position is the position of the one of rank
+    /// `k` · [`count()`](BitCount::count) / `parts`, so segments contain
+    /// either ⌊`count()` / `parts`⌋ or ⌈`count()` / `parts`⌉ ones. The first
+    /// segment starts at zero, and the last segment ends at the
+    /// [length](BitLength::len) of the bit vector. If there are no ones,
+    /// all positions are equal to the length of the bit vector.
+    ///
+    /// Since each selection starts from the previous position, the cost is
+    /// linear in the length of the bit vector.
+    ///
+    /// # Panics
+    /// Panics if `parts` is zero.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let bits: CountBitVec = [true, true, false, false, true, true, false, true]
+    ///     .into_iter()
+    ///     .collect::<BitVec>()
+    ///     .into();
+    /// assert_eq!(bits.balanced_split_points(2), vec![4]);
+    /// ```
+    pub fn balanced_split_points(&self, parts: usize) -> Vec<usize> {
+        assert!(parts > 0, "The number of parts must be positive");
+        if self.number_of_ones == 0 {
+            return vec![self.len; parts - 1];
+        }
+        let mut points = Vec::with_capacity(parts - 1);
+        let (mut pos, mut rank_at_pos) = (0, 0);
+        for k in 1..parts {
+            let rank = (k * self.number_of_ones) / parts;
+            // SAFETY: rank < number_of_ones as k < parts, and rank_at_pos
+            // is the rank of pos
+            pos = unsafe { self.select_hinted_unchecked(rank, pos, rank_at_pos) };
+            rank_at_pos = rank;
+            points.push(pos);
+        }
+        points
+    }
+}
+
+/// Rank by counting the ones preceding the position: the cost is linear
+/// in the position. Use a ranking structure such as [`Rank9`](crate::rank_sel::Rank9)
+/// for constant-time ranking.
+impl<W: Word, B: AsRef<[W]>> Rank for CountBitVec<W, B> {
+    #[inline(always)]
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        count_ones_range::<W, Lsb0>(self.data.as_ref(), 0, pos)
     }
 }
 
-impl<B: AsRef<[usize]>> Select for CountBitVec<B> {
+impl<W: Word, B: AsRef<[W]>> RankZero for CountBitVec<W, B> {}
+
+impl<W: Word + SelectInWord, B: AsRef<[W]>> Select for CountBitVec<W, B> {
     #[inline(always)]
     unsafe fn select_unchecked(&self, rank: usize) -> usize {
         self.select_hinted_unchecked(rank, 0, 0)
     }
 }
 
-impl<B: AsRef<[usize]>> SelectHinted for CountBitVec<B> {
+impl<W: Word + SelectInWord, B: AsRef<[W]>> SelectHinted for CountBitVec<W, B> {
     unsafe fn select_hinted_unchecked(&self, rank: usize, pos: usize, rank_at_pos: usize) -> usize {
         select_hinted_unchecked(self.data.as_ref(), rank, pos, rank_at_pos)
     }
@@ -640,14 +1185,14 @@ impl<B: AsRef<[usize]>> SelectHinted for CountBitVec<B> {
     }
 }
 
-impl<B: AsRef<[usize]>> SelectZero for CountBitVec<B> {
+impl<W: Word + SelectInWord, B: AsRef<[W]>> SelectZero for CountBitVec<W, B> {
     #[inline(always)]
     unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
         self.select_zero_hinted_unchecked(rank, 0, 0)
     }
 }
 
-impl<B: AsRef<[usize]>> SelectZeroHinted for CountBitVec<B> {
+impl<W: Word + SelectInWord, B: AsRef<[W]>> SelectZeroHinted for CountBitVec<W, B> {
     unsafe fn select_zero_hinted_unchecked(
         &self,
         rank: usize,
@@ -662,13 +1207,197 @@ impl<B: AsRef<[usize]>> SelectZeroHinted for CountBitVec<B> {
     }
 }
 
+/// Return the number of ones in the bits of `words` of position in
+/// `start..end`, numbered using the bit order `O`.
+///
+/// Only the words containing bits in the range are accessed.
+fn count_ones_range<W: Word, O: BitOrder>(words: &[W], start: usize, end: usize) -> usize {
+    if start >= end {
+        return 0;
+    }
+    let first = start / W::BITS;
+    let last = (end - 1) / W::BITS;
+    let first_mask = !O::prefix_mask::<W>(start % W::BITS);
+    let last_mask = match end % W::BITS {
+        0 => W::MAX,
+        residual => O::prefix_mask::<W>(residual),
+    };
+    if first == last {
+        return (words[first] & first_mask & last_mask).count_ones() as usize;
+    }
+    (words[first] & first_mask).count_ones() as usize
+        + words[first + 1..last]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum::<usize>()
+        + (words[last] & last_mask).count_ones() as usize
+}
+
+/// Combine word by word the first `len` bits of `dst` with those of `src`.
+#[inline(always)]
+fn combine_words<W: Word>(dst: &mut [W], src: &[W], len: usize, op: impl Fn(W, W) -> W) {
+    let num_words = len.div_ceil(W::BITS);
+    dst[..num_words]
+        .iter_mut()
+        .zip(&src[..num_words])
+        .for_each(|(a, &b)| *a = op(*a, b));
+}
+
+/// Implement a bitwise operator and its assignment variant for
+/// [bit vectors](BitVec) and [counting bit vectors](CountBitVec) of the same length.
+///
+/// The binary operator is implemented on references, returning a new
+/// vector, and on owned vectors, reusing their storage. The number
+/// of ones of counting bit vectors is recomputed.
+macro_rules! impl_bit_op {
+    ($op:ident, $method:ident, $op_assign:ident, $method_assign:ident, $sym:tt) => {
+        impl<W: Word, B: AsRef<[W]> + AsMut<[W]>, C: AsRef<[W]>, O: BitOrder>
+            $op_assign<&BitVec<W, C, O>> for BitVec<W, B, O>
+        {
+            /// # Panics
+            /// Panics if the two bit vectors have different lengths.
+            fn $method_assign(&mut self, rhs: &BitVec<W, C, O>) {
+                assert_eq!(
+                    self.len, rhs.len,
+                    "The bit vectors have different lengths"
+                );
+                combine_words(self.data.as_mut(), rhs.data.as_ref(), self.len, |a, b| a $sym b);
+            }
+        }
+
+        impl<W: Word, B: AsRef<[W]>, C: AsRef<[W]>, O: BitOrder> $op<&BitVec<W, C, O>>
+            for &BitVec<W, B, O>
+        {
+            type Output = BitVec<W, Vec<W>, O>;
+
+            /// # Panics
+            /// Panics if the two bit vectors have different lengths.
+            fn $method(self, rhs: &BitVec<W, C, O>) -> Self::Output {
+                let mut result = BitVec {
+                    data: self.data.as_ref()[..self.len.div_ceil(W::BITS)].to_vec(),
+                    len: self.len,
+                    _marker: PhantomData,
+                };
+                result.$method_assign(rhs);
+                result
+            }
+        }
+
+        impl<W: Word, C: AsRef<[W]>, O: BitOrder> $op<&BitVec<W, C, O>> for BitVec<W, Vec<W>, O> {
+            type Output = Self;
+
+            /// # Panics
+            /// Panics if the two bit vectors have different lengths.
+            fn $method(mut self, rhs: &BitVec<W, C, O>) -> Self::Output {
+                self.$method_assign(rhs);
+                self
+            }
+        }
+
+        impl<W: Word, B: AsRef<[W]> + AsMut<[W]>, C: AsRef<[W]>> $op_assign<&CountBitVec<W, C>>
+            for CountBitVec<W, B>
+        {
+            /// # Panics
+            /// Panics if the two bit vectors have different lengths.
+            fn $method_assign(&mut self, rhs: &CountBitVec<W, C>) {
+                assert_eq!(
+                    self.len, rhs.len,
+                    "The bit vectors have different lengths"
+                );
+                combine_words(self.data.as_mut(), rhs.data.as_ref(), self.len, |a, b| a $sym b);
+                self.number_of_ones = count_ones_range::<W, Lsb0>(self.data.as_ref(), 0, self.len);
+            }
+        }
+
+        impl<W: Word, B: AsRef<[W]>, C: AsRef<[W]>> $op<&CountBitVec<W, C>> for &CountBitVec<W, B> {
+            type Output = CountBitVec<W, Vec<W>>;
+
+            /// # Panics
+            /// Panics if the two bit vectors have different lengths.
+            fn $method(self, rhs: &CountBitVec<W, C>) -> Self::Output {
+                let mut result = CountBitVec {
+                    data: self.data.as_ref()[..self.len.div_ceil(W::BITS)].to_vec(),
+                    len: self.len,
+                    number_of_ones: self.number_of_ones,
+                    _marker: PhantomData,
+                };
+                result.$method_assign(rhs);
+                result
+            }
+        }
+
+        impl<W: Word, C: AsRef<[W]>> $op<&CountBitVec<W, C>> for CountBitVec<W, Vec<W>> {
+            type Output = Self;
+
+            /// # Panics
+            /// Panics if the two bit vectors have different lengths.
+            fn $method(mut self, rhs: &CountBitVec<W, C>) -> Self::Output {
+                self.$method_assign(rhs);
+                self
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+/// Complement a bit vector in place using [`BitVec::flip`].
+impl<W: Word, O: BitOrder> Not for BitVec<W, Vec<W>, O> {
+    type Output = Self;
+
+    fn not(mut self) -> Self::Output {
+        self.flip();
+        self
+    }
+}
+
+/// Return the complement of a bit vector.
+impl<W: Word, B: AsRef<[W]>, O: BitOrder> Not for &BitVec<W, B, O> {
+    type Output = BitVec<W, Vec<W>, O>;
+
+    fn not(self) -> Self::Output {
+        !BitVec {
+            data: self.data.as_ref()[..self.len.div_ceil(W::BITS)].to_vec(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Complement a counting bit vector in place.
+impl<W: Word> Not for CountBitVec<W, Vec<W>> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        let (data, len, number_of_ones) = self.into_raw_parts();
+        let bits = !unsafe { BitVec::<W, _>::from_raw_parts(data, len) };
+        bits.with_count(len - number_of_ones)
+    }
+}
+
+/// Return the complement of a counting bit vector.
+impl<W: Word, B: AsRef<[W]>> Not for &CountBitVec<W, B> {
+    type Output = CountBitVec<W, Vec<W>>;
+
+    fn not(self) -> Self::Output {
+        !CountBitVec {
+            data: self.data.as_ref()[..self.len.div_ceil(W::BITS)].to_vec(),
+            len: self.len,
+            number_of_ones: self.number_of_ones,
+            _marker: PhantomData,
+        }
+    }
+}
+
 /// Provide conversion from [bit vectors](BitVec) to
 /// [atomic bit vectors](AtomicBitVec) whose backends
 /// are [convertible](ConvertTo).
 ///
 /// Many implementations of this trait are then used to
 /// implement by delegation a corresponding [`From`].
-impl<B, C> ConvertTo<AtomicBitVec<C>> for BitVec<B>
+impl<B, C> ConvertTo<AtomicBitVec<C>> for BitVec<usize, B>
 where
     B: ConvertTo<C>,
 {
@@ -686,28 +1415,29 @@ where
 ///
 /// Many implementations of this trait are then used to
 /// implement by delegation a corresponding [`From`].
-impl<B, C> ConvertTo<BitVec<C>> for AtomicBitVec<B>
+impl<B, C> ConvertTo<BitVec<usize, C>> for AtomicBitVec<B>
 where
     B: ConvertTo<C>,
 {
-    fn convert_to(self) -> Result<BitVec<C>> {
+    fn convert_to(self) -> Result<BitVec<usize, C>> {
         Ok(BitVec {
             len: self.len,
             data: self.data.convert_to()?,
+            _marker: PhantomData,
         })
     }
 }
 
 /// Provide conversion from standard to atomic bit vectors.
-impl From<BitVec<Vec<usize>>> for AtomicBitVec<Vec<AtomicUsize>> {
+impl From<BitVec<usize, Vec<usize>>> for AtomicBitVec<Vec<AtomicUsize>> {
     #[inline]
-    fn from(bm: BitVec<Vec<usize>>) -> Self {
+    fn from(bm: BitVec<usize, Vec<usize>>) -> Self {
         bm.convert_to().unwrap()
     }
 }
 
 /// Provide conversion from standard to atomic bit vectors.
-impl From<AtomicBitVec<Vec<AtomicUsize>>> for BitVec<Vec<usize>> {
+impl From<AtomicBitVec<Vec<AtomicUsize>>> for BitVec<usize, Vec<usize>> {
     #[inline]
     fn from(bm: AtomicBitVec<Vec<AtomicUsize>>) -> Self {
         bm.convert_to().unwrap()
@@ -716,16 +1446,16 @@ impl From<AtomicBitVec<Vec<AtomicUsize>>> for BitVec<Vec<usize>> {
 
 /// Provide conversion from references to standard bit vectors
 /// to references to atomic bit vectors.
-impl<'a> From<BitVec<&'a [usize]>> for AtomicBitVec<&'a [AtomicUsize]> {
+impl<'a> From<BitVec<usize, &'a [usize]>> for AtomicBitVec<&'a [AtomicUsize]> {
     #[inline]
-    fn from(bm: BitVec<&'a [usize]>) -> Self {
+    fn from(bm: BitVec<usize, &'a [usize]>) -> Self {
         bm.convert_to().unwrap()
     }
 }
 
 /// Provide conversion from references to atomic bit vectors
 /// to references to standard bit vectors.
-impl<'a> From<AtomicBitVec<&'a [AtomicUsize]>> for BitVec<&'a [usize]> {
+impl<'a> From<AtomicBitVec<&'a [AtomicUsize]>> for BitVec<usize, &'a [usize]> {
     #[inline]
     fn from(bm: AtomicBitVec<&'a [AtomicUsize]>) -> Self {
         bm.convert_to().unwrap()
@@ -733,37 +1463,39 @@ impl<'a> From<AtomicBitVec<&'a [AtomicUsize]>> for BitVec<&'a [usize]> {
 }
 
 /// Forget the number of ones.
-impl<B> ConvertTo<BitVec<B>> for CountBitVec<B> {
-    fn convert_to(self) -> Result<BitVec<B>> {
+impl<W: Word, B> ConvertTo<BitVec<W, B>> for CountBitVec<W, B> {
+    fn convert_to(self) -> Result<BitVec<W, B>> {
         Ok(BitVec {
             data: self.data,
             len: self.len,
+            _marker: PhantomData,
         })
     }
 }
 
 /// Forget the number of ones.
-impl<B> From<CountBitVec<B>> for BitVec<B> {
-    fn from(cb: CountBitVec<B>) -> Self {
+impl<W: Word, B> From<CountBitVec<W, B>> for BitVec<W, B> {
+    fn from(cb: CountBitVec<W, B>) -> Self {
         cb.convert_to().unwrap()
     }
 }
 
 /// Compute the number of ones and return a [`CountBitVec`].
-impl ConvertTo<CountBitVec<Vec<usize>>> for BitVec<Vec<usize>> {
-    fn convert_to(self) -> Result<CountBitVec<Vec<usize>>> {
+impl<W: Word> ConvertTo<CountBitVec<W, Vec<W>>> for BitVec<W, Vec<W>> {
+    fn convert_to(self) -> Result<CountBitVec<W, Vec<W>>> {
         let number_of_ones = self.count_ones();
         Ok(CountBitVec {
             data: self.data,
             len: self.len,
             number_of_ones,
+            _marker: PhantomData,
         })
     }
 }
 
 /// Compute the number of ones and return a [`CountBitVec`].
-impl From<BitVec<Vec<usize>>> for CountBitVec<Vec<usize>> {
-    fn from(bitmap: BitVec<Vec<usize>>) -> Self {
+impl<W: Word> From<BitVec<W, Vec<W>>> for CountBitVec<W, Vec<W>> {
+    fn from(bitmap: BitVec<W, Vec<W>>) -> Self {
         bitmap.convert_to().unwrap()
     }
 }
@@ -773,36 +1505,40 @@ impl From<BitVec<Vec<usize>>> for CountBitVec<Vec<usize>> {
 ///
 /// Many implementations of this trait are then used to
 /// implement by delegation a corresponding [`From`].
-impl<B, D> ConvertTo<CountBitVec<D>> for CountBitVec<B>
+impl<W: Word, B, D> ConvertTo<CountBitVec<W, D>> for CountBitVec<W, B>
 where
     B: ConvertTo<D>,
 {
-    fn convert_to(self) -> Result<CountBitVec<D>> {
+    fn convert_to(self) -> Result<CountBitVec<W, D>> {
         Ok(CountBitVec {
             number_of_ones: self.number_of_ones,
             len: self.len,
             data: self.data.convert_to()?,
+            _marker: PhantomData,
         })
     }
 }
 
-impl<B: AsRef<[usize]>> AsRef<[usize]> for CountBitVec<B> {
+impl<W: Word, B: AsRef<[W]>> AsRef<[W]> for CountBitVec<W, B> {
     #[inline(always)]
-    fn as_ref(&self) -> &[usize] {
+    fn as_ref(&self) -> &[W] {
         self.data.as_ref()
     }
 }
 
-impl<B: AsRef<[usize]>> AsRef<[usize]> for BitVec<B> {
+// Access to the words is limited to the Lsb0 order, as the structures
+// for ranking and selection consuming them assume it.
+
+impl<W: Word, B: AsRef<[W]>> AsRef<[W]> for BitVec<W, B> {
     #[inline(always)]
-    fn as_ref(&self) -> &[usize] {
+    fn as_ref(&self) -> &[W] {
         self.data.as_ref()
     }
 }
 
-impl<B: AsMut<[usize]>> AsMut<[usize]> for BitVec<B> {
+impl<W: Word, B: AsMut<[W]>> AsMut<[W]> for BitVec<W, B> {
     #[inline(always)]
-    fn as_mut(&mut self) -> &mut [usize] {
+    fn as_mut(&mut self) -> &mut [W] {
         self.data.as_mut()
     }
 }
@@ -814,9 +1550,9 @@ impl<B: AsRef<[AtomicUsize]>> AsRef<[AtomicUsize]> for AtomicBitVec<B> {
     }
 }
 
-impl FromIterator<bool> for BitVec<Vec<usize>> {
+impl<W: Word, O: BitOrder> FromIterator<bool> for BitVec<W, Vec<W>, O> {
     fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
-        let mut res = Self::new(0);
+        let mut res = Self::with_value(0, false);
         res.extend(iter);
         res
     }
@@ -827,7 +1563,7 @@ impl FromIterator<bool> for BitVec<Vec<usize>> {
 pub struct OnesIterator<B> {
     mem_words: B,
     word_idx: usize,
-    /// This is a usize because BitVec is currently implemented only for `Vec<usize>` and `&[usize]`.
+    /// This is a usize because this iterator is implemented only for `AsRef<[usize]>` backends.
     word: usize,
     len: usize,
 }
@@ -874,14 +1610,15 @@ impl<B: AsRef<[usize]>> Iterator for OnesIterator<B> {
 
 // Iterates over the bits as booleans.
 #[derive(Debug, Clone, MemDbg, MemSize)]
-pub struct BitIterator<'a, B> {
+pub struct BitIterator<'a, B, O = Lsb0> {
     mem_words: &'a B,
     next_bit_pos: usize,
     len: usize,
+    _marker: PhantomData<O>,
 }
 
-impl<'a> IntoIterator for &'a BitVec<Vec<usize>> {
-    type IntoIter = BitIterator<'a, Vec<usize>>;
+impl<'a, W: Word, O: BitOrder> IntoIterator for &'a BitVec<W, Vec<W>, O> {
+    type IntoIter = BitIterator<'a, Vec<W>, O>;
     type Item = bool;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -889,26 +1626,27 @@ impl<'a> IntoIterator for &'a BitVec<Vec<usize>> {
             mem_words: &self.data,
             next_bit_pos: 0,
             len: self.len,
+            _marker: PhantomData,
         }
     }
 }
 
-impl<'a> Iterator for BitIterator<'a, Vec<usize>> {
+impl<'a, W: Word, O: BitOrder> Iterator for BitIterator<'a, Vec<W>, O> {
     type Item = bool;
     fn next(&mut self) -> Option<bool> {
         if self.next_bit_pos == self.len {
             return None;
         }
-        let word_idx = self.next_bit_pos / BITS;
-        let bit_idx = self.next_bit_pos % BITS;
+        let word_idx = self.next_bit_pos / W::BITS;
+        let bit_idx = O::shift::<W>(self.next_bit_pos % W::BITS);
         let word = unsafe { *self.mem_words.get_unchecked(word_idx) };
-        let bit = (word >> bit_idx) & 1;
+        let bit = (word >> bit_idx) & W::ONE;
         self.next_bit_pos += 1;
-        Some(bit != 0)
+        Some(bit != W::ZERO)
     }
 }
 
-impl fmt::Display for BitVec<Vec<usize>> {
+impl<W: Word, O: BitOrder> fmt::Display for BitVec<W, Vec<W>, O> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "[")?;
         for b in self {
@@ -0,0 +1,175 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Submodule implementing writable memory mapping of a serialized `BitVec`.
+
+use super::{AtomicBitVec, BitOrder, BitVec, Lsb0};
+use crate::bits::bit_field_vec::map_file_mut;
+use crate::traits::Word;
+use anyhow::{ensure, Context, Result};
+use epserde::prelude::*;
+use mmap_rs::MmapMut;
+use std::path::Path;
+use std::sync::atomic::AtomicUsize;
+
+/// Check that the words of a deserialized [`BitVec`] are sufficient
+/// to contain its bits.
+fn check<W: Word, O: BitOrder>(mapped: &BitVec<W, &[W], O>) -> Result<()> {
+    let num_words = mapped.words().len();
+    ensure!(
+        mapped.len().div_ceil(W::BITS) <= num_words,
+        "{} words are not sufficient to store {} bits",
+        num_words,
+        mapped.len()
+    );
+    Ok(())
+}
+
+/// A [`BitVec`] serialized with [ε-serde](epserde) and memory-mapped
+/// in writable, shared mode, so that modifications are written to the file.
+///
+/// Instances are returned by [`map_bit_vec_mut`]. The length of the bit
+/// vector is fixed, but bits can be modified in place either through a
+/// [`BitVec`] view returned by [`get_mut`](MappedBitVec::get_mut), or, for
+/// the default word type and bit order, concurrently through an
+/// [`AtomicBitVec`] view returned by [`as_atomic`](MappedBitVec::as_atomic).
+/// Views borrow the mapping, so they cannot outlive it.
+///
+/// Modifications are written to the file by [`flush`](MappedBitVec::flush),
+/// and when the mapping is dropped.
+pub struct MappedBitVec<W: Word = usize, O: BitOrder = Lsb0> {
+    mmap: MmapMut,
+    /// The offset in bytes of the words of the bit vector in the mapping.
+    offset: usize,
+    /// The number of words of the bit vector.
+    num_words: usize,
+    len: usize,
+    _marker: core::marker::PhantomData<(W, O)>,
+}
+
+impl<W: Word, O: BitOrder> MappedBitVec<W, O> {
+    fn words(&self) -> &[W] {
+        // SAFETY: the words have been validated by ε-serde at construction
+        // time, and the mapping is aligned to a page boundary
+        unsafe {
+            core::slice::from_raw_parts(
+                self.mmap.as_ptr().add(self.offset) as *const W,
+                self.num_words,
+            )
+        }
+    }
+
+    /// Return a read-only view of the bit vector.
+    pub fn get(&self) -> BitVec<W, &[W], O> {
+        // SAFETY: the length has been checked at construction time
+        unsafe { BitVec::from_raw_parts(self.words(), self.len) }
+    }
+
+    /// Return a mutable view of the bit vector.
+    pub fn get_mut(&mut self) -> BitVec<W, &mut [W], O> {
+        let (offset, num_words) = (self.offset, self.num_words);
+        // SAFETY: see words()
+        let words = unsafe {
+            core::slice::from_raw_parts_mut(self.mmap.as_mut_ptr().add(offset) as *mut W, num_words)
+        };
+        // SAFETY: the length has been checked at construction time
+        unsafe { BitVec::from_raw_parts(words, self.len) }
+    }
+
+    /// Write synchronously to the file the modifications to the bit vector.
+    pub fn flush(&self) -> Result<()> {
+        self.mmap
+            .flush(0..self.mmap.len())
+            .context("Cannot flush the mapping")
+    }
+}
+
+impl MappedBitVec {
+    /// Return an atomic view of the bit vector, which can be shared among threads.
+    ///
+    /// The view borrows mutably the mapping, so no non-atomic view can
+    /// exist at the same time.
+    pub fn as_atomic(&mut self) -> AtomicBitVec<&[AtomicUsize]> {
+        // SAFETY: AtomicUsize has the same size and alignment of usize,
+        // and see words()
+        let words = unsafe {
+            core::slice::from_raw_parts(
+                self.mmap.as_mut_ptr().add(self.offset) as *const AtomicUsize,
+                self.num_words,
+            )
+        };
+        // SAFETY: the length has been checked at construction time
+        unsafe { AtomicBitVec::from_raw_parts(words, self.len) }
+    }
+}
+
+/// Flush the modifications, ignoring errors: call
+/// [`flush`](MappedBitVec::flush) explicitly to handle them.
+impl<W: Word, O: BitOrder> Drop for MappedBitVec<W, O> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Memory-map in writable, shared mode a [`BitVec`] serialized with
+/// [ε-serde](epserde), checking that its words are sufficient to contain
+/// its bits.
+///
+/// The result is a [`MappedBitVec`] providing views that modify the
+/// bits of the vector directly in the file. This is the counterpart of
+/// [`map_bit_field_vec_mut`](crate::bits::map_bit_field_vec_mut) for bit vectors.
+///
+/// # Safety
+/// The file must not be modified or truncated by other processes or
+/// by other mappings while it is mapped.
+///
+/// # Examples
+/// ```rust
+/// use epserde::prelude::*;
+/// use sux::prelude::*;
+/// # fn main() -> anyhow::Result<()> {
+/// let b = BitVec::new(100);
+/// let path = std::env::temp_dir().join("map_bit_vec_mut_doctest");
+/// b.store(&path)?;
+///
+/// let mut mapped = unsafe { map_bit_vec_mut::<usize, Lsb0>(&path)? };
+/// mapped.get_mut().set(42, true);
+/// mapped.flush()?;
+/// drop(mapped);
+///
+/// let loaded = <BitVec>::load_full(&path)?;
+/// assert!(loaded[42]);
+/// # std::fs::remove_file(&path)?;
+/// # Ok(())
+/// # }
+/// ```
+pub unsafe fn map_bit_vec_mut<W: Word, O: BitOrder>(
+    path: impl AsRef<Path>,
+) -> Result<MappedBitVec<W, O>>
+where
+    BitVec<W, Vec<W>, O>:
+        TypeHash + ReprHash + for<'a> DeserializeInner<DeserType<'a> = BitVec<W, &'a [W], O>>,
+{
+    let mmap = map_file_mut(path.as_ref())?;
+    let (offset, num_words, len) = {
+        let mapped = <BitVec<W, Vec<W>, O>>::deserialize_eps(mmap.as_slice())?;
+        check(&mapped)?;
+        let (data, len) = mapped.into_raw_parts();
+        (
+            data.as_ptr() as usize - mmap.as_ptr() as usize,
+            data.len(),
+            len,
+        )
+    };
+    Ok(MappedBitVec {
+        mmap,
+        offset,
+        num_words,
+        len,
+        _marker: core::marker::PhantomData,
+    })
+}
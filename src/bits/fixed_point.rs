@@ -0,0 +1,160 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Vectors of non-negative fixed-point values stored in a [`BitFieldVec`].
+
+A [`FixedPointBitFieldVec`] stores a [`BitFieldVec`] together with a
+scale factor: a value `x` is stored as the integer `x · scale` rounded
+to the nearest integer, and the integer `q` represents the value
+`q / scale`. The scale can be an arbitrary positive divisor, or a power
+of two specified by a number of [fractional bits](FixedPointBitFieldVec::with_shift).
+
+Values outside of the representable range, that is, from zero to
+[`max_f64`](FixedPointBitFieldVec::max_f64), are clamped; `NaN` is stored as zero.
+
+```rust
+use sux::prelude::*;
+
+// 8 bits, 4 of which fractional: steps of 1/16 from 0 to 255/16
+let mut v = FixedPointBitFieldVec::<u16>::with_shift(8, 10, 4);
+v.set_f64(0, 3.14);
+assert!((v.get_f64(0) - 3.14).abs() <= v.step() / 2.0);
+v.set_f64(1, 100.0);
+assert_eq!(v.get_f64(1), v.max_f64());
+v.set_f64(2, -1.0);
+assert_eq!(v.get_f64(2), 0.0);
+```
+
+*/
+
+use crate::prelude::*;
+use common_traits::{CastableFrom, CastableInto};
+use mem_dbg::*;
+
+/// A vector of non-negative fixed-point values stored in a [`BitFieldVec`]
+/// with a given scale.
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct FixedPointBitFieldVec<W: Word = usize> {
+    values: BitFieldVec<W>,
+    /// The integer `q` represents the value `q / scale`.
+    scale: f64,
+}
+
+impl<W: Word + CastableFrom<u128> + CastableInto<u128>> FixedPointBitFieldVec<W> {
+    /// Create a new vector of zeros of given bit width and length
+    /// in which the integer `q` represents the value `q / scale`.
+    ///
+    /// # Panics
+    /// Panics if `scale` is not positive and finite.
+    pub fn new(bit_width: usize, len: usize, scale: f64) -> Self {
+        Self::from_parts(BitFieldVec::new(bit_width, len), scale)
+    }
+
+    /// Create a new vector of zeros of given bit width and length
+    /// in which `frac_bits` bits out of `bit_width` are fractional,
+    /// that is, the integer `q` represents the value `q / 2^frac_bits`.
+    pub fn with_shift(bit_width: usize, len: usize, frac_bits: u32) -> Self {
+        Self::new(bit_width, len, (frac_bits as f64).exp2())
+    }
+
+    /// Create a vector from a [`BitFieldVec`] of integers, in which the
+    /// integer `q` represents the value `q / scale`.
+    ///
+    /// # Panics
+    /// Panics if `scale` is not positive and finite.
+    pub fn from_parts(values: BitFieldVec<W>, scale: f64) -> Self {
+        assert!(
+            scale > 0.0 && scale.is_finite(),
+            "The scale must be positive and finite (got {})",
+            scale
+        );
+        Self { values, scale }
+    }
+
+    /// Return the scale of the vector.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Return the quantization step of the vector, that is, the difference
+    /// between two consecutive representable values.
+    pub fn step(&self) -> f64 {
+        self.scale.recip()
+    }
+
+    /// Return the largest representable value.
+    pub fn max_f64(&self) -> f64 {
+        self.dequantize(self.values.mask())
+    }
+
+    /// Return the integer representing a value, clamping the value
+    /// to the representable range.
+    pub fn quantize(&self, x: f64) -> W {
+        let mask = self.values.mask();
+        let q = (x * self.scale).round();
+        if q.is_nan() || q <= 0.0 {
+            W::ZERO
+        } else if q >= CastableInto::<u128>::cast(mask) as f64 {
+            mask
+        } else {
+            W::cast_from(q as u128)
+        }
+    }
+
+    /// Return the value represented by an integer.
+    pub fn dequantize(&self, q: W) -> f64 {
+        CastableInto::<u128>::cast(q) as f64 / self.scale
+    }
+
+    /// Return the value at the specified index.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    pub fn get_f64(&self, index: usize) -> f64 {
+        self.dequantize(self.values.get(index))
+    }
+
+    /// Set the value at the specified index, clamping it to the
+    /// representable range.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    pub fn set_f64(&mut self, index: usize, x: f64) {
+        let q = self.quantize(x);
+        self.values.set(index, q);
+    }
+
+    /// Return an iterator over the values of the vector.
+    pub fn iter_f64(&self) -> impl Iterator<Item = f64> + '_ {
+        self.values.iter().map(|q| self.dequantize(q))
+    }
+
+    /// Return the number of values in the vector.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Return true if the vector contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.len() == 0
+    }
+
+    /// Return the underlying vector of integers and the scale.
+    pub fn into_parts(self) -> (BitFieldVec<W>, f64) {
+        (self.values, self.scale)
+    }
+}
+
+impl<W: Word> AsRef<BitFieldVec<W>> for FixedPointBitFieldVec<W> {
+    fn as_ref(&self) -> &BitFieldVec<W> {
+        &self.values
+    }
+}
@@ -8,12 +8,20 @@
 /*!
 
 Structures for [bit vectors](`bit_vec`) and
-[vectors of values of bounded bit width](`bit_field_vec`).
+[vectors of values of bounded bit width](`bit_field_vec`),
+possibly [representing fixed-point values](`fixed_point`) or
+[sharing a single buffer](`bit_field_arena`).
 
 */
 
+pub mod bit_field_arena;
+pub use bit_field_arena::*;
+
 pub mod bit_field_vec;
 pub use bit_field_vec::*;
 
 pub mod bit_vec;
 pub use bit_vec::*;
+
+pub mod fixed_point;
+pub use fixed_point::*;
@@ -37,9 +37,13 @@ let ef: EliasFano<SelectZeroFixed2<SelectFixed2>> =
 use crate::prelude::*;
 use crate::traits::bit_field_slice::*;
 use anyhow::{bail, Result};
+use common_traits::SelectInWord;
+use core::ops::Range;
 use core::sync::atomic::Ordering;
 use epserde::*;
 use mem_dbg::*;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 /// A sequential builder for [`EliasFano`].
 ///
@@ -51,19 +55,38 @@ pub struct EliasFanoBuilder {
     l: usize,
     low_bits: BitFieldVec,
     high_bits: BitVec,
+    /// The positions in `high_bits` of the values of index multiple
+    /// of 2<sup>[`Self::LOG2_ONES_PER_INVENTORY`]</sup>, if the builder
+    /// has been created by [`EliasFanoBuilder::with_select`].
+    inventory: Option<Vec<usize>>,
     last_value: usize,
     count: usize,
+    /// The word arrays of the lower and upper bits are padded with zeros
+    /// to a multiple of this number of words.
+    pad_words: usize,
+}
+
+/// Return the number of lower bits used by an [`EliasFano`] structure
+/// containing `n` numbers smaller than or equal to `u`, that is,
+/// `⌊log₂(u / n)⌋` if `u ≥ n > 0`, and zero otherwise.
+pub(crate) fn num_lower_bits(n: usize, u: usize) -> usize {
+    if n != 0 && u >= n {
+        // ⌊log₂(u / n)⌋ = ⌊log₂ ⌊u / n⌋⌋, and u / n ≥ 1
+        bit_width_of(u / n) - 1
+    } else {
+        0
+    }
 }
 
 impl EliasFanoBuilder {
+    /// The inventory spacing of the selection structure built by
+    /// [`EliasFanoBuilder::build_with_select`].
+    const LOG2_ONES_PER_INVENTORY: usize = 8;
+
     /// Create a builder for an [`EliasFano`] containing
     /// `n` numbers smaller than or equal to `u`.
     pub fn new(n: usize, u: usize) -> Self {
-        let l = if u >= n {
-            (u as f64 / n as f64).log2().floor() as usize
-        } else {
-            0
-        };
+        let l = num_lower_bits(n, u);
 
         Self {
             u,
@@ -71,8 +94,60 @@ impl EliasFanoBuilder {
             l,
             low_bits: BitFieldVec::new(l, n),
             high_bits: BitVec::new(n + (u >> l) + 1),
+            inventory: None,
             last_value: 0,
             count: 0,
+            pad_words: 1,
+        }
+    }
+
+    /// Create a builder for an [`EliasFano`] containing `n` numbers smaller
+    /// than or equal to `u` that fills, while values are pushed, the
+    /// inventory of the selection structure built by
+    /// [`build_with_select`](EliasFanoBuilder::build_with_select).
+    pub fn with_select(n: usize, u: usize) -> Self {
+        Self {
+            inventory: Some(Vec::with_capacity(
+                n.div_ceil(1 << Self::LOG2_ONES_PER_INVENTORY),
+            )),
+            ..Self::new(n, u)
+        }
+    }
+
+    /// Pad with zeros the word arrays of the lower and upper bits of the
+    /// structures built by [`build`](EliasFanoBuilder::build) and
+    /// [`build_with_select`](EliasFanoBuilder::build_with_select)
+    /// to a multiple of `words` words.
+    ///
+    /// This option is useful for vectorized decoders, which can then read
+    /// whole SIMD registers past the logical end of the arrays without bounds
+    /// checks. The content of the structure is not affected.
+    ///
+    /// # Panics
+    /// Panics if `words` is zero.
+    pub fn pad_to(mut self, words: usize) -> Self {
+        assert!(words != 0, "The padding must be at least one word");
+        self.pad_words = words;
+        self
+    }
+
+    /// Return the lower bits and the upper bits, padded as
+    /// specified by [`pad_to`](EliasFanoBuilder::pad_to).
+    fn padded_bits(
+        low_bits: BitFieldVec,
+        high_bits: BitVec,
+        pad_words: usize,
+    ) -> (BitFieldVec, BitVec) {
+        let (mut low_data, bit_width, len) = low_bits.into_raw_parts();
+        low_data.resize(low_data.len().next_multiple_of(pad_words), 0);
+        let (mut high_data, num_bits) = high_bits.into_raw_parts();
+        high_data.resize(high_data.len().next_multiple_of(pad_words), 0);
+        // SAFETY: we just appended zero words.
+        unsafe {
+            (
+                BitFieldVec::from_raw_parts(low_data, bit_width, len),
+                BitVec::from_raw_parts(high_data, num_bits),
+            )
         }
     }
 
@@ -111,18 +186,73 @@ impl EliasFanoBuilder {
 
         let high = (value >> self.l) + self.count;
         self.high_bits.set(high, true);
+        if let Some(inventory) = &mut self.inventory {
+            if self.count & ((1 << Self::LOG2_ONES_PER_INVENTORY) - 1) == 0 {
+                inventory.push(high);
+            }
+        }
 
         self.count += 1;
         self.last_value = value;
     }
 
     pub fn build(self) -> EliasFano {
+        let (low_bits, high_bits) =
+            Self::padded_bits(self.low_bits, self.high_bits, self.pad_words);
         EliasFano {
             u: self.u,
             n: self.n,
             l: self.l,
-            low_bits: self.low_bits,
-            high_bits: self.high_bits.with_count(self.n),
+            low_bits,
+            high_bits: high_bits.with_count(self.n),
+        }
+    }
+
+    /// Build an [`InterleavedEliasFano`],
+    /// which stores high and low bits of each block of values contiguously.
+    pub fn build_interleaved(self) -> crate::dict::InterleavedEliasFano {
+        crate::dict::InterleavedEliasFano::new(
+            self.u,
+            self.n,
+            self.l,
+            &self.low_bits,
+            &self.high_bits,
+        )
+    }
+
+    /// Build an [`EliasFano`] with a [`SelectFixed1`] selection structure
+    /// on the high bits.
+    ///
+    /// The result is the same as that of calling [`ConvertTo::convert_to`] on the
+    /// result of [`EliasFanoBuilder::build`]. If the builder has been created by
+    /// [`EliasFanoBuilder::with_select`], the inventory of the selection
+    /// structure has been filled while pushing values, avoiding a second pass
+    /// on the high bits.
+    ///
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut efb = EliasFanoBuilder::with_select(3, 10);
+    /// efb.push(1).unwrap();
+    /// efb.push(5).unwrap();
+    /// efb.push(10).unwrap();
+    /// let ef: EliasFano<SelectFixed1> = efb.build_with_select();
+    /// assert_eq!(ef.get(1), 5);
+    /// ```
+    pub fn build_with_select(self) -> EliasFano<SelectFixed1> {
+        let (low_bits, high_bits) =
+            Self::padded_bits(self.low_bits, self.high_bits, self.pad_words);
+        let high_bits = high_bits.with_count(self.n);
+        EliasFano {
+            u: self.u,
+            n: self.n,
+            l: self.l,
+            low_bits,
+            high_bits: match self.inventory {
+                // SAFETY: the inventory contains the position of every
+                // 2^LOG2_ONES_PER_INVENTORY-th one of the high bits.
+                Some(inventory) => unsafe { SelectFixed1::from_raw_parts(high_bits, inventory) },
+                None => SelectFixed1::new(high_bits, self.n),
+            },
         }
     }
 }
@@ -146,11 +276,7 @@ impl EliasFanoConcurrentBuilder {
     /// Create a builder for an [`EliasFano`] containing
     /// `n` numbers smaller than or equal to `u`.
     pub fn new(n: usize, u: usize) -> Self {
-        let l = if u >= n {
-            (u as f64 / n as f64).log2().floor() as usize
-        } else {
-            0
-        };
+        let l = num_lower_bits(n, u);
 
         Self {
             u,
@@ -178,6 +304,42 @@ impl EliasFanoConcurrentBuilder {
         self.high_bits.set(high, true, order);
     }
 
+    /// Concurrently set the values given by the positions of the ones
+    /// in a range of words of a bit vector.
+    ///
+    /// The ones in the words of `bit_vec` with index in `words` are set as
+    /// values of the sequence, in increasing order, starting from index `rank`.
+    /// Since the index of each value is the rank of the corresponding one,
+    /// `rank` must be the number of ones in `bit_vec` preceding the first word of
+    /// the range: thus, you must compute the number of ones in each range before
+    /// calling this method, but then ranges can be processed concurrently and in
+    /// any order.
+    ///
+    /// # Safety
+    /// - `rank` must be the number of ones in `bit_vec` before word `words.start`.
+    /// - Ranges passed to different calls must be disjoint.
+    /// - All positions of the ones must be smaller than or equal to `u`.
+    /// - The ranges must contain overall exactly `n` ones.
+    pub unsafe fn set_from_bit_vec_chunk<B: AsRef<[usize]>>(
+        &self,
+        bit_vec: &BitVec<usize, B>,
+        words: Range<usize>,
+        rank: usize,
+        order: Ordering,
+    ) {
+        let data = bit_vec.as_ref();
+        let mut index = rank;
+        for word_index in words {
+            let mut word = data[word_index];
+            while word != 0 {
+                let value = word_index * usize::BITS as usize + word.trailing_zeros() as usize;
+                self.set(index, value, order);
+                index += 1;
+                word &= word - 1;
+            }
+        }
+    }
+
     pub fn build(self) -> EliasFano {
         let bit_vec: BitVec = self.high_bits.into();
         EliasFano {
@@ -204,6 +366,100 @@ pub struct EliasFano<H = CountBitVec, L = BitFieldVec> {
     high_bits: H,
 }
 
+impl EliasFano {
+    /// Build an [`EliasFano`] containing the positions of the ones of a bit vector.
+    ///
+    /// The upper bound of the resulting structure is the length of the bit vector.
+    /// No intermediate vector is created, as ones are enumerated word by word.
+    /// To build the structure concurrently, see
+    /// [`EliasFanoConcurrentBuilder::set_from_bit_vec_chunk`].
+    pub fn from_bit_vec<B: AsRef<[usize]>>(bit_vec: &BitVec<usize, B>) -> Self {
+        let n = bit_vec.count_ones();
+        let mut efb = EliasFanoBuilder::new(n, bit_vec.len());
+        for value in OnesIterator::new(bit_vec.as_ref(), n) {
+            // SAFETY: ones are enumerated in increasing order, and there are n of them.
+            unsafe { efb.push_unchecked(value) };
+        }
+        efb.build()
+    }
+
+    /// Build an [`EliasFano`] containing the values of a sorted bit-field slice
+    /// (e.g., a [`BitFieldVec`]).
+    ///
+    /// This method will return an error if the values are not monotone
+    /// or if some value is larger than the upper bound `u`.
+    pub fn from_bit_field_vec(values: &impl BitFieldSlice<usize>, u: usize) -> Result<Self> {
+        let mut efb = EliasFanoBuilder::new(values.len(), u);
+        for index in 0..values.len() {
+            // SAFETY: index is within bounds.
+            efb.push(unsafe { values.get_unchecked(index) })?;
+        }
+        Ok(efb.build())
+    }
+
+    /// Build an [`EliasFano`] containing the values of a sorted slice.
+    ///
+    /// The upper bound of the resulting structure is the last value
+    /// (or zero if the slice is empty).
+    ///
+    /// This method will return an error if the values are not monotone.
+    pub fn from_sorted_slice(values: &[usize]) -> Result<Self> {
+        let mut efb = EliasFanoBuilder::new(values.len(), values.last().copied().unwrap_or(0));
+        for &value in values {
+            efb.push(value)?;
+        }
+        Ok(efb.build())
+    }
+
+    /// Build an [`EliasFano`] containing the values returned by an iterator
+    /// in nondecreasing order.
+    ///
+    /// As the number of values and the upper bound are not known in advance,
+    /// the values are buffered in a vector and then passed to
+    /// [`from_sorted_slice`](EliasFano::from_sorted_slice).
+    ///
+    /// This method will return an error if the values are not monotone.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let ef = EliasFano::from_sorted_iter([1, 5, 5, 42]).unwrap();
+    /// assert_eq!(ef.len(), 4);
+    /// assert_eq!(ef.get(3), 42);
+    /// assert!(EliasFano::from_sorted_iter([3, 2]).is_err());
+    /// ```
+    pub fn from_sorted_iter(values: impl IntoIterator<Item = usize>) -> Result<Self> {
+        Self::from_sorted_slice(&values.into_iter().collect::<Vec<_>>())
+    }
+
+    /// Build in parallel an [`EliasFano`] containing the values of a sorted slice.
+    ///
+    /// The result is identical to that of
+    /// [`from_sorted_slice`](EliasFano::from_sorted_slice), but monotonicity
+    /// is checked in parallel, and values are then set in parallel using an
+    /// [`EliasFanoConcurrentBuilder`].
+    ///
+    /// This method will return an error if the values are not monotone.
+    #[cfg(feature = "rayon")]
+    pub fn from_sorted_slice_par(values: &[usize]) -> Result<Self> {
+        if let Some(index) = values.par_windows(2).position_any(|pair| pair[0] > pair[1]) {
+            bail!(
+                "The values provided are not monotone: {} < {}",
+                values[index + 1],
+                values[index]
+            );
+        }
+        let efcb =
+            EliasFanoConcurrentBuilder::new(values.len(), values.last().copied().unwrap_or(0));
+        values.par_iter().enumerate().for_each(|(index, &value)| {
+            // SAFETY: indices are distinct and smaller than n, values are monotone,
+            // the last one is u, and each value is set exactly once.
+            unsafe { efcb.set(index, value, Ordering::Relaxed) };
+        });
+        Ok(efcb.build())
+    }
+}
+
 impl<H, L> EliasFano<H, L> {
     #[inline]
     pub fn len(&self) -> usize {
@@ -217,7 +473,12 @@ impl<H, L> EliasFano<H, L> {
 
     /// Estimate the size of an instance.
     pub fn estimate_size(u: usize, n: usize) -> usize {
-        2 * n + (n * (u as f64 / n as f64).log2().ceil() as usize)
+        if n == 0 || u <= n {
+            2 * n
+        } else {
+            // ⌈log₂(u / n)⌉ = ⌈log₂ ⌈u / n⌉⌉
+            2 * n + n * bit_width_of((u - 1) / n)
+        }
     }
 
     pub fn transform<F, H2, L2>(self, func: F) -> EliasFano<H2, L2>
@@ -233,6 +494,123 @@ impl<H, L> EliasFano<H, L> {
             high_bits,
         }
     }
+
+    /// Attach to the high bits a [`SelectFixed2`] selection structure,
+    /// a two-level inventory of sampled positions of ones making
+    /// [`get`](IndexedDict::get) constant-time with a small space overhead.
+    ///
+    /// The result is the same as that of calling [`ConvertTo::convert_to`]
+    /// towards `EliasFano<SelectFixed2<H>, L>`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let ef = EliasFano::from_sorted_slice(&[1, 5, 5, 42]).unwrap();
+    /// let ef: EliasFano<SelectFixed2> = ef.with_select_index();
+    /// assert_eq!(ef.get(3), 42);
+    /// ```
+    pub fn with_select_index(self) -> EliasFano<SelectFixed2<H>, L>
+    where
+        H: SelectHinted + BitLength + BitCount + AsRef<[usize]>,
+    {
+        self.transform(|high_bits, low_bits| (SelectFixed2::new(high_bits), low_bits))
+    }
+
+    /// Replace the lower-bits array with a different one containing the same
+    /// data, possibly with a different backend (e.g., a memory-mapped one).
+    ///
+    /// Return an error if the length or the bit width of `low_bits` do not
+    /// match those of the current lower-bits array.
+    pub fn with_low_bits<L2: BitFieldSliceCore<usize>>(
+        self,
+        low_bits: L2,
+    ) -> Result<EliasFano<H, L2>> {
+        if low_bits.len() != self.n {
+            bail!(
+                "Wrong number of lower bits: {} != {}",
+                low_bits.len(),
+                self.n
+            );
+        }
+        if low_bits.bit_width() != self.l {
+            bail!(
+                "Wrong bit width of lower bits: {} != {}",
+                low_bits.bit_width(),
+                self.l
+            );
+        }
+        Ok(EliasFano {
+            u: self.u,
+            n: self.n,
+            l: self.l,
+            low_bits,
+            high_bits: self.high_bits,
+        })
+    }
+
+    /// Replace the upper-bits array with a different one containing the same
+    /// data, possibly with a different backend or with additional indices.
+    ///
+    /// Return an error if the length or the number of ones of `high_bits` do not
+    /// match those of the current upper-bits array.
+    pub fn with_high_bits<H2: BitLength + BitCount>(
+        self,
+        high_bits: H2,
+    ) -> Result<EliasFano<H2, L>> {
+        let len = self.n + (self.u >> self.l) + 1;
+        if high_bits.len() != len {
+            bail!("Wrong length of upper bits: {} != {}", high_bits.len(), len);
+        }
+        if high_bits.count() != self.n {
+            bail!(
+                "Wrong number of ones in upper bits: {} != {}",
+                high_bits.count(),
+                self.n
+            );
+        }
+        Ok(EliasFano {
+            u: self.u,
+            n: self.n,
+            l: self.l,
+            low_bits: self.low_bits,
+            high_bits,
+        })
+    }
+}
+
+/// Return the natural logarithm of the gamma function, using
+/// the Stirling series after shifting the argument above 7.
+fn ln_gamma(mut x: f64) -> f64 {
+    let mut shift = 0.0;
+    while x < 7.0 {
+        shift -= x.ln();
+        x += 1.0;
+    }
+    let x2 = x * x;
+    shift + (x - 0.5) * x.ln() - x
+        + 0.5 * (2.0 * std::f64::consts::PI).ln()
+        + (1.0 / 12.0 - (1.0 / 360.0 - 1.0 / (1260.0 * x2)) / x2) / x
+}
+
+/// Return the base-2 logarithm of the binomial coefficient `n` choose `k`.
+fn log2_binomial(n: usize, k: usize) -> f64 {
+    debug_assert!(k <= n);
+    (ln_gamma(n as f64 + 1.0) - ln_gamma(k as f64 + 1.0) - ln_gamma((n - k) as f64 + 1.0))
+        / std::f64::consts::LN_2
+}
+
+impl<H: MemSize, L: MemSize> EliasFano<H, L> {
+    /// Return the ratio between the number of bits used by this structure
+    /// (as reported by [`MemSize`], so including additional indices, if any)
+    /// and the information-theoretical lower bound for a nondecreasing
+    /// sequence of `n` values smaller than or equal to `u`, that is,
+    /// log₂(<sup>`u` + `n`</sup>C<sub>`n`</sub>).
+    ///
+    /// The result is not finite if the lower bound is zero (e.g., if the
+    /// sequence is empty).
+    pub fn space_overhead(&self) -> f64 {
+        (self.mem_size(SizeFlags::default()) * 8) as f64 / log2_binomial(self.u + self.n, self.n)
+    }
 }
 
 impl<H, L> EliasFano<H, L> {
@@ -254,6 +632,14 @@ impl<H, L> EliasFano<H, L> {
     }
 }
 
+/// Give the hint both to the high bits and to the low bits.
+impl<H: AsRef<[usize]>, L: Advise> Advise for EliasFano<H, L> {
+    fn advise(&self, advice: Advice) -> Result<()> {
+        advise_slice(self.high_bits.as_ref(), advice)?;
+        self.low_bits.advise(advice)
+    }
+}
+
 impl<H: AsRef<[usize]> + Select + SelectZero, L: BitFieldSlice<usize>> IndexedDict
     for EliasFano<H, L>
 where
@@ -275,60 +661,90 @@ where
     }
 
     fn contains(&self, value: &Self::Input) -> bool {
-        if *value > self.u {
-            return false;
-        }
-        let zeros_to_skip = *value >> self.l;
-        let bit_pos = if zeros_to_skip == 0 {
-            0
-        } else {
-            self.high_bits.select_zero(zeros_to_skip - 1).unwrap() + 1
-        };
-
-        let mut rank = bit_pos - zeros_to_skip;
-        let mut iter = self.low_bits.into_unchecked_iter_from(rank);
-        let mut word_idx = bit_pos / (usize::BITS as usize);
-        let bits_to_clean = bit_pos % (usize::BITS as usize);
-
-        // SAFETY: we are certainly iterating within the length of the arrays
-        // and within the range of the iterator because there is a successor for sure.
-
-        let mut window = unsafe { *self.high_bits.as_ref().get_unchecked(word_idx) }
-            & (usize::MAX << bits_to_clean);
+        self.locate(*value).is_ok()
+    }
 
-        loop {
-            while window == 0 {
-                word_idx += 1;
-                if word_idx >= self.high_bits.as_ref().len() {
-                    return false;
-                }
-                window = unsafe { *self.high_bits.as_ref().get_unchecked(word_idx) };
-            }
-            // find the lowest bit set index in the word
-            let bit_idx = window.trailing_zeros() as usize;
-            // compute the global bit index
-            let high_bits = (word_idx * usize::BITS as usize) + bit_idx - rank;
-            // compose the value
-            let res = (high_bits << self.l) | unsafe { iter.next_unchecked() };
+    fn index_of(&self, value: &Self::Input) -> Option<usize> {
+        self.locate(*value).ok()
+    }
+}
 
-            if res == *value {
-                return true;
-            }
-            if res > *value {
-                return false;
-            }
+impl<H: SelectZero, L> EliasFano<H, L> {
+    /// Return the position in the high bits of the zero of given rank,
+    /// or `None` if `rank` is larger than `u >> l`.
+    ///
+    /// Since the high bits contain, for each value `x`, a one followed by the
+    /// zeros of rank smaller than `x >> l`, the position of the zero of rank
+    /// `r` minus `r` is the number of values smaller than `(r + 1) << l`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut efb = EliasFanoBuilder::new(3, 8);
+    /// for value in [1, 2, 7] {
+    ///     efb.push(value).unwrap();
+    /// }
+    /// // There is one lower bit, and the high bits are 10100100
+    /// let ef: EliasFano<SelectZeroFixed1> = efb.build().convert_to().unwrap();
+    /// assert_eq!(ef.select_zero(0), Some(1));
+    /// // There are two values smaller than 6
+    /// assert_eq!(ef.select_zero(2), Some(4));
+    /// assert_eq!(ef.select_zero(4), Some(7));
+    /// assert_eq!(ef.select_zero(5), None);
+    /// ```
+    pub fn select_zero(&self, rank: usize) -> Option<usize> {
+        self.high_bits.select_zero(rank)
+    }
+}
 
-            // clear the lowest bit set
-            window &= window - 1;
-            rank += 1;
+impl<H: AsRef<[usize]> + Select + SelectZero, L: BitFieldSlice<usize>> EliasFano<H, L>
+where
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+{
+    /// Return the number of values smaller than or equal to `value`.
+    ///
+    /// For distinct values, `rank(get(i))` is `i + 1`; in general, the result
+    /// is one plus the index of the last occurrence of `value`, if present,
+    /// and the number of values smaller than `value` otherwise.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut efb = EliasFanoBuilder::new(4, 100);
+    /// for value in [3, 10, 10, 99] {
+    ///     efb.push(value).unwrap();
+    /// }
+    /// let ef: EliasFano<SelectZeroFixed1<SelectFixed1>> =
+    ///     efb.build_with_select().convert_to().unwrap();
+    /// assert_eq!(ef.rank(2), 0);
+    /// assert_eq!(ef.rank(3), 1);
+    /// assert_eq!(ef.rank(10), 3);
+    /// assert_eq!(ef.rank(1000), 4);
+    /// ```
+    pub fn rank(&self, value: usize) -> usize {
+        if value >= self.u {
+            return self.n;
+        }
+        // The values smaller than or equal to value are those smaller than value + 1
+        match self.locate(value + 1) {
+            Ok(rank) | Err(rank) => rank,
         }
     }
 
-    fn index_of(&self, value: &Self::Input) -> Option<usize> {
-        if *value > self.u {
-            return None;
+    /// Locate a value with the same semantics of [`slice::binary_search`].
+    ///
+    /// Return `Ok(index)` with the index of the first occurrence of `value`,
+    /// if `value` is present, or `Err(rank)`, where `rank` is the number of
+    /// values smaller than `value` (i.e., the index at which `value` could be
+    /// inserted), otherwise.
+    ///
+    /// The high bits are traversed just once, so this method is faster than
+    /// computing the rank and then checking the value at that position.
+    pub fn locate(&self, value: usize) -> Result<usize, usize> {
+        if value > self.u {
+            return Err(self.n);
         }
-        let zeros_to_skip = *value >> self.l;
+        let zeros_to_skip = value >> self.l;
         let bit_pos = if zeros_to_skip == 0 {
             0
         } else {
@@ -341,7 +757,7 @@ where
         let bits_to_clean = bit_pos % (usize::BITS as usize);
 
         // SAFETY: we are certainly iterating within the length of the arrays
-        // and within the range of the iterator because there is a successor for sure.
+        // and within the range of the iterator because we stop at the last one.
 
         let mut window = unsafe { *self.high_bits.as_ref().get_unchecked(word_idx) }
             & (usize::MAX << bits_to_clean);
@@ -350,7 +766,7 @@ where
             while window == 0 {
                 word_idx += 1;
                 if word_idx >= self.high_bits.as_ref().len() {
-                    return None;
+                    return Err(self.n);
                 }
                 window = unsafe { *self.high_bits.as_ref().get_unchecked(word_idx) };
             }
@@ -360,11 +776,11 @@ where
             let high_bits = (word_idx * usize::BITS as usize) + bit_idx - rank;
             // compose the value
             let res = (high_bits << self.l) | unsafe { iter.next_unchecked() };
-            if res == *value {
-                return Some(rank);
+            if res == value {
+                return Ok(rank);
             }
-            if res > *value {
-                return None;
+            if res > value {
+                return Err(rank);
             }
 
             // clear the lowest bit set
@@ -391,9 +807,200 @@ impl<H: AsRef<[usize]> + Select, L: BitFieldSlice<usize>> EliasFano<H, L>
 where
     for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
 {
+    /// Return an iterator over the values, which can be also
+    /// traversed backwards.
+    ///
+    /// The iterator scans the high bits once, so iterating over all values
+    /// takes time linear in the number of values plus the number of words of
+    /// high bits, without any selection after its creation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let mut efb = EliasFanoBuilder::new(4, 100);
+    /// for value in [3, 10, 10, 99] {
+    ///     efb.push(value).unwrap();
+    /// }
+    /// let ef = efb.build_with_select();
+    /// assert_eq!(ef.iter().collect::<Vec<_>>(), [3, 10, 10, 99]);
+    /// assert_eq!(ef.iter().rev().collect::<Vec<_>>(), [99, 10, 10, 3]);
+    /// ```
+    pub fn iter(&self) -> EliasFanoIterator<'_, H, L> {
+        EliasFanoIterator::new(self)
+    }
+
+    /// Return an iterator over the values starting from the value
+    /// of given index.
+    ///
+    /// # Panics
+    /// Panics if `from` is greater than the length of the sequence.
     pub fn into_iter_from(&self, from: usize) -> EliasFanoIterator<'_, H, L> {
         EliasFanoIterator::new_from(self, from)
     }
+
+    /// Return an iterator over the values of given index range.
+    ///
+    /// # Panics
+    /// Panics if the range is not within the bounds of the sequence.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let ef = EliasFano::from_sorted_slice(&[1, 5, 5, 42, 50]).unwrap();
+    /// let ef: EliasFano<SelectFixed1> = ef.convert_to().unwrap();
+    /// assert_eq!(ef.get_range(1..4).collect::<Vec<_>>(), [5, 5, 42]);
+    /// ```
+    pub fn get_range(&self, range: Range<usize>) -> core::iter::Take<EliasFanoIterator<'_, H, L>> {
+        assert!(
+            range.start <= range.end && range.end <= self.n,
+            "Range out of bounds: {:?} (length {})",
+            range,
+            self.n
+        );
+        self.into_iter_from(range.start).take(range.len())
+    }
+
+    /// Store in the first `indices.len()` elements of `out` the values
+    /// of given indices.
+    ///
+    /// Indices can be in any order, but if they are sorted the high bits
+    /// are scanned forward from one index to the next, performing a
+    /// selection only when the next index is far from the current one:
+    /// for a sorted batch, the cost is thus at most that of a linear scan
+    /// of the high bits.
+    ///
+    /// # Panics
+    /// Panics if some index is not smaller than the length of the sequence,
+    /// or if `out` is shorter than `indices`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let ef = EliasFano::from_sorted_slice(&[1, 5, 5, 42, 50]).unwrap();
+    /// let ef: EliasFano<SelectFixed1> = ef.convert_to().unwrap();
+    /// let mut out = [0; 3];
+    /// ef.get_batch(&[0, 3, 4], &mut out);
+    /// assert_eq!(out, [1, 42, 50]);
+    /// ```
+    pub fn get_batch(&self, indices: &[usize], out: &mut [usize]) {
+        // Each word of the high bits contains on average half a word of ones,
+        // so scanning few words is faster than selecting
+        const MAX_SCAN: usize = 8 * usize::BITS as usize;
+        let out = &mut out[..indices.len()];
+        let high_bits = self.high_bits.as_ref();
+        // The lowest one in window, if any, has rank `rank`
+        let mut rank = 0;
+        let mut word_idx = 0;
+        let mut window = high_bits.first().copied().unwrap_or(0);
+
+        for (&index, value) in indices.iter().zip(out.iter_mut()) {
+            assert!(
+                index < self.n,
+                "Index out of bounds: {} >= {}",
+                index,
+                self.n
+            );
+            let pos = if index < rank || index - rank > MAX_SCAN {
+                // SAFETY: index < n
+                let pos = unsafe { self.high_bits.select_unchecked(index) };
+                word_idx = pos / usize::BITS as usize;
+                pos
+            } else {
+                loop {
+                    let ones = window.count_ones() as usize;
+                    if rank + ones > index {
+                        break;
+                    }
+                    rank += ones;
+                    word_idx += 1;
+                    window = high_bits[word_idx];
+                }
+                word_idx * usize::BITS as usize + window.select_in_word(index - rank)
+            };
+            window = high_bits[word_idx] & (usize::MAX << (pos % usize::BITS as usize));
+            rank = index;
+            // SAFETY: index < n
+            *value = ((pos - index) << self.l) | unsafe { self.low_bits.get_unchecked(index) };
+        }
+    }
+
+    /// Return a [`BitFieldVec`] containing the values of this structure.
+    ///
+    /// The bit width of the result is the minimum bit width necessary to
+    /// represent the upper bound `u`.
+    pub fn to_bit_field_vec(&self) -> BitFieldVec {
+        let bit_width = bit_width_of(self.u);
+        let mut bit_field_vec = BitFieldVec::new(bit_width, self.n);
+        for (index, value) in self.iter().enumerate() {
+            // SAFETY: values are smaller than or equal to u.
+            unsafe { bit_field_vec.set_unchecked(index, value) };
+        }
+        bit_field_vec
+    }
+
+    /// Return the distribution of the bit lengths of the gaps between
+    /// consecutive values.
+    ///
+    /// The result has `max_bits + 1` entries: the entry of index `k` is the
+    /// number of gaps whose bit length (i.e., `⌊log₂ gap⌋ + 1`, or zero for a
+    /// zero gap) is `k`, except for the last entry, which counts all gaps
+    /// whose bit length is at least `max_bits`. The sum of the entries
+    /// is thus `len() - 1` (or zero, if the structure is empty).
+    ///
+    /// The histogram is computed in a single pass over the values, and it is
+    /// useful to assess whether the values would be compressed better by an
+    /// instantaneous code for the gaps.
+    pub fn gap_histogram(&self, max_bits: usize) -> Vec<usize> {
+        let mut histogram = vec![0; max_bits + 1];
+        let mut iter = self.iter();
+        if let Some(first) = iter.next() {
+            iter.fold(first, |prev, value| {
+                let bit_length = bit_width_of(value - prev);
+                histogram[bit_length.min(max_bits)] += 1;
+                value
+            });
+        }
+        histogram
+    }
+
+    /// Return the values that must be added to and removed from this
+    /// structure to obtain `other`.
+    ///
+    /// The result is a pair `(added, removed)`, where `added` contains, in
+    /// increasing order, the values of `other` that are not in this
+    /// structure, and `removed` the values of this structure that are not
+    /// in `other`. The two structures are scanned just once, in parallel.
+    pub fn diff<H2: AsRef<[usize]> + Select, L2: BitFieldSlice<usize>>(
+        &self,
+        other: &EliasFano<H2, L2>,
+    ) -> (Vec<usize>, Vec<usize>)
+    where
+        for<'b> &'b L2: IntoUncheckedIterator<Item = usize>,
+    {
+        let mut added = vec![];
+        let mut removed = vec![];
+        let mut ours = self.iter().peekable();
+        let mut theirs = other.iter().peekable();
+        while let (Some(&a), Some(&b)) = (ours.peek(), theirs.peek()) {
+            match a.cmp(&b) {
+                core::cmp::Ordering::Less => {
+                    removed.push(a);
+                    ours.next();
+                }
+                core::cmp::Ordering::Greater => {
+                    added.push(b);
+                    theirs.next();
+                }
+                core::cmp::Ordering::Equal => {
+                    ours.next();
+                    theirs.next();
+                }
+            }
+        }
+        removed.extend(ours);
+        added.extend(theirs);
+        (added, removed)
+    }
 }
 
 impl<H1, L1, H2, L2> ConvertTo<EliasFano<H1, L1>> for EliasFano<H2, L2>
@@ -414,6 +1021,9 @@ where
 }
 
 /// An iterator streaming over the Elias--Fano representation.
+///
+/// Internal iteration (e.g., [`Iterator::fold`], [`Iterator::for_each`], or
+/// [`Iterator::sum`]) is specialized.
 #[derive(MemDbg, MemSize)]
 pub struct EliasFanoIterator<'a, H: AsRef<[usize]>, L: BitFieldSlice<usize>>
 where
@@ -428,6 +1038,15 @@ where
     /// This is an usize because BitVec is implemented only for `Vec<usize>` and `&[usize]`.
     window: usize,
     low_bits: <&'a L as IntoUncheckedIterator>::IntoUncheckedIter,
+    /// The index of the last value returned by `next_back`, or the length
+    /// of the sequence if `next_back` has not been called yet.
+    end: usize,
+    /// Index of the word loaded in the `back_window` field, or
+    /// [`usize::MAX`] if `next_back` has not been called yet.
+    back_word_idx: usize,
+    /// Current window on the high bits for backward iteration; it contains
+    /// the ones preceding the last one returned by `next_back`.
+    back_window: usize,
 }
 
 impl<'a, H: Select + AsRef<[usize]>, L: BitFieldSlice<usize>> EliasFanoIterator<'a, H, L>
@@ -446,6 +1065,9 @@ where
             word_idx: 0,
             window: word,
             low_bits: ef.low_bits.into_unchecked_iter(),
+            end: ef.len(),
+            back_word_idx: usize::MAX,
+            back_window: 0,
         }
     }
 
@@ -453,17 +1075,17 @@ where
         if start_index > ef.len() {
             panic!("Index out of bounds: {} > {}", start_index, ef.len());
         }
-        let bit_pos = unsafe { ef.high_bits.select_unchecked(start_index) };
-        let word_idx = bit_pos / (usize::BITS as usize);
-        let bits_to_clean = bit_pos % (usize::BITS as usize);
-
-        let window = if ef.high_bits.as_ref().is_empty() {
-            0
+        let (word_idx, window) = if start_index == ef.len() {
+            // the iterator is exhausted, and there is no one to select
+            (0, 0)
         } else {
+            let bit_pos = unsafe { ef.high_bits.select_unchecked(start_index) };
+            let word_idx = bit_pos / (usize::BITS as usize);
+            let bits_to_clean = bit_pos % (usize::BITS as usize);
             // get the word from the high bits
             let word = unsafe { *ef.high_bits.as_ref().get_unchecked(word_idx) };
             // clean off the bits that we don't care about
-            word & (usize::MAX << bits_to_clean)
+            (word_idx, word & (usize::MAX << bits_to_clean))
         };
 
         Self {
@@ -472,6 +1094,9 @@ where
             word_idx,
             window,
             low_bits: ef.low_bits.into_unchecked_iter_from(start_index),
+            end: ef.len(),
+            back_word_idx: usize::MAX,
+            back_window: 0,
         }
     }
 }
@@ -484,7 +1109,7 @@ where
 
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.ef.len() {
+        if self.index >= self.end {
             return None;
         }
         // find the next word with zeros
@@ -504,6 +1129,40 @@ where
         self.index += 1;
         Some(res)
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+
+    fn fold<B, F>(mut self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        let high_bits = self.ef.high_bits.as_ref();
+        let l = self.ef.l;
+        let end = self.end;
+        let mut index = self.index;
+        let mut word_idx = self.word_idx;
+        let mut window = self.window;
+        let mut acc = init;
+        while index < end {
+            // decode in a batch all the ones in the current word
+            while window != 0 && index < end {
+                let high =
+                    (word_idx * usize::BITS as usize) + window.trailing_zeros() as usize - index;
+                window &= window - 1;
+                acc = f(acc, (high << l) | unsafe { self.low_bits.next_unchecked() });
+                index += 1;
+            }
+            if index < end {
+                word_idx += 1;
+                debug_assert!(word_idx < high_bits.len());
+                window = unsafe { *high_bits.get_unchecked(word_idx) };
+            }
+        }
+        acc
+    }
 }
 
 impl<'a, H: AsRef<[usize]>, L: BitFieldSlice<usize>> ExactSizeIterator
@@ -513,7 +1172,47 @@ where
 {
     #[inline(always)]
     fn len(&self) -> usize {
-        self.ef.len() - self.index
+        self.end - self.index
+    }
+}
+
+impl<'a, H: Select + AsRef<[usize]>, L: BitFieldSlice<usize>> DoubleEndedIterator
+    for EliasFanoIterator<'a, H, L>
+where
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index >= self.end {
+            return None;
+        }
+        if self.back_word_idx == usize::MAX {
+            // First call: load the word containing the last one of the high
+            // bits, which is not done at construction time as it requires a
+            // selection, clearing the following bits
+            let bit_pos = unsafe { self.ef.high_bits.select_unchecked(self.end - 1) };
+            let bit_idx = bit_pos % (usize::BITS as usize);
+            self.back_word_idx = bit_pos / (usize::BITS as usize);
+            self.back_window =
+                unsafe { *self.ef.high_bits.as_ref().get_unchecked(self.back_word_idx) }
+                    & (usize::MAX >> (usize::BITS as usize - 1 - bit_idx));
+        }
+        // find the previous word with ones
+        while self.back_window == 0 {
+            self.back_word_idx -= 1;
+            self.back_window =
+                unsafe { *self.ef.high_bits.as_ref().get_unchecked(self.back_word_idx) };
+        }
+        // find the highest bit set index in the word
+        let bit_idx = usize::BITS as usize - 1 - self.back_window.leading_zeros() as usize;
+        self.end -= 1;
+        // compute the global bit index
+        let high_bits = (self.back_word_idx * usize::BITS as usize) + bit_idx - self.end;
+        // clear the highest bit set
+        self.back_window ^= 1 << bit_idx;
+        // compose the value; the lower bits are accessed directly, as
+        // random access is constant time
+        Some((high_bits << self.ef.l) | unsafe { self.ef.low_bits.get_unchecked(self.end) })
     }
 }
 
@@ -594,6 +1293,10 @@ where
         } else {
             debug_assert!(*value >= self.get(0));
         }
+        // All values are at most u, and there are no zeros to skip past u >> l
+        if *value > self.u {
+            return self.pred_unchecked::<false>(&self.u);
+        }
 
         let zeros_to_skip = value >> self.l;
         let mut bit_pos = self.high_bits.select_zero(zeros_to_skip).unwrap() - 1;
@@ -643,3 +1346,54 @@ where
         }
     }
 }
+
+impl<H: SelectZero + Select + AsRef<[usize]>, L: BitFieldSlice<usize>> EliasFano<H, L>
+where
+    for<'b> &'b L: IntoReverseUncheckedIterator<Item = usize>,
+    for<'b> &'b L: IntoUncheckedIterator<Item = usize>,
+{
+    /// The maximum number of values examined by [`EliasFano::pred_interpolated`]
+    /// around the estimated rank before falling back to [`Pred::pred`].
+    const INTERPOLATION_STEPS: usize = 8;
+
+    /// Return the index and the value of the predecessor of `value`
+    /// (i.e., the largest value smaller than or equal to `value`), or `None`
+    /// if there is no such value, using interpolation search.
+    ///
+    /// The rank of the predecessor is estimated as `value` · `n` / `u`, and
+    /// then a few values around the estimate are examined; if the predecessor
+    /// is not found, this method falls back to [`Pred::pred`].
+    ///
+    /// The result is always the same as that of [`Pred::pred`], but this method
+    /// is faster only if the values are distributed almost uniformly in the range
+    /// `0..=u`; on skewed distributions it will be slightly slower.
+    pub fn pred_interpolated(&self, value: usize) -> Option<(usize, usize)> {
+        if self.n == 0 {
+            return None;
+        }
+        let estimate = ((value as u128 * self.n as u128) / (self.u as u128 + 1)) as usize;
+        let mut rank = estimate.min(self.n - 1);
+        // SAFETY: rank is always smaller than n
+        let mut current = unsafe { self.get_unchecked(rank) };
+        for _ in 0..Self::INTERPOLATION_STEPS {
+            if current <= value {
+                if rank + 1 == self.n {
+                    return Some((rank, current));
+                }
+                let next = unsafe { self.get_unchecked(rank + 1) };
+                if next > value {
+                    return Some((rank, current));
+                }
+                rank += 1;
+                current = next;
+            } else {
+                if rank == 0 {
+                    return None;
+                }
+                rank -= 1;
+                current = unsafe { self.get_unchecked(rank) };
+            }
+        }
+        self.pred(&value)
+    }
+}
@@ -0,0 +1,254 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+An Elias–Fano representation of monotone sequences interleaving high and low bits.
+
+In a standard [`EliasFano`] structure the lower bits, the upper bits and the
+selection structure on the upper bits are stored in separate arrays, so
+a [`get`](IndexedDict::get) touches at least three distant memory regions.
+An [`InterleavedEliasFano`] divides instead the sequence in blocks of
+2<sup>[`LOG2_VALUES_PER_BLOCK`]</sup> values, and stores contiguously, for each block:
+
+- the position in the upper-bits array of the first value of the block
+  (which plays the role of a selection inventory);
+- the lower bits of the values of the block;
+- the segment of the upper-bits array containing the upper bits of the values
+  of the block.
+
+Thus, besides a small array of block offsets, a [`get`](IndexedDict::get) touches
+a single, usually small region of memory. The price to pay is about one word
+per block of additional space, and the lack of constant-time
+[successor](Succ) and [predecessor](Pred) operations, which are implemented
+by binary search.
+
+The layout is chosen at build time by calling
+[`EliasFanoBuilder::build_interleaved`] instead of [`EliasFanoBuilder::build`]:
+
+```rust
+use sux::prelude::*;
+
+# fn main() -> anyhow::Result<()> {
+let mut efb = EliasFanoBuilder::new(4, 100);
+for value in [3, 10, 10, 99] {
+    efb.push(value)?;
+}
+let ef = efb.build_interleaved();
+assert_eq!(ef.get(1), 10);
+assert_eq!(ef.succ(&11), Some((3, 99)));
+assert_eq!(ef.pred(&9), Some((0, 3)));
+# Ok(())
+# }
+```
+
+The benchmark `bench_elias_fano` compares random accesses on the two layouts;
+to compare cache misses, run it under a profiler such as `perf stat -e cache-misses`.
+
+*/
+
+use crate::prelude::*;
+use common_traits::SelectInWord;
+use epserde::*;
+use mem_dbg::*;
+
+/// The base-2 logarithm of the number of values in a block.
+pub const LOG2_VALUES_PER_BLOCK: usize = 6;
+const VALUES_PER_BLOCK: usize = 1 << LOG2_VALUES_PER_BLOCK;
+const BITS: usize = usize::BITS as usize;
+
+/// An Elias–Fano representation of a monotone sequence interleaving
+/// high and low bits.
+///
+/// See the [module documentation](self) for more details.
+#[derive(Epserde, Debug, Clone, Hash, MemDbg, MemSize)]
+pub struct InterleavedEliasFano<D = Vec<usize>> {
+    /// An upper bound to the values.
+    u: usize,
+    /// The number of values.
+    n: usize,
+    /// The number of lower bits.
+    l: usize,
+    /// The offset in `data` of each block, plus a final sentinel.
+    blocks: D,
+    /// For each block, the position in the upper-bits array of its first
+    /// value, the lower bits of its values and the segment of the
+    /// upper-bits array containing its upper bits.
+    data: D,
+}
+
+impl InterleavedEliasFano {
+    /// Create an interleaved structure from the components of
+    /// a standard Elias–Fano representation.
+    pub(crate) fn new(
+        u: usize,
+        n: usize,
+        l: usize,
+        low_bits: &impl BitFieldSlice<usize>,
+        high_bits: &BitVec,
+    ) -> Self {
+        let mut blocks = Vec::with_capacity(n.div_ceil(VALUES_PER_BLOCK) + 1);
+        let mut data = vec![];
+        let mut ones = high_bits.iter_ones();
+        let mut positions = Vec::with_capacity(VALUES_PER_BLOCK);
+
+        for start in (0..n).step_by(VALUES_PER_BLOCK) {
+            let end = (start + VALUES_PER_BLOCK).min(n);
+            positions.clear();
+            positions.extend(ones.by_ref().take(end - start));
+            let first = positions[0];
+            blocks.push(data.len());
+            data.push(first);
+
+            let low_start = data.len();
+            data.resize(low_start + ((end - start) * l).div_ceil(BITS), 0);
+            if l != 0 {
+                for (r, index) in (start..end).enumerate() {
+                    let low = low_bits.get(index);
+                    let pos = r * l;
+                    let (word, bit) = (low_start + pos / BITS, pos % BITS);
+                    data[word] |= low << bit;
+                    if bit + l > BITS {
+                        data[word + 1] |= low >> (BITS - bit);
+                    }
+                }
+            }
+
+            let high_start = data.len();
+            data.resize(
+                high_start + (positions[end - start - 1] - first + 1).div_ceil(BITS),
+                0,
+            );
+            for &pos in &positions {
+                let pos = pos - first;
+                data[high_start + pos / BITS] |= 1 << (pos % BITS);
+            }
+        }
+        blocks.push(data.len());
+
+        Self {
+            u,
+            n,
+            l,
+            blocks,
+            data,
+        }
+    }
+}
+
+impl<D: AsRef<[usize]>> InterleavedEliasFano<D> {
+    /// Return the number of values smaller than `value` (if `STRICT` is false)
+    /// or smaller than or equal to `value` (if `STRICT` is true).
+    fn count_smaller<const STRICT: bool>(&self, value: usize) -> usize {
+        let (mut lo, mut hi) = (0, self.n);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let x = unsafe { self.get_unchecked(mid) };
+            if x < value || (STRICT && x == value) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// Return an iterator over the values of the sequence.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.n).map(|index| unsafe { self.get_unchecked(index) })
+    }
+
+    /// Return the upper bound on the values provided at construction time.
+    pub fn upper_bound(&self) -> usize {
+        self.u
+    }
+}
+
+impl<D: AsRef<[usize]>> IndexedDict for InterleavedEliasFano<D> {
+    type Input = usize;
+    type Output = usize;
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.n
+    }
+
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        let block = index >> LOG2_VALUES_PER_BLOCK;
+        let r = index & (VALUES_PER_BLOCK - 1);
+        let data = self.data.as_ref();
+        let start = *self.blocks.as_ref().get_unchecked(block);
+        let first = *data.get_unchecked(start);
+
+        let low = if self.l == 0 {
+            0
+        } else {
+            let pos = r * self.l;
+            let (word, bit) = (start + 1 + pos / BITS, pos % BITS);
+            let mut low = *data.get_unchecked(word) >> bit;
+            if bit + self.l > BITS {
+                low |= *data.get_unchecked(word + 1) << (BITS - bit);
+            }
+            low & (usize::MAX >> (BITS - self.l))
+        };
+
+        let block_len = (self.n - (block << LOG2_VALUES_PER_BLOCK)).min(VALUES_PER_BLOCK);
+        let high_start = start + 1 + (block_len * self.l).div_ceil(BITS);
+        let mut word_index = high_start;
+        let mut residual = r;
+        let mut word = *data.get_unchecked(word_index);
+        loop {
+            let ones = word.count_ones() as usize;
+            if residual < ones {
+                break;
+            }
+            residual -= ones;
+            word_index += 1;
+            word = *data.get_unchecked(word_index);
+        }
+        let high = first + (word_index - high_start) * BITS + word.select_in_word(residual) - index;
+
+        (high << self.l) | low
+    }
+
+    fn index_of(&self, value: &Self::Input) -> Option<usize> {
+        let index = self.count_smaller::<false>(*value);
+        if index < self.n && unsafe { self.get_unchecked(index) } == *value {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl<D: AsRef<[usize]>> Succ for InterleavedEliasFano<D> {
+    unsafe fn succ_unchecked<const STRICT: bool>(
+        &self,
+        value: &Self::Input,
+    ) -> (usize, Self::Output) {
+        let index = if STRICT {
+            self.count_smaller::<true>(*value)
+        } else {
+            self.count_smaller::<false>(*value)
+        };
+        (index, self.get_unchecked(index))
+    }
+}
+
+impl<D: AsRef<[usize]>> Pred for InterleavedEliasFano<D> {
+    unsafe fn pred_unchecked<const STRICT: bool>(
+        &self,
+        value: &Self::Input,
+    ) -> (usize, Self::Output) {
+        let index = if STRICT {
+            self.count_smaller::<false>(*value)
+        } else {
+            self.count_smaller::<true>(*value)
+        } - 1;
+        (index, self.get_unchecked(index))
+    }
+}
@@ -10,5 +10,14 @@
 pub mod elias_fano;
 pub use elias_fano::{EliasFano, EliasFanoBuilder, EliasFanoConcurrentBuilder};
 
+pub mod interleaved_elias_fano;
+pub use interleaved_elias_fano::InterleavedEliasFano;
+
 pub mod rear_coded_list;
 pub use rear_coded_list::{RearCodedList, RearCodedListBuilder};
+
+pub mod var_len_array;
+pub use var_len_array::{VarLenArray, VarLenArrayBuilder};
+
+pub mod sorted_bit_field_vec;
+pub use sorted_bit_field_vec::SortedBitFieldVec;
@@ -0,0 +1,159 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Mutable sorted sets of integers stored in a [`BitFieldVec`].
+
+A [`SortedBitFieldVec`] keeps its values sorted in a [`BitFieldVec`]:
+insertions find the insertion point by binary search, and then shift the
+following values. Thus, insertions have linear cost, but storage
+is compact and queries are logarithmic.
+
+The bit width of the underlying vector is the minimum necessary
+to represent the largest value inserted so far, and it is increased
+automatically when needed:
+
+```rust
+use sux::prelude::*;
+
+let mut set = SortedBitFieldVec::new();
+for value in [10, 3, 7, 3, 1000] {
+    set.insert(value);
+}
+assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 7, 10, 1000]);
+assert_eq!(set.rank(10), 2);
+assert!(set.contains(&7));
+assert_eq!(set.succ(&8), Some((2, 10)));
+assert_eq!(set.pred(&8), Some((1, 7)));
+```
+
+*/
+
+use crate::prelude::*;
+use mem_dbg::*;
+
+/// A mutable sorted set of integers stored in a [`BitFieldVec`].
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct SortedBitFieldVec {
+    values: BitFieldVec,
+}
+
+impl SortedBitFieldVec {
+    /// Create a new empty set.
+    pub fn new() -> Self {
+        Self {
+            values: BitFieldVec::new(0, 0),
+        }
+    }
+
+    /// Insert a value in the set, returning true if the value
+    /// was not already present.
+    pub fn insert(&mut self, value: usize) -> bool {
+        if value > self.values.mask() {
+            self.widen(bit_width_of(value));
+        }
+        let index = self.rank(value);
+        if index < self.values.len() && unsafe { self.values.get_unchecked(index) } == value {
+            return false;
+        }
+        self.values.insert(index, value);
+        true
+    }
+
+    /// Increase the bit width of the underlying vector.
+    fn widen(&mut self, bit_width: usize) {
+        let mut values = BitFieldVec::with_capacity(bit_width, self.values.len());
+        values.extend(self.values.iter());
+        self.values = values;
+    }
+
+    /// Return the number of values in the set smaller than `value`.
+    pub fn rank(&self, value: usize) -> usize {
+        if value == 0 {
+            0
+        } else {
+            self.values.quantile_index(value - 1)
+        }
+    }
+
+    /// Return an iterator over the values of the set, in increasing order.
+    pub fn iter(&self) -> BitFieldVecIterator<'_, usize, Vec<usize>> {
+        self.values.iter()
+    }
+
+    /// Return the underlying vector of sorted values.
+    pub fn into_inner(self) -> BitFieldVec {
+        self.values
+    }
+}
+
+impl Default for SortedBitFieldVec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsRef<BitFieldVec> for SortedBitFieldVec {
+    fn as_ref(&self) -> &BitFieldVec {
+        &self.values
+    }
+}
+
+impl IndexedDict for SortedBitFieldVec {
+    type Input = usize;
+    type Output = usize;
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline(always)]
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        self.values.get_unchecked(index)
+    }
+
+    fn index_of(&self, value: &Self::Input) -> Option<usize> {
+        let index = self.rank(*value);
+        if index < self.len() && unsafe { self.get_unchecked(index) } == *value {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl Succ for SortedBitFieldVec {
+    unsafe fn succ_unchecked<const STRICT: bool>(
+        &self,
+        value: &Self::Input,
+    ) -> (usize, Self::Output) {
+        let index = if STRICT {
+            self.values.quantile_index(*value)
+        } else {
+            self.rank(*value)
+        };
+        (index, self.get_unchecked(index))
+    }
+}
+
+impl Pred for SortedBitFieldVec {
+    unsafe fn pred_unchecked<const STRICT: bool>(
+        &self,
+        value: &Self::Input,
+    ) -> (usize, Self::Output) {
+        let index = if STRICT {
+            self.rank(*value)
+        } else {
+            self.values.quantile_index(*value)
+        } - 1;
+        (index, self.get_unchecked(index))
+    }
+}
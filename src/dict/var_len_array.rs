@@ -0,0 +1,139 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Immutable arrays of variable-length byte strings.
+
+A [`VarLenArray`] stores the concatenation of its byte strings in a single
+buffer, and the starting position of each string in an [`EliasFano`]
+structure. The `i`-th string is the slice of the buffer between
+the `i`-th and the `i + 1`-th offset:
+
+```rust
+use sux::dict::VarLenArrayBuilder;
+
+let mut builder = VarLenArrayBuilder::new();
+builder.push(b"foo");
+builder.push(b"");
+builder.push(b"barbaz");
+let array = builder.build();
+
+assert_eq!(array.len(), 3);
+assert_eq!(array.get(0), b"foo");
+assert_eq!(array.get(1), b"");
+assert_eq!(array.get(2), b"barbaz");
+```
+
+*/
+
+use crate::prelude::*;
+use epserde::*;
+use mem_dbg::*;
+
+/// Immutable arrays of variable-length byte strings.
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug, Clone, Epserde, MemDbg, MemSize)]
+pub struct VarLenArray<D: AsRef<[u8]> = Vec<u8>, O = EliasFano<SelectFixed2>> {
+    /// The concatenation of the strings.
+    data: D,
+    /// The `len() + 1` offsets of the strings in `data`.
+    offsets: O,
+}
+
+/// A builder for [`VarLenArray`].
+#[derive(Debug, Clone, Default, MemDbg, MemSize)]
+pub struct VarLenArrayBuilder {
+    /// The concatenation of the strings.
+    data: Vec<u8>,
+    /// The offsets of the strings in `data`, with the exception of the last one.
+    offsets: Vec<usize>,
+}
+
+impl VarLenArrayBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a byte string to the end of the array.
+    #[inline]
+    pub fn push(&mut self, bytes: impl AsRef<[u8]>) {
+        self.offsets.push(self.data.len());
+        self.data.extend_from_slice(bytes.as_ref());
+    }
+
+    /// Append all the byte strings returned by an iterator to the end of the array.
+    pub fn extend<S: AsRef<[u8]>>(&mut self, iter: impl IntoIterator<Item = S>) {
+        for bytes in iter {
+            self.push(bytes);
+        }
+    }
+
+    /// Build the array, storing the offsets in an [`EliasFano`] structure.
+    pub fn build(mut self) -> VarLenArray {
+        let u = self.data.len();
+        self.offsets.push(u);
+        let mut efb = EliasFanoBuilder::new(self.offsets.len(), u);
+        for &offset in &self.offsets {
+            // SAFETY: offsets are nondecreasing and at most u
+            unsafe { efb.push_unchecked(offset) };
+        }
+        let offsets: EliasFano<SelectFixed2> = efb
+            .build()
+            .convert_to()
+            .expect("Building the offsets cannot fail");
+        VarLenArray {
+            data: self.data,
+            offsets,
+        }
+    }
+}
+
+impl<D: AsRef<[u8]>, O: IndexedDict<Output = usize>> VarLenArray<D, O> {
+    /// Return the number of strings in the array.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Return whether the array is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the string at the specified index.
+    ///
+    /// # Panics
+    /// Panics if the index is not in [0..[len](`VarLenArray::len`)).
+    #[inline]
+    pub fn get(&self, index: usize) -> &[u8] {
+        if index >= self.len() {
+            panic!("Index out of bounds: {} >= {}", index, self.len());
+        }
+        unsafe { self.get_unchecked(index) }
+    }
+
+    /// Return the string at the specified index.
+    ///
+    /// # Safety
+    /// `index` must be in [0..[len](`VarLenArray::len`)). No bounds checking is performed.
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> &[u8] {
+        let start = self.offsets.get_unchecked(index);
+        let end = self.offsets.get_unchecked(index + 1);
+        self.data.as_ref().get_unchecked(start..end)
+    }
+
+    /// Return an iterator over the strings of the array.
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        // SAFETY: the index is within bounds
+        (0..self.len()).map(|index| unsafe { self.get_unchecked(index) })
+    }
+}
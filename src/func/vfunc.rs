@@ -12,6 +12,7 @@ use crate::utils::*;
 use anyhow::bail;
 use arbitrary_chunks::ArbitraryChunks;
 use bit_field_slice::BitFieldSliceCore;
+use bit_field_slice::BitFieldSliceMut;
 use bit_field_slice::Word;
 use common_traits::{AsBytes, AtomicUnsignedInt, IntoAtomic};
 use derivative::Derivative;
@@ -395,7 +396,9 @@ where
                         data.get(edge[0], Relaxed) ^ data.get(edge[1], Relaxed)
                     };
 
-                    data.set(v, chunk[edge_index].val ^ value, Relaxed);
+                    // SAFETY: each chunk is assigned by a single thread, and
+                    // vertices of different chunks are disjoint.
+                    unsafe { data.set_unfenced(v, chunk[edge_index].val ^ value) };
                     debug_assert_eq!(
                         data.get(edge[0], Relaxed)
                             ^ data.get(edge[1], Relaxed)
@@ -465,6 +468,40 @@ where
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Return the bit width of the values stored in the function.
+    pub fn bit_width(&self) -> usize {
+        self.values.bit_width()
+    }
+}
+
+impl<T: ?Sized + ToSig, O: ZeroCopy + SerializeInner + DeserializeInner + Word + IntoAtomic>
+    VFunc<T, O, BitFieldVec<O>>
+{
+    /// Repack the stored values using the minimum bit width sufficient to
+    /// represent all of them.
+    ///
+    /// Since the value associated with a key is the XOR of stored values,
+    /// the result of queries is not affected. Note that functions built
+    /// by a [`VFuncBuilder`] are already tight, in which case this method
+    /// does nothing.
+    pub fn tighten(&mut self) {
+        let bit_width = self
+            .values
+            .iter()
+            .map(|x| x.len() as usize)
+            .max()
+            .unwrap_or(0);
+
+        if bit_width < self.values.bit_width() {
+            let mut values = BitFieldVec::new(bit_width, self.values.len());
+            for (i, x) in self.values.iter().enumerate() {
+                // SAFETY: i is within bounds and x fits in bit_width bits.
+                unsafe { values.set_unchecked(i, x) };
+            }
+            self.values = values;
+        }
+    }
 }
 
 impl<
@@ -696,3 +733,34 @@ where
         })
     }
 }
+
+#[cfg(test)]
+#[cfg_attr(test, test)]
+fn test_tighten_repack() -> anyhow::Result<()> {
+    for n in [10_usize, 100, 1000, 100000] {
+        let func = VFuncBuilder::default().build(
+            FromIntoIterator::from(0..n),
+            FromIntoIterator::from(0_usize..),
+            &mut Option::<ProgressLogger>::None,
+        )?;
+        let bit_width = func.bit_width();
+
+        // Repack the values with a larger bit width
+        let mut values = BitFieldVec::<usize>::new(bit_width + 7, func.values.len());
+        for (i, value) in func.values.iter().enumerate() {
+            values.set(i, value);
+        }
+        let mut func = VFunc { values, ..func };
+        assert_eq!(func.bit_width(), bit_width + 7);
+        for i in 0..n {
+            assert_eq!(i, func.get(&i));
+        }
+
+        func.tighten();
+        assert_eq!(func.bit_width(), bit_width);
+        for i in 0..n {
+            assert_eq!(i, func.get(&i));
+        }
+    }
+    Ok(())
+}
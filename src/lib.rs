@@ -18,6 +18,8 @@ pub mod utils;
 #[cfg(feature = "fuzz")]
 pub mod fuzz;
 
+pub use utils::representation::{recommend_representation, Representation};
+
 pub mod prelude {
     pub use crate::bits::*;
     pub use crate::dict::*;
@@ -0,0 +1,226 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use core::cell::OnceCell;
+use mem_dbg::*;
+
+/**
+
+A ranking structure whose counters are computed lazily.
+
+The bit vector is divided in blocks of 2<sup>`LOG2_WORDS_PER_BLOCK`</sup> words,
+and the structure records, for each block, the number of ones preceding the
+block. Counters are however computed on demand: when a rank is requested,
+the counter of the containing block is computed starting from the closest
+preceding block whose counter is already known, memoizing all the counters
+computed in the process, and then the remaining words are scanned.
+
+Thus, repeated ranks in the same region of the bit vector are fast, and the
+cost of building the structure is paid only for the regions that are actually
+queried. This is useful for sparse query patterns over very large bit vectors.
+The space used is one [`OnceCell<usize>`] per block.
+
+Since the counters are memoized using interior mutability, this structure
+is not [`Sync`].
+
+```rust
+use sux::prelude::*;
+
+let bits: BitVec = [true, false, true, true, false].into_iter().collect();
+let rank = LazyRank::<_>::new(bits);
+assert_eq!(rank.rank(0), 0);
+assert_eq!(rank.rank(3), 2);
+assert_eq!(rank.rank(5), 3);
+```
+
+*/
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct LazyRank<B, const LOG2_WORDS_PER_BLOCK: usize = 8> {
+    bits: B,
+    /// The number of ones preceding each block, if already computed.
+    blocks: Vec<OnceCell<usize>>,
+}
+
+impl<B: AsRef<[usize]> + BitLength, const LOG2_WORDS_PER_BLOCK: usize>
+    LazyRank<B, LOG2_WORDS_PER_BLOCK>
+{
+    const WORDS_PER_BLOCK: usize = 1 << LOG2_WORDS_PER_BLOCK;
+
+    /// Create a new lazy ranking structure; no counter is computed.
+    pub fn new(bits: B) -> Self {
+        let num_words = bits.len().div_ceil(usize::BITS as usize);
+        let num_blocks = num_words.div_ceil(Self::WORDS_PER_BLOCK) + 1;
+        let blocks = vec![OnceCell::new(); num_blocks];
+        // The first counter is always zero.
+        blocks[0].set(0).unwrap();
+        Self { bits, blocks }
+    }
+
+    /// Return the number of blocks whose counter has already been computed.
+    pub fn num_cached_blocks(&self) -> usize {
+        self.blocks
+            .iter()
+            .filter(|block| block.get().is_some())
+            .count()
+    }
+
+    /// Return the number of ones preceding the specified block, computing
+    /// and memoizing the missing counters.
+    fn block_rank(&self, block: usize) -> usize {
+        if let Some(&rank) = self.blocks[block].get() {
+            return rank;
+        }
+        // Find the closest preceding block whose counter is known;
+        // the first block always is.
+        let mut start = block - 1;
+        while self.blocks[start].get().is_none() {
+            start -= 1;
+        }
+        let words = self.bits.as_ref();
+        let mut rank = *self.blocks[start].get().unwrap();
+        for b in start..block {
+            let first = b * Self::WORDS_PER_BLOCK;
+            let last = (first + Self::WORDS_PER_BLOCK).min(words.len());
+            rank += words[first..last]
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum::<usize>();
+            let _ = self.blocks[b + 1].set(rank);
+        }
+        rank
+    }
+
+    /// Forget all counters computed so far.
+    pub fn clear_cache(&mut self) {
+        for block in &mut self.blocks[1..] {
+            block.take();
+        }
+    }
+
+    /// Return the underlying bit vector, forgetting the counters.
+    pub fn into_inner(self) -> B {
+        self.bits
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength, const LOG2_WORDS_PER_BLOCK: usize> Rank
+    for LazyRank<B, LOG2_WORDS_PER_BLOCK>
+{
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        let word_pos = pos / usize::BITS as usize;
+        let bit_pos = pos % usize::BITS as usize;
+        let block = word_pos >> LOG2_WORDS_PER_BLOCK;
+        let words = self.bits.as_ref();
+        let mut rank = self.block_rank(block);
+        for word in words.get_unchecked(block << LOG2_WORDS_PER_BLOCK..word_pos) {
+            rank += word.count_ones() as usize;
+        }
+        if bit_pos != 0 {
+            rank += (words.get_unchecked(word_pos) & ((1 << bit_pos) - 1)).count_ones() as usize;
+        }
+        rank
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength, const LOG2_WORDS_PER_BLOCK: usize> RankZero
+    for LazyRank<B, LOG2_WORDS_PER_BLOCK>
+{
+}
+
+/// Forward [`BitLength`] to the underlying implementation.
+impl<B: BitLength, const LOG2_WORDS_PER_BLOCK: usize> BitLength
+    for LazyRank<B, LOG2_WORDS_PER_BLOCK>
+{
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+/// Forward [`BitCount`] to the underlying implementation.
+impl<B: BitCount, const LOG2_WORDS_PER_BLOCK: usize> BitCount
+    for LazyRank<B, LOG2_WORDS_PER_BLOCK>
+{
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.bits.count()
+    }
+}
+
+/// Forward [`Select`] to the underlying implementation.
+impl<B: Select, const LOG2_WORDS_PER_BLOCK: usize> Select for LazyRank<B, LOG2_WORDS_PER_BLOCK> {
+    #[inline(always)]
+    fn select(&self, rank: usize) -> Option<usize> {
+        self.bits.select(rank)
+    }
+
+    #[inline(always)]
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_unchecked(rank)
+    }
+}
+
+/// Forward [`SelectHinted`] to the underlying implementation.
+impl<B: SelectHinted, const LOG2_WORDS_PER_BLOCK: usize> SelectHinted
+    for LazyRank<B, LOG2_WORDS_PER_BLOCK>
+{
+    #[inline(always)]
+    unsafe fn select_hinted_unchecked(&self, rank: usize, pos: usize, rank_at_pos: usize) -> usize {
+        self.bits.select_hinted_unchecked(rank, pos, rank_at_pos)
+    }
+
+    #[inline(always)]
+    fn select_hinted(&self, rank: usize, pos: usize, rank_at_pos: usize) -> Option<usize> {
+        self.bits.select_hinted(rank, pos, rank_at_pos)
+    }
+}
+
+/// Forward [`SelectZero`] to the underlying implementation.
+impl<B: SelectZero, const LOG2_WORDS_PER_BLOCK: usize> SelectZero
+    for LazyRank<B, LOG2_WORDS_PER_BLOCK>
+{
+    #[inline(always)]
+    fn select_zero(&self, rank: usize) -> Option<usize> {
+        self.bits.select_zero(rank)
+    }
+
+    #[inline(always)]
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_zero_unchecked(rank)
+    }
+}
+
+/// Forward [`SelectZeroHinted`] to the underlying implementation.
+impl<B: SelectZeroHinted, const LOG2_WORDS_PER_BLOCK: usize> SelectZeroHinted
+    for LazyRank<B, LOG2_WORDS_PER_BLOCK>
+{
+    #[inline(always)]
+    unsafe fn select_zero_hinted_unchecked(
+        &self,
+        rank: usize,
+        pos: usize,
+        rank_at_pos: usize,
+    ) -> usize {
+        self.bits
+            .select_zero_hinted_unchecked(rank, pos, rank_at_pos)
+    }
+
+    #[inline(always)]
+    fn select_zero_hinted(&self, rank: usize, pos: usize, rank_at_pos: usize) -> Option<usize> {
+        self.bits.select_zero_hinted(rank, pos, rank_at_pos)
+    }
+}
+
+/// Forward `AsRef<[usize]>` to the underlying implementation.
+impl<B: AsRef<[usize]>, const LOG2_WORDS_PER_BLOCK: usize> AsRef<[usize]>
+    for LazyRank<B, LOG2_WORDS_PER_BLOCK>
+{
+    fn as_ref(&self) -> &[usize] {
+        self.bits.as_ref()
+    }
+}
@@ -37,7 +37,8 @@ The "fixed" selection structures are very simple and fast, but they require that
 the bit vector has a reasonably uniform distribution of zeroes and ones. The default parameters
 are a good choice for a vector with approximately the same number of zeroes and ones, such as
 the high bits of the [Elias–Fano representation of monotone sequences](crate::dict::elias_fano::EliasFano),
-but they can be tuned for other densities.
+but they can be tuned for other densities. When the distribution of ones is unknown or clustered,
+[`SelectDArray`] adapts to the local density of the bit vector.
 
 */
 mod select_fixed1;
@@ -51,3 +52,12 @@ pub use select_fixed2::*;
 
 mod select_zero_fixed2;
 pub use select_zero_fixed2::*;
+
+mod lazy_rank;
+pub use lazy_rank::*;
+
+mod rank9;
+pub use rank9::*;
+
+mod select_darray;
+pub use select_darray::*;
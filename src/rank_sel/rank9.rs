@@ -0,0 +1,209 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use epserde::*;
+use mem_dbg::*;
+
+/**
+
+A ranking structure using 25% of additional space and providing constant-time ranking.
+
+This is the classic two-level structure described by Sebastiano Vigna in
+“<a href="https://link.springer.com/chapter/10.1007/978-3-540-68552-4_12">Broadword
+Implementation of Rank/Select Queries</a>”, _Proc. of the 7th International Workshop
+on Experimental Algorithms, WEA 2008_, volume 5038 of Lecture Notes in Computer Science, pages
+154–168. Springer, 2008.
+
+The bit vector is divided in blocks of eight 64-bit words. For each block,
+the structure records in a first `u64` the number of ones preceding the block,
+and in a second `u64` seven 9-bit counters containing the number of ones
+preceding each of the words of the block but the first, relatively to
+the start of the block. Thus, a rank requires two memory accesses to the
+counters and one to the bit vector.
+
+The underlying words are assumed to be 64 bits wide.
+
+```rust
+use sux::prelude::*;
+
+let bits: BitVec = [true, false, true, true, false].into_iter().collect();
+let rank9 = Rank9::new(bits);
+assert_eq!(rank9.rank(0), 0);
+assert_eq!(rank9.rank(3), 2);
+assert_eq!(rank9.rank(5), 3);
+```
+
+*/
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct Rank9<B = BitVec, C: AsRef<[u64]> = Vec<u64>> {
+    bits: B,
+    /// Pairs of absolute and packed relative counters, one pair per block.
+    counts: C,
+}
+
+impl<B, C: AsRef<[u64]>> Rank9<B, C> {
+    const WORDS_PER_BLOCK: usize = 8;
+    const COUNTER_BITS: usize = 9;
+    const COUNTER_MASK: u64 = (1 << Self::COUNTER_BITS) - 1;
+
+    /// Return the underlying bit vector, discarding the counters.
+    pub fn into_inner(self) -> B {
+        self.bits
+    }
+
+    /// Return the underlying bit vector and the counters.
+    pub fn into_raw_parts(self) -> (B, C) {
+        (self.bits, self.counts)
+    }
+
+    /// Create a ranking structure from a bit vector and its counters.
+    ///
+    /// # Safety
+    /// The counters must be those computed by [`Rank9::new`] on `bits`.
+    pub unsafe fn from_raw_parts(bits: B, counts: C) -> Self {
+        Self { bits, counts }
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength> Rank9<B, Vec<u64>> {
+    /// Create a new ranking structure, computing all counters.
+    pub fn new(bits: B) -> Self {
+        debug_assert_eq!(usize::BITS, 64);
+        let words = bits.as_ref();
+        let num_words = bits.len().div_ceil(usize::BITS as usize);
+        // One additional block so that ranking at the end of a full block works
+        let num_blocks = num_words / Self::WORDS_PER_BLOCK + 1;
+        let mut counts = Vec::with_capacity(2 * num_blocks);
+        let mut rank = 0;
+        for block in words[..num_words].chunks(Self::WORDS_PER_BLOCK) {
+            counts.push(rank);
+            let mut relative = 0;
+            let mut packed = 0;
+            for (i, word) in block.iter().enumerate() {
+                if i != 0 {
+                    packed |= relative << (Self::COUNTER_BITS * (i - 1));
+                }
+                relative += word.count_ones() as u64;
+            }
+            // Missing words at the end of the last block have no ones
+            for i in block.len()..Self::WORDS_PER_BLOCK {
+                packed |= relative << (Self::COUNTER_BITS * (i - 1));
+            }
+            counts.push(packed);
+            rank += relative;
+        }
+        if counts.len() < 2 * num_blocks {
+            counts.push(rank);
+            counts.push(0);
+        }
+        Self { bits, counts }
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength, C: AsRef<[u64]>> Rank for Rank9<B, C> {
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        let word_pos = pos / usize::BITS as usize;
+        let bit_pos = pos % usize::BITS as usize;
+        let block = word_pos / Self::WORDS_PER_BLOCK;
+        let offset = word_pos % Self::WORDS_PER_BLOCK;
+        let counts = self.counts.as_ref();
+        let mut rank = *counts.get_unchecked(2 * block) as usize;
+        if offset != 0 {
+            rank += ((*counts.get_unchecked(2 * block + 1) >> (Self::COUNTER_BITS * (offset - 1)))
+                & Self::COUNTER_MASK) as usize;
+        }
+        if bit_pos != 0 {
+            rank += (self.bits.as_ref().get_unchecked(word_pos) & ((1 << bit_pos) - 1)).count_ones()
+                as usize;
+        }
+        rank
+    }
+}
+
+impl<B: AsRef<[usize]> + BitLength, C: AsRef<[u64]>> RankZero for Rank9<B, C> {}
+
+/// Forward [`BitLength`] to the underlying implementation.
+impl<B: BitLength, C: AsRef<[u64]>> BitLength for Rank9<B, C> {
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+/// Forward [`BitCount`] to the underlying implementation.
+impl<B: BitCount, C: AsRef<[u64]>> BitCount for Rank9<B, C> {
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.bits.count()
+    }
+}
+
+/// Forward [`Select`] to the underlying implementation.
+impl<B: Select, C: AsRef<[u64]>> Select for Rank9<B, C> {
+    #[inline(always)]
+    fn select(&self, rank: usize) -> Option<usize> {
+        self.bits.select(rank)
+    }
+
+    #[inline(always)]
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_unchecked(rank)
+    }
+}
+
+/// Forward [`SelectHinted`] to the underlying implementation.
+impl<B: SelectHinted, C: AsRef<[u64]>> SelectHinted for Rank9<B, C> {
+    #[inline(always)]
+    unsafe fn select_hinted_unchecked(&self, rank: usize, pos: usize, rank_at_pos: usize) -> usize {
+        self.bits.select_hinted_unchecked(rank, pos, rank_at_pos)
+    }
+
+    #[inline(always)]
+    fn select_hinted(&self, rank: usize, pos: usize, rank_at_pos: usize) -> Option<usize> {
+        self.bits.select_hinted(rank, pos, rank_at_pos)
+    }
+}
+
+/// Forward [`SelectZero`] to the underlying implementation.
+impl<B: SelectZero, C: AsRef<[u64]>> SelectZero for Rank9<B, C> {
+    #[inline(always)]
+    fn select_zero(&self, rank: usize) -> Option<usize> {
+        self.bits.select_zero(rank)
+    }
+
+    #[inline(always)]
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_zero_unchecked(rank)
+    }
+}
+
+/// Forward [`SelectZeroHinted`] to the underlying implementation.
+impl<B: SelectZeroHinted, C: AsRef<[u64]>> SelectZeroHinted for Rank9<B, C> {
+    #[inline(always)]
+    unsafe fn select_zero_hinted_unchecked(
+        &self,
+        rank: usize,
+        pos: usize,
+        rank_at_pos: usize,
+    ) -> usize {
+        self.bits
+            .select_zero_hinted_unchecked(rank, pos, rank_at_pos)
+    }
+
+    #[inline(always)]
+    fn select_zero_hinted(&self, rank: usize, pos: usize, rank_at_pos: usize) -> Option<usize> {
+        self.bits.select_zero_hinted(rank, pos, rank_at_pos)
+    }
+}
+
+/// Forward `AsRef<[usize]>` to the underlying implementation.
+impl<B: AsRef<[usize]>, C: AsRef<[u64]>> AsRef<[usize]> for Rank9<B, C> {
+    fn as_ref(&self) -> &[usize] {
+        self.bits.as_ref()
+    }
+}
@@ -0,0 +1,351 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use anyhow::Result;
+use epserde::*;
+use mem_dbg::*;
+
+/**
+
+A selection structure based on the _darray_ of Okanohara and Sadakane.
+
+Daisuke Okanohara and Kunihiko Sadakane, “<a href="https://doi.org/10.1137/1.9781611972870.6">Practical
+Entropy-Compressed Rank/Select Dictionary</a>”, _Proc. of the Workshop on Algorithm Engineering
+and Experiments, ALENEX 2007_, pages 60–70. SIAM, 2007.
+
+The ones of the bit vector are divided in blocks of 2<sup>`LOG2_ONES_PER_BLOCK`</sup> ones.
+A block is _dense_ if the distance between its first and last one is
+smaller than 2<sup>16</sup>, and _sparse_ otherwise. For a dense block, the
+structure records the position of the first one of the block and, in a
+subinventory of `u16`'s, the offset from the first one of every
+2<sup>`LOG2_ONES_PER_SUB`</sup>-th one of the block; a selection uses the
+closest preceding sample as a hint for
+[`SelectHinted`], which scans at most 2<sup>16</sup> bits. For a sparse block, the
+positions of all ones are recorded explicitly, which costs at most 64 bits
+every 2<sup>16</sup>/2<sup>`LOG2_ONES_PER_BLOCK`</sup> bits of the bit vector.
+
+Contrarily to [`SelectFixed1`] and [`SelectFixed2`], which assume a reasonably uniform
+distribution of ones, the structure adapts to the local density of the bit vector, and
+it is thus a good choice when the distribution of ones is unknown or clustered.
+
+The easiest way to build the structure on a [`BitVec`] is [`BitVec::with_select`].
+
+```rust
+use sux::prelude::*;
+
+let bits: BitVec = [false, true, true, false, false, true].into_iter().collect();
+let select = bits.with_select();
+assert_eq!(select.select(0), Some(1));
+assert_eq!(select.select(1), Some(2));
+assert_eq!(select.select(2), Some(5));
+assert_eq!(select.select(3), None);
+```
+
+*/
+#[derive(Epserde, Debug, Clone, MemDbg, MemSize)]
+pub struct SelectDArray<
+    B: SelectHinted = CountBitVec,
+    const LOG2_ONES_PER_BLOCK: usize = 10,
+    const LOG2_ONES_PER_SUB: usize = 5,
+> {
+    bits: B,
+    /// For each block, the position of its first one if the block is dense,
+    /// or the start of its ones in `overflow`, tagged with
+    /// [`SPARSE_FLAG`](Self::SPARSE_FLAG), if the block is sparse.
+    inventory: Vec<u64>,
+    /// For each dense block, the offsets of the sampled ones from the first one of the block.
+    subinventory: Vec<u16>,
+    /// The positions of the ones of the sparse blocks.
+    overflow: Vec<u64>,
+}
+
+impl<B: SelectHinted, const LOG2_ONES_PER_BLOCK: usize, const LOG2_ONES_PER_SUB: usize>
+    SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    const ONES_PER_BLOCK: usize = 1 << LOG2_ONES_PER_BLOCK;
+    const SUBS_PER_BLOCK: usize = 1 << (LOG2_ONES_PER_BLOCK - LOG2_ONES_PER_SUB);
+    /// Blocks whose span is at least this large are sparse.
+    const MAX_DENSE_SPAN: usize = 1 << 16;
+    /// We use the sign bit to mark sparse blocks.
+    const SPARSE_FLAG: u64 = 1 << 63;
+
+    /// Return the underlying bit vector, discarding the index.
+    pub fn into_inner(self) -> B {
+        self.bits
+    }
+
+    /// Return the number of sparse blocks, that is, blocks whose ones
+    /// are recorded explicitly.
+    pub fn num_sparse_blocks(&self) -> usize {
+        self.inventory
+            .iter()
+            .filter(|&&entry| entry & Self::SPARSE_FLAG != 0)
+            .count()
+    }
+}
+
+impl<
+        B: SelectHinted + BitLength + AsRef<[usize]>,
+        const LOG2_ONES_PER_BLOCK: usize,
+        const LOG2_ONES_PER_SUB: usize,
+    > SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    /// Create a new selection structure on the given bit vector.
+    ///
+    /// # Panics
+    /// Panics if `LOG2_ONES_PER_SUB` is greater than `LOG2_ONES_PER_BLOCK`.
+    pub fn new(bits: B) -> Self {
+        assert!(
+            LOG2_ONES_PER_SUB <= LOG2_ONES_PER_BLOCK,
+            "LOG2_ONES_PER_SUB ({}) must not be greater than LOG2_ONES_PER_BLOCK ({})",
+            LOG2_ONES_PER_SUB,
+            LOG2_ONES_PER_BLOCK
+        );
+        let mut res = Self {
+            bits,
+            inventory: Vec::new(),
+            subinventory: Vec::new(),
+            overflow: Vec::new(),
+        };
+        let len = res.bits.len();
+        let words = &res.bits.as_ref()[..len.div_ceil(usize::BITS as usize)];
+        let mut block = Vec::with_capacity(Self::ONES_PER_BLOCK);
+        for (word_index, &word) in words.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let pos = word_index * usize::BITS as usize + word.trailing_zeros() as usize;
+                // Bits beyond the length are not guaranteed to be zero
+                if pos >= len {
+                    break;
+                }
+                word &= word - 1;
+                block.push(pos);
+                if block.len() == Self::ONES_PER_BLOCK {
+                    Self::add_block(
+                        &block,
+                        &mut res.inventory,
+                        &mut res.subinventory,
+                        &mut res.overflow,
+                    );
+                    block.clear();
+                }
+            }
+        }
+        if !block.is_empty() {
+            Self::add_block(
+                &block,
+                &mut res.inventory,
+                &mut res.subinventory,
+                &mut res.overflow,
+            );
+        }
+        res
+    }
+
+    /// Add to the index a block of ones with the given positions.
+    fn add_block(
+        block: &[usize],
+        inventory: &mut Vec<u64>,
+        subinventory: &mut Vec<u16>,
+        overflow: &mut Vec<u64>,
+    ) {
+        let first = block[0];
+        let last = block[block.len() - 1];
+        let start = subinventory.len();
+        if last - first < Self::MAX_DENSE_SPAN {
+            inventory.push(first as u64);
+            subinventory.extend(
+                block
+                    .iter()
+                    .step_by(1 << LOG2_ONES_PER_SUB)
+                    .map(|&pos| (pos - first) as u16),
+            );
+        } else {
+            inventory.push(overflow.len() as u64 | Self::SPARSE_FLAG);
+            overflow.extend(block.iter().map(|&pos| pos as u64));
+        }
+        // Every block uses the same number of subinventory entries, so that
+        // they can be indexed directly by rank
+        subinventory.resize(start + Self::SUBS_PER_BLOCK, 0);
+    }
+}
+
+impl<
+        B: SelectHinted + BitCount,
+        const LOG2_ONES_PER_BLOCK: usize,
+        const LOG2_ONES_PER_SUB: usize,
+    > Select for SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    #[inline]
+    unsafe fn select_unchecked(&self, rank: usize) -> usize {
+        let block = rank >> LOG2_ONES_PER_BLOCK;
+        let entry = *self.inventory.as_slice().get_unchecked(block);
+        if entry & Self::SPARSE_FLAG != 0 {
+            let start = (entry & !Self::SPARSE_FLAG) as usize;
+            return *self
+                .overflow
+                .as_slice()
+                .get_unchecked(start + (rank & (Self::ONES_PER_BLOCK - 1)))
+                as usize;
+        }
+        let sub = rank >> LOG2_ONES_PER_SUB;
+        let pos = entry as usize + *self.subinventory.as_slice().get_unchecked(sub) as usize;
+        let rank_at_pos = sub << LOG2_ONES_PER_SUB;
+        self.bits.select_hinted_unchecked(rank, pos, rank_at_pos)
+    }
+}
+
+impl<B: AsRef<[usize]>> BitVec<usize, B> {
+    /// Return a [`SelectDArray`] selection structure on this bit vector.
+    ///
+    /// The number of ones is computed, and the bit vector is wrapped
+    /// in a [`CountBitVec`] before building the index.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sux::prelude::*;
+    /// let bits: BitVec = (0..1000).map(|i| i % 7 == 0).collect();
+    /// let select = bits.with_select();
+    /// assert_eq!(select.select(3), Some(21));
+    /// ```
+    pub fn with_select(self) -> SelectDArray<CountBitVec<usize, B>> {
+        let count = self.count_ones();
+        SelectDArray::new(self.with_count(count))
+    }
+}
+
+/// Forget the index.
+impl<B: SelectHinted, const LOG2_ONES_PER_BLOCK: usize, const LOG2_ONES_PER_SUB: usize> ConvertTo<B>
+    for SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    #[inline(always)]
+    fn convert_to(self) -> Result<B> {
+        Ok(self.bits)
+    }
+}
+
+/// Create and add a selection structure.
+impl<
+        B: SelectHinted + BitLength + AsRef<[usize]>,
+        const LOG2_ONES_PER_BLOCK: usize,
+        const LOG2_ONES_PER_SUB: usize,
+    > ConvertTo<SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>> for B
+{
+    #[inline(always)]
+    fn convert_to(self) -> Result<SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>> {
+        Ok(SelectDArray::new(self))
+    }
+}
+
+/// Forward [`BitLength`] to the underlying implementation.
+impl<
+        B: SelectHinted + BitLength,
+        const LOG2_ONES_PER_BLOCK: usize,
+        const LOG2_ONES_PER_SUB: usize,
+    > BitLength for SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+}
+
+/// Forward [`BitCount`] to the underlying implementation.
+impl<
+        B: SelectHinted + BitCount,
+        const LOG2_ONES_PER_BLOCK: usize,
+        const LOG2_ONES_PER_SUB: usize,
+    > BitCount for SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    #[inline(always)]
+    fn count(&self) -> usize {
+        self.bits.count()
+    }
+}
+
+/// Forward [`SelectZero`] to the underlying implementation.
+impl<
+        B: SelectHinted + SelectZero,
+        const LOG2_ONES_PER_BLOCK: usize,
+        const LOG2_ONES_PER_SUB: usize,
+    > SelectZero for SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    #[inline(always)]
+    fn select_zero(&self, rank: usize) -> Option<usize> {
+        self.bits.select_zero(rank)
+    }
+    #[inline(always)]
+    unsafe fn select_zero_unchecked(&self, rank: usize) -> usize {
+        self.bits.select_zero_unchecked(rank)
+    }
+}
+
+/// Forward [`SelectZeroHinted`] to the underlying implementation.
+impl<
+        B: SelectHinted + SelectZeroHinted,
+        const LOG2_ONES_PER_BLOCK: usize,
+        const LOG2_ONES_PER_SUB: usize,
+    > SelectZeroHinted for SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    #[inline(always)]
+    unsafe fn select_zero_hinted_unchecked(
+        &self,
+        rank: usize,
+        pos: usize,
+        rank_at_pos: usize,
+    ) -> usize {
+        self.bits
+            .select_zero_hinted_unchecked(rank, pos, rank_at_pos)
+    }
+
+    #[inline(always)]
+    fn select_zero_hinted(&self, rank: usize, pos: usize, rank_at_pos: usize) -> Option<usize> {
+        self.bits.select_zero_hinted(rank, pos, rank_at_pos)
+    }
+}
+
+/// Forward [`Rank`] to the underlying implementation.
+impl<B: SelectHinted + Rank, const LOG2_ONES_PER_BLOCK: usize, const LOG2_ONES_PER_SUB: usize> Rank
+    for SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    fn rank(&self, pos: usize) -> usize {
+        self.bits.rank(pos)
+    }
+
+    unsafe fn rank_unchecked(&self, pos: usize) -> usize {
+        self.bits.rank_unchecked(pos)
+    }
+}
+
+/// Forward [`RankZero`] to the underlying implementation.
+impl<
+        B: SelectHinted + RankZero,
+        const LOG2_ONES_PER_BLOCK: usize,
+        const LOG2_ONES_PER_SUB: usize,
+    > RankZero for SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    fn rank_zero(&self, pos: usize) -> usize {
+        self.bits.rank_zero(pos)
+    }
+
+    unsafe fn rank_zero_unchecked(&self, pos: usize) -> usize {
+        self.bits.rank_zero_unchecked(pos)
+    }
+}
+
+/// Forward `AsRef<[usize]>` to the underlying implementation.
+impl<
+        B: SelectHinted + AsRef<[usize]>,
+        const LOG2_ONES_PER_BLOCK: usize,
+        const LOG2_ONES_PER_SUB: usize,
+    > AsRef<[usize]> for SelectDArray<B, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>
+{
+    fn as_ref(&self) -> &[usize] {
+        self.bits.as_ref()
+    }
+}
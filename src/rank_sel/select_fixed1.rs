@@ -64,6 +64,21 @@ impl<B: SelectHinted + AsRef<[usize]>, const LOG2_ONES_PER_INVENTORY: usize>
         res.build_ones();
         res
     }
+
+    /// Create a selection structure from a bit vector and an inventory
+    /// that has already been computed.
+    ///
+    /// # Safety
+    /// `inventory` must contain, for each `i`, the position of the one of rank
+    /// `i`·2<sup>`LOG2_ONES_PER_INVENTORY`</sup> in `bitvec`, for all ranks
+    /// smaller than the number of ones.
+    pub unsafe fn from_raw_parts(bitvec: B, inventory: Vec<usize>) -> Self {
+        SelectFixed1 {
+            bits: bitvec,
+            inventory,
+            _marker: core::marker::PhantomData,
+        }
+    }
 }
 
 impl<
@@ -88,6 +88,7 @@ pub trait BitFieldSliceCore<W> {
     }
 }
 
+#[cfg(not(feature = "panic_free"))]
 macro_rules! panic_if_out_of_bounds {
     ($index: expr, $len: expr) => {
         if $index >= $len {
@@ -95,8 +96,21 @@ macro_rules! panic_if_out_of_bounds {
         }
     };
 }
+
+// With the `panic_free` feature, bound checks panic with a static message,
+// avoiding the formatting machinery; they cannot be removed, as safe methods
+// rely on them to call unchecked methods.
+#[cfg(feature = "panic_free")]
+macro_rules! panic_if_out_of_bounds {
+    ($index: expr, $len: expr) => {
+        if $index >= $len {
+            panic!("Index out of bounds")
+        }
+    };
+}
 pub(crate) use panic_if_out_of_bounds;
 
+#[cfg(not(feature = "panic_free"))]
 macro_rules! panic_if_value {
     ($value: expr, $mask: expr, $bit_width: expr) => {
         if $value & $mask != $value {
@@ -104,6 +118,18 @@ macro_rules! panic_if_value {
         }
     };
 }
+
+// With the `panic_free` feature, value checks panic with a static message;
+// all arguments are evaluated anyway, so that callers do not get warnings.
+#[cfg(feature = "panic_free")]
+macro_rules! panic_if_value {
+    ($value: expr, $mask: expr, $bit_width: expr) => {
+        let _ = $bit_width;
+        if $value & $mask != $value {
+            panic!("Value does not fit in the bit width")
+        }
+    };
+}
 pub(crate) use panic_if_value;
 
 macro_rules! debug_assert_bounds {
@@ -133,6 +159,34 @@ pub trait BitFieldSlice<W: Word>: BitFieldSliceCore<W> {
         panic_if_out_of_bounds!(index, self.len());
         unsafe { self.get_unchecked(index) }
     }
+
+    /// Return the values at the specified indices, or the first index
+    /// that is not in [0..[len](`BitFieldSliceCore::len`)).
+    ///
+    /// All indices are checked before reading any value, so this method
+    /// can be used safely with indices coming from untrusted sources.
+    fn get_all_checked(&self, indices: &[usize]) -> Result<Vec<W>, usize> {
+        let len = self.len();
+        if let Some(&index) = indices.iter().find(|&&index| index >= len) {
+            return Err(index);
+        }
+        // SAFETY: all indices have been checked
+        Ok(indices
+            .iter()
+            .map(|&index| unsafe { self.get_unchecked(index) })
+            .collect())
+    }
+
+    /// Return the words storing the values, if the values are stored
+    /// contiguously in a slice of words, starting from the lowest bit
+    /// of the first word, and `None` otherwise.
+    ///
+    /// This method makes it possible to copy whole words when moving values
+    /// between slices of the same bit width. The default implementation
+    /// returns `None`.
+    fn as_words(&self) -> Option<&[W]> {
+        None
+    }
 }
 
 /// A mutable slice of bit fields of constant bit width.
@@ -166,6 +220,37 @@ pub trait BitFieldSliceMut<W: Word>: BitFieldSliceCore<W> {
         }
     }
 
+    /// Set the element of the slice at the specified index, returning an
+    /// error instead of panicking if the index is not in
+    /// [0..[len](`BitFieldSliceCore::len`)) or the value does not fit in
+    /// [`BitFieldSliceCore::bit_width`] bits.
+    ///
+    /// The slice is left unchanged if an error is returned.
+    fn try_set(&mut self, index: usize, value: W) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            index < self.len(),
+            "Index out of bounds: {} >= {}",
+            index,
+            self.len()
+        );
+        let bw = self.bit_width();
+        let mask = if bw == 0 {
+            W::ZERO
+        } else {
+            W::MAX >> (W::BITS as u32 - bw as u32)
+        };
+        anyhow::ensure!(
+            value & mask == value,
+            "Value {} does not fit in {} bits",
+            value,
+            bw
+        );
+        unsafe {
+            self.set_unchecked(index, value);
+        }
+        Ok(())
+    }
+
     /// Set all values to zero
     fn reset(&mut self);
 }
@@ -381,6 +466,11 @@ macro_rules! impl_ref {
                 debug_assert_bounds!(index, self.len());
                 *self.as_ref().get_unchecked(index)
             }
+
+            #[inline(always)]
+            fn as_words(&self) -> Option<&[$ty]> {
+                Some(self.as_ref())
+            }
         }
     )*};
 }
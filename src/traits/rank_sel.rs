@@ -186,3 +186,78 @@ pub trait SelectZeroHinted {
     /// and its rank.
     fn select_zero_hinted(&self, rank: usize, hint_pos: usize, hint_rank: usize) -> Option<usize>;
 }
+
+/// Self-checks of the consistency between [`Rank`] and [`Select`].
+///
+/// This trait is implemented for every type implementing both [`Rank`] and
+/// [`Select`], and it is mainly useful for testing and fuzzing.
+pub trait CheckRankSelect: Rank + Select {
+    /// The maximum number of ranks and positions checked by
+    /// [`check_rank_select`](CheckRankSelect::check_rank_select).
+    const MAX_CHECKS: usize = 1 << 16;
+
+    /// Check that the number of ones is consistent with the rank of the
+    /// [length](BitLength::len), that `rank(select(k)) == k` for every
+    /// rank `k`, and that `select(rank(p)) == p` for every position `p`
+    /// containing a one.
+    ///
+    /// If there are more than [`MAX_CHECKS`](CheckRankSelect::MAX_CHECKS)
+    /// ranks or positions, only about `MAX_CHECKS` evenly spaced
+    /// ranks or positions are checked.
+    ///
+    /// This method will return an error describing the first violation found.
+    fn check_rank_select(&self) -> anyhow::Result<()> {
+        let len = self.len();
+        let count = self.count();
+        let rank_len = self.rank(len);
+        anyhow::ensure!(
+            rank_len == count,
+            "The rank of the length ({}) is {}, but the number of ones is {}",
+            len,
+            rank_len,
+            count
+        );
+
+        for k in (0..count).step_by(count.div_ceil(Self::MAX_CHECKS).max(1)) {
+            let Some(pos) = self.select(k) else {
+                anyhow::bail!("select({}) returned None, but there are {} ones", k, count);
+            };
+            anyhow::ensure!(
+                pos < len,
+                "select({}) = {} is not smaller than the length {}",
+                k,
+                pos,
+                len
+            );
+            let (rank, rank_next) = (self.rank(pos), self.rank(pos + 1));
+            anyhow::ensure!(
+                rank == k && rank_next == k + 1,
+                "select({}) = {}, but rank({}) = {} and rank({}) = {}",
+                k,
+                pos,
+                pos,
+                rank,
+                pos + 1,
+                rank_next
+            );
+        }
+
+        for pos in (0..len).step_by(len.div_ceil(Self::MAX_CHECKS).max(1)) {
+            let rank = self.rank(pos);
+            if self.rank(pos + 1) == rank + 1 {
+                let select = self.select(rank);
+                anyhow::ensure!(
+                    select == Some(pos),
+                    "Position {} contains a one of rank {}, but select({}) = {:?}",
+                    pos,
+                    rank,
+                    rank,
+                    select
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Rank + Select + ?Sized> CheckRankSelect for T {}
@@ -0,0 +1,149 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Access-pattern hints for memory-mapped structures.
+
+When a structure is [memory-mapped](epserde::deser::Deserialize::mmap),
+the kernel pages it in on demand using heuristics that are tuned for
+sequential access. [ε-serde](epserde) makes it possible to pass
+[`Flags::SEQUENTIAL`] or [`Flags::RANDOM_ACCESS`] at mapping time, but
+hints cannot be changed afterwards, and cannot be given to
+single components of a structure.
+
+The [`Advise`] trait wraps `madvise()` to give an [`Advice`] on the memory
+backing a structure: it is implemented by bit vectors, bit-field vectors,
+and the Elias–Fano representation of monotone sequences, and by a [`MemCase`]
+wrapping any of them. [`mmap_with_advice`] maps a structure and gives
+an initial advice. For example, random point queries on a large
+memory-mapped [`EliasFano`](crate::dict::EliasFano) benefit
+from [`Advice::Random`] followed by [`Advice::WillNeed`]:
+
+```rust
+use epserde::prelude::*;
+use sux::prelude::*;
+
+# fn main() -> anyhow::Result<()> {
+let ef: EliasFano = EliasFano::from_sorted_slice(&[0, 3, 10, 1000])?;
+let path = std::env::temp_dir().join("advice_doctest");
+ef.store(&path)?;
+let mapped = mmap_with_advice::<EliasFano>(&path, Flags::empty(), Advice::Random)?;
+mapped.advise(Advice::WillNeed)?;
+assert_eq!(mapped.get(2), 10);
+# std::fs::remove_file(&path)?;
+# Ok(())
+# }
+```
+
+Hints are ignored on platforms other than Unix.
+
+*/
+
+use crate::bits::{BitOrder, BitVec, CountBitVec};
+use crate::traits::Word;
+use anyhow::Result;
+use epserde::prelude::*;
+use std::path::Path;
+
+/// An access-pattern hint for the memory backing a structure.
+///
+/// Only hints that do not alter the content of the memory are available,
+/// as the memory might be allocated on the heap (e.g., by [`Deserialize::load_mem`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Advice {
+    /// No special treatment (`MADV_NORMAL`).
+    Normal,
+    /// Pages will be accessed in random order, so read-ahead is
+    /// counterproductive (`MADV_RANDOM`).
+    Random,
+    /// Pages will be accessed in sequential order, so aggressive
+    /// read-ahead is useful (`MADV_SEQUENTIAL`).
+    Sequential,
+    /// Pages will be accessed soon, so they should be read in
+    /// advance (`MADV_WILLNEED`).
+    WillNeed,
+}
+
+/// Give an access-pattern hint for the memory containing a slice.
+///
+/// The hint is given for all the pages overlapping the slice. Empty
+/// slices are ignored.
+pub fn advise_slice<T>(slice: &[T], advice: Advice) -> Result<()> {
+    if slice.is_empty() {
+        return Ok(());
+    }
+    #[cfg(unix)]
+    {
+        let advice = match advice {
+            Advice::Normal => libc::MADV_NORMAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+        };
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let start = slice.as_ptr() as usize;
+        let end = start + std::mem::size_of_val(slice);
+        let aligned_start = start - start % page_size;
+        // SAFETY: the range covers only pages containing the slice, and
+        // the hints above do not modify the content of the memory
+        let result = unsafe {
+            libc::madvise(
+                aligned_start as *mut libc::c_void,
+                end - aligned_start,
+                advice,
+            )
+        };
+        anyhow::ensure!(
+            result == 0,
+            "madvise() failed: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+    #[cfg(not(unix))]
+    let _ = advice;
+    Ok(())
+}
+
+/// A structure whose backing memory can receive access-pattern hints.
+pub trait Advise {
+    /// Give an access-pattern hint for the memory backing this structure.
+    fn advise(&self, advice: Advice) -> Result<()>;
+}
+
+impl<S: Advise> Advise for MemCase<S> {
+    fn advise(&self, advice: Advice) -> Result<()> {
+        (**self).advise(advice)
+    }
+}
+
+impl<W: Word, B: AsRef<[W]>, O: BitOrder> Advise for BitVec<W, B, O> {
+    fn advise(&self, advice: Advice) -> Result<()> {
+        advise_slice(self.words(), advice)
+    }
+}
+
+impl<W: Word, B: AsRef<[W]>> Advise for CountBitVec<W, B> {
+    fn advise(&self, advice: Advice) -> Result<()> {
+        advise_slice(self.as_ref(), advice)
+    }
+}
+
+/// Memory-map a structure using [`Deserialize::mmap`] and give an
+/// initial access-pattern hint for its memory.
+pub fn mmap_with_advice<'a, T: Deserialize>(
+    path: impl AsRef<Path>,
+    flags: Flags,
+    advice: Advice,
+) -> Result<MemCase<<T as DeserializeInner>::DeserType<'a>>>
+where
+    <T as DeserializeInner>::DeserType<'a>: Advise,
+{
+    let mapped = T::mmap(path, flags)?;
+    mapped.advise(advice)?;
+    Ok(mapped)
+}
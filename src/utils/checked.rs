@@ -0,0 +1,198 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Serialization with integrity checks.
+
+[ε-serde](epserde) checks the header of a serialized file, but a truncated
+or corrupted payload is detected only when (and if) it makes the
+deserialization fail, and zero-copy deserialization might
+access the missing or corrupted data directly.
+
+[`store_checked`] serializes a structure and appends to the file a fixed-size
+trailer containing a magic cookie, a format version, the length of the payload
+and its [XXH3](xxhash_rust::xxh3) hash. The `*_checked` loading functions
+verify the trailer and the hash of the payload before delegating to the
+corresponding method of [`Deserialize`], returning an error on mismatch.
+
+The check data is stored in a trailer rather than in a header so that the
+serialized structure is at the start of the file, where ε-serde expects it: files
+written by [`store_checked`] can be still loaded or memory-mapped with
+the standard methods of [`Deserialize`], and memory mapping remains zero-copy.
+
+Note that the payload is verified before being loaded or mapped, so
+the check cannot detect modifications happening in between.
+
+```rust
+use epserde::prelude::*;
+use sux::prelude::*;
+
+# fn main() -> anyhow::Result<()> {
+let ef: EliasFano = EliasFano::from_sorted_slice(&[0, 3, 10, 1000])?;
+let path = std::env::temp_dir().join("checked_doctest");
+store_checked(&ef, &path)?;
+let mapped = mmap_checked::<EliasFano>(&path, Flags::empty())?;
+assert_eq!(mapped.get(2), 10);
+
+// A truncated file is rejected
+let file = std::fs::OpenOptions::new().write(true).open(&path)?;
+file.set_len(file.metadata()?.len() - 1)?;
+assert!(mmap_checked::<EliasFano>(&path, Flags::empty()).is_err());
+# std::fs::remove_file(&path)?;
+# Ok(())
+# }
+```
+
+*/
+
+use anyhow::{ensure, Context, Result};
+use epserde::prelude::*;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use xxhash_rust::xxh3::Xxh3;
+
+/// The magic cookie at the start of the trailer.
+pub const CHECKED_MAGIC: u64 = u64::from_le_bytes(*b"suxcheck");
+/// The version of the format of the trailer.
+pub const CHECKED_VERSION: u32 = 1;
+/// The length in bytes of the trailer: magic cookie, version, four
+/// reserved bytes, length of the payload and hash of the payload,
+/// all in little-endian byte order.
+const TRAILER_LEN: u64 = 32;
+
+/// A writer computing the length and the hash of the data written.
+struct HashWriter<W> {
+    inner: W,
+    hasher: Xxh3,
+    len: u64,
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Serialize a structure with ε-serde to a file, appending a trailer with
+/// the length and the hash of the serialized data.
+pub fn store_checked<T: Serialize>(value: &T, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file = File::create(path).with_context(|| format!("Cannot create {}", path.display()))?;
+    let mut writer = HashWriter {
+        inner: BufWriter::new(file),
+        hasher: Xxh3::new(),
+        len: 0,
+    };
+    value.serialize(&mut writer)?;
+    let HashWriter {
+        mut inner,
+        hasher,
+        len,
+    } = writer;
+    inner.write_all(&CHECKED_MAGIC.to_le_bytes())?;
+    inner.write_all(&CHECKED_VERSION.to_le_bytes())?;
+    inner.write_all(&[0; 4])?;
+    inner.write_all(&len.to_le_bytes())?;
+    inner.write_all(&hasher.digest().to_le_bytes())?;
+    inner.flush()?;
+    Ok(())
+}
+
+/// Verify the trailer of a file written by [`store_checked`] and the hash
+/// of the serialized data, returning an error if they do not match.
+pub fn verify_checked(path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let mut file = File::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+    let file_len = file.metadata()?.len();
+    ensure!(
+        file_len >= TRAILER_LEN,
+        "File {} is too short ({} bytes) to contain a trailer",
+        path.display(),
+        file_len
+    );
+
+    let mut trailer = [0; TRAILER_LEN as usize];
+    file.seek(SeekFrom::Start(file_len - TRAILER_LEN))?;
+    file.read_exact(&mut trailer)?;
+    let magic = u64::from_le_bytes(trailer[0..8].try_into().unwrap());
+    let version = u32::from_le_bytes(trailer[8..12].try_into().unwrap());
+    let len = u64::from_le_bytes(trailer[16..24].try_into().unwrap());
+    let hash = u64::from_le_bytes(trailer[24..32].try_into().unwrap());
+    ensure!(
+        magic == CHECKED_MAGIC,
+        "Wrong magic cookie {:#018x} in the trailer of {} (truncated or not written by store_checked?)",
+        magic,
+        path.display()
+    );
+    ensure!(
+        version == CHECKED_VERSION,
+        "Unsupported trailer version {} in {} (expected {})",
+        version,
+        path.display(),
+        CHECKED_VERSION
+    );
+    ensure!(
+        len == file_len - TRAILER_LEN,
+        "Wrong payload length in {}: the trailer declares {} bytes, but the file contains {}",
+        path.display(),
+        len,
+        file_len - TRAILER_LEN
+    );
+
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(file).take(len);
+    let mut hasher = Xxh3::new();
+    let mut buf = [0; 1 << 16];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    ensure!(
+        hasher.digest() == hash,
+        "Hash mismatch in {}: the file is corrupted",
+        path.display()
+    );
+    Ok(())
+}
+
+/// Verify a file written by [`store_checked`] and fully deserialize it using
+/// [`Deserialize::load_full`].
+pub fn load_full_checked<T: Deserialize>(path: impl AsRef<Path>) -> Result<T> {
+    verify_checked(&path)?;
+    Ok(T::load_full(path)?)
+}
+
+/// Verify a file written by [`store_checked`] and load it in memory
+/// using [`Deserialize::load_mem`].
+pub fn load_mem_checked<'a, T: Deserialize>(
+    path: impl AsRef<Path>,
+) -> Result<MemCase<<T as DeserializeInner>::DeserType<'a>>> {
+    verify_checked(&path)?;
+    T::load_mem(path)
+}
+
+/// Verify a file written by [`store_checked`] and memory-map it
+/// using [`Deserialize::mmap`].
+pub fn mmap_checked<'a, T: Deserialize>(
+    path: impl AsRef<Path>,
+    flags: Flags,
+) -> Result<MemCase<<T as DeserializeInner>::DeserType<'a>>> {
+    verify_checked(&path)?;
+    T::mmap(path, flags)
+}
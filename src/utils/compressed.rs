@@ -0,0 +1,122 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Compressed serialization.
+
+Some structures (e.g., [`BitFieldVec`](crate::bits::BitFieldVec)s
+with skewed values) compress well, and when they are stored or transmitted
+it might be convenient to trade decompression time for space.
+
+[`serialize_compressed`] writes a fixed-size header containing a magic cookie
+and a format version, followed by a [zstd] stream containing
+the ε-serde serialization of a structure; [`store_compressed`] does the
+same on a file. Compressed data cannot be memory-mapped, so [`load_compressed`]
+decompresses the payload in memory and then ε-deserializes it, returning
+an [`OwnedCase`] that keeps the decompressed buffer alive,
+whereas [`load_full_compressed`] fully deserializes the structure directly from
+the zstd stream.
+
+The loading functions return an error if the header is missing;
+conversely, the standard methods of [`Deserialize`] return
+an error on compressed files, as the magic cookie of the header
+does not match ε-serde's.
+
+```rust
+use epserde::prelude::*;
+use sux::prelude::*;
+
+# fn main() -> anyhow::Result<()> {
+let mut bfv = BitFieldVec::<usize>::new(10, 1000);
+bfv.set(500, 1);
+let path = std::env::temp_dir().join("compressed_doctest");
+store_compressed(&bfv, &path)?;
+let loaded = load_compressed::<BitFieldVec<usize>>(&path)?;
+assert_eq!(loaded.uncase().get(500), 1);
+assert!(<BitFieldVec<usize>>::load_mem(&path).is_err());
+# std::fs::remove_file(&path)?;
+# Ok(())
+# }
+```
+
+*/
+
+use crate::utils::{from_owned_bytes, OwnedCase};
+use anyhow::{ensure, Context, Result};
+use epserde::prelude::*;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// The magic cookie at the start of the header.
+pub const COMPRESSED_MAGIC: u64 = u64::from_le_bytes(*b"suxzstd\0");
+/// The version of the format of the header.
+pub const COMPRESSED_VERSION: u32 = 1;
+/// The length in bytes of the header: magic cookie, version, and four
+/// reserved bytes, all in little-endian byte order.
+const HEADER_LEN: usize = 16;
+
+/// Serialize a structure with ε-serde, compressing the result with zstd.
+pub fn serialize_compressed<T: Serialize>(value: &T, writer: &mut impl Write) -> Result<()> {
+    writer.write_all(&COMPRESSED_MAGIC.to_le_bytes())?;
+    writer.write_all(&COMPRESSED_VERSION.to_le_bytes())?;
+    writer.write_all(&[0; 4])?;
+    let mut encoder = zstd::Encoder::new(writer, 0)?;
+    value.serialize(&mut encoder)?;
+    encoder.finish()?.flush()?;
+    Ok(())
+}
+
+/// Serialize a structure with ε-serde to a file, compressing the result
+/// with zstd.
+pub fn store_compressed<T: Serialize>(value: &T, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let file = File::create(path).with_context(|| format!("Cannot create {}", path.display()))?;
+    serialize_compressed(value, &mut BufWriter::new(file))
+}
+
+/// Open a file written by [`store_compressed`], check its header,
+/// and return a decoder for the payload.
+fn open_compressed(path: &Path) -> Result<zstd::Decoder<'static, BufReader<File>>> {
+    let mut file = File::open(path).with_context(|| format!("Cannot open {}", path.display()))?;
+    let mut header = [0; HEADER_LEN];
+    file.read_exact(&mut header)
+        .with_context(|| format!("File {} is too short to contain a header", path.display()))?;
+    let magic = u64::from_le_bytes(header[0..8].try_into().unwrap());
+    let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    ensure!(
+        magic == COMPRESSED_MAGIC,
+        "Wrong magic cookie {:#018x} in the header of {} (not written by store_compressed?)",
+        magic,
+        path.display()
+    );
+    ensure!(
+        version == COMPRESSED_VERSION,
+        "Unsupported header version {} in {} (expected {})",
+        version,
+        path.display(),
+        COMPRESSED_VERSION
+    );
+    Ok(zstd::Decoder::new(file)?)
+}
+
+/// Load a file written by [`store_compressed`], decompressing it in memory
+/// and ε-deserializing the result.
+pub fn load_compressed<T: Deserialize>(path: impl AsRef<Path>) -> Result<OwnedCase<T>> {
+    let mut decoder = open_compressed(path.as_ref())?;
+    let mut bytes = vec![];
+    decoder.read_to_end(&mut bytes)?;
+    from_owned_bytes::<T>(bytes)
+}
+
+/// Load a file written by [`store_compressed`], fully deserializing it
+/// from the decompressed stream.
+pub fn load_full_compressed<T: Deserialize>(path: impl AsRef<Path>) -> Result<T> {
+    let mut decoder = open_compressed(path.as_ref())?;
+    Ok(T::deserialize_full(&mut decoder)?)
+}
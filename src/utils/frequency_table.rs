@@ -0,0 +1,131 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Compact frequency tables for small integer keys.
+
+A [`FrequencyTable`] counts the occurrences of keys in the range `0..=max_key`
+using a [`BitFieldVec`] indexed by key. The bit width of the counters is
+chosen initially depending on the expected number of occurrences, and
+it is increased automatically whenever a counter would overflow:
+
+```rust
+use sux::utils::FrequencyTable;
+
+# fn main() -> anyhow::Result<()> {
+let table = FrequencyTable::from_keys(3, [1, 3, 1, 1, 0])?;
+assert_eq!(table.get(0), 1);
+assert_eq!(table.get(1), 3);
+assert_eq!(table.get(2), 0);
+assert_eq!(table.get(3), 1);
+# Ok(())
+# }
+```
+
+*/
+
+use crate::prelude::*;
+use anyhow::{ensure, Result};
+use mem_dbg::*;
+
+/// A table of counters for the keys in the range `0..=max_key`.
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct FrequencyTable {
+    counts: BitFieldVec,
+}
+
+impl FrequencyTable {
+    /// Create a frequency table for the keys in the range `0..=max_key` with
+    /// all counters set to zero, using initially counters of `bit_width` bits.
+    pub fn new(max_key: usize, bit_width: usize) -> Self {
+        Self {
+            counts: BitFieldVec::new(bit_width.clamp(1, usize::BITS as usize), max_key + 1),
+        }
+    }
+
+    /// Create a frequency table counting the occurrences of the given keys,
+    /// which must be in the range `0..=max_key`.
+    ///
+    /// The initial bit width of the counters is the one sufficient to
+    /// represent the average number of occurrences of a key, as estimated
+    /// by the lower bound of the [size hint](Iterator::size_hint) of `keys`.
+    ///
+    /// This method will return an error if a key is larger than `max_key`.
+    pub fn from_keys(max_key: usize, keys: impl IntoIterator<Item = usize>) -> Result<Self> {
+        let keys = keys.into_iter();
+        let expected_count = keys.size_hint().0 / (max_key + 1);
+        let mut table = Self::new(max_key, bit_width_of(expected_count));
+        for key in keys {
+            table.increment(key)?;
+        }
+        Ok(table)
+    }
+
+    /// Increment the counter associated with a key, widening all counters
+    /// if the counter would overflow.
+    ///
+    /// This method will return an error if the key is larger than the
+    /// maximum key.
+    pub fn increment(&mut self, key: usize) -> Result<()> {
+        ensure!(
+            key < self.counts.len(),
+            "Key {} is larger than the maximum key {}",
+            key,
+            self.counts.len() - 1
+        );
+        // SAFETY: we checked the key
+        let count = unsafe { self.counts.get_unchecked(key) };
+        if count == self.counts.mask() {
+            self.widen();
+        }
+        unsafe { self.counts.set_unchecked(key, count + 1) };
+        Ok(())
+    }
+
+    /// Increase by one the bit width of all counters.
+    fn widen(&mut self) {
+        let mut counts = BitFieldVec::new(self.counts.bit_width() + 1, self.counts.len());
+        for (key, count) in self.counts.iter().enumerate() {
+            // SAFETY: the key is within bounds and the new bit width is larger
+            unsafe { counts.set_unchecked(key, count) };
+        }
+        self.counts = counts;
+    }
+
+    /// Return the number of occurrences of a key.
+    ///
+    /// # Panics
+    /// Panics if the key is larger than the maximum key.
+    pub fn get(&self, key: usize) -> usize {
+        self.counts.get(key)
+    }
+
+    /// Return the number of keys in the table, that is, the maximum key plus one.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Return the current bit width of the counters.
+    pub fn bit_width(&self) -> usize {
+        self.counts.bit_width()
+    }
+
+    /// Return the underlying vector of counters, indexed by key.
+    pub fn into_inner(self) -> BitFieldVec {
+        self.counts
+    }
+}
+
+impl AsRef<BitFieldVec> for FrequencyTable {
+    fn as_ref(&self) -> &BitFieldVec {
+        &self.counts
+    }
+}
@@ -9,11 +9,35 @@
 
 Utility traits and implementations.
 
+Selection inside a word (`select_in_word`) is provided by [`common_traits::SelectInWord`].
+
 */
 
+pub mod advice;
+pub use crate::utils::advice::*;
+pub mod checked;
+pub use crate::utils::checked::*;
+pub mod compressed;
+pub use crate::utils::compressed::*;
+
+pub mod frequency_table;
+pub use crate::utils::frequency_table::*;
+
 pub mod lenders;
 pub use crate::utils::lenders::*;
 
+pub mod owned_case;
+pub use crate::utils::owned_case::*;
+
+pub mod pack;
+pub use crate::utils::pack::*;
+
+pub mod permutation;
+pub use crate::utils::permutation::*;
+
+pub mod representation;
+pub use crate::utils::representation::*;
+
 pub mod sig_store;
 pub use crate::utils::sig_store::*;
 
@@ -0,0 +1,167 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+ε-deserialization from in-memory buffers.
+
+The methods of [`Deserialize`] loading or mapping a structure need a path.
+When the serialized data is already in memory (e.g., because it has been
+received from the network) [`deserialize_from_bytes`] ε-deserializes a
+structure borrowing the bytes, and [`from_owned_bytes`] ε-deserializes a structure
+taking ownership of the bytes, returning an [`OwnedCase`] that keeps them alive.
+
+```rust
+use epserde::prelude::*;
+use sux::prelude::*;
+
+# fn main() -> anyhow::Result<()> {
+let ef: EliasFano = EliasFano::from_sorted_slice(&[0, 3, 10, 1000])?;
+let mut bytes = vec![];
+ef.serialize(&mut bytes)?;
+
+let owned = from_owned_bytes::<EliasFano>(bytes)?;
+assert_eq!(owned.uncase().get(2), 10);
+# Ok(())
+# }
+```
+
+The structure can be accessed only through [`OwnedCase::uncase`], which
+ties its lifetime to that of the [`OwnedCase`], so it cannot outlive
+the buffer it borrows from:
+
+```compile_fail
+use epserde::prelude::*;
+use sux::prelude::*;
+
+# fn main() -> anyhow::Result<()> {
+let v = BitFieldVec::<usize>::new(10, 100);
+let mut bytes = vec![];
+v.serialize(&mut bytes)?;
+
+let owned = from_owned_bytes::<BitFieldVec<usize>>(bytes)?;
+let escaped = owned.uncase().clone();
+drop(owned);
+assert_eq!(escaped.get(0), 0);
+# Ok(())
+# }
+```
+
+*/
+
+use anyhow::Result;
+use epserde::prelude::*;
+
+/// The alignment guaranteed to the buffer of an [`OwnedCase`], which is
+/// sufficient for all unsigned integer types up to `u128`.
+const ALIGNMENT: usize = core::mem::align_of::<u128>();
+
+/// ε-deserialize a structure from a slice of bytes, borrowing them.
+///
+/// This is a convenience wrapper around [`Deserialize::deserialize_eps`]
+/// returning a [`MemCase`] with no backend, which can be used in place of
+/// a [`MemCase`] returned by [`Deserialize::mmap`] or [`Deserialize::load_mem`].
+///
+/// # Errors
+/// Besides the errors returned by ε-serde for invalid data, an alignment
+/// error is returned if the bytes are not suitably aligned for the
+/// structure: use [`from_owned_bytes`] if the alignment of the
+/// buffer is not under your control.
+pub fn deserialize_from_bytes<T: Deserialize>(
+    bytes: &[u8],
+) -> Result<MemCase<<T as DeserializeInner>::DeserType<'_>>> {
+    Ok(MemCase::encase(T::deserialize_eps(bytes)?))
+}
+
+/// The buffer of an [`OwnedCase`].
+enum Buffer {
+    /// The original buffer, if it was suitably aligned.
+    Bytes(Vec<u8>),
+    /// An aligned copy of the original buffer.
+    Aligned(Vec<u128>),
+}
+
+impl Buffer {
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            Buffer::Bytes(bytes) => bytes,
+            // SAFETY: the vector contains initialized integers
+            Buffer::Aligned(words) => unsafe {
+                core::slice::from_raw_parts(
+                    words.as_ptr() as *const u8,
+                    core::mem::size_of_val(words.as_slice()),
+                )
+            },
+        }
+    }
+}
+
+/// An ε-deserialized structure together with the buffer it borrows from.
+///
+/// Instances are returned by [`from_owned_bytes`]. Differently from a
+/// [`MemCase`], an [`OwnedCase`] does not implement [`Deref`](core::ops::Deref):
+/// the structure is accessed using [`OwnedCase::uncase`], whose result
+/// borrows from the [`OwnedCase`].
+pub struct OwnedCase<T: Deserialize> {
+    /// The structure, which must be dropped before the buffer. The
+    /// `'static` lifetime is fictitious, and must never be exposed.
+    value: <T as DeserializeInner>::DeserType<'static>,
+    /// The buffer the structure borrows from; its content is never moved
+    /// or modified.
+    _buffer: Buffer,
+}
+
+impl<T: Deserialize> OwnedCase<T> {
+    /// Return a reference to the ε-deserialized structure, with a lifetime
+    /// bound to that of this [`OwnedCase`].
+    #[inline(always)]
+    pub fn uncase(&self) -> &<T as DeserializeInner>::DeserType<'_> {
+        // SAFETY: ε-deserialized types borrow from the buffer only through
+        // shared references, so they are covariant in their lifetime, and
+        // shortening it to that of self is sound.
+        unsafe {
+            &*(&self.value as *const <T as DeserializeInner>::DeserType<'static>
+                as *const <T as DeserializeInner>::DeserType<'_>)
+        }
+    }
+}
+
+/// ε-deserialize a structure from a vector of bytes, taking ownership
+/// of the vector.
+///
+/// If the vector is aligned to 16 bytes, which is sufficient for all
+/// unsigned integer types up to `u128`, the structure borrows directly the
+/// content of the vector; otherwise, the content is copied into a suitably
+/// aligned buffer. In both cases, the buffer is kept alive by the returned
+/// [`OwnedCase`].
+pub fn from_owned_bytes<T: Deserialize>(bytes: Vec<u8>) -> Result<OwnedCase<T>> {
+    let buffer = if bytes.as_ptr() as usize & (ALIGNMENT - 1) == 0 {
+        Buffer::Bytes(bytes)
+    } else {
+        let mut words = vec![0_u128; bytes.len().div_ceil(ALIGNMENT)];
+        // SAFETY: the words contain at least bytes.len() bytes
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                bytes.as_ptr(),
+                words.as_mut_ptr() as *mut u8,
+                bytes.len(),
+            );
+        }
+        Buffer::Aligned(words)
+    };
+    // SAFETY: the heap memory of the buffer is not moved when the buffer
+    // is moved into the OwnedCase, which drops the structure before the
+    // buffer; the structure is accessible only through OwnedCase::uncase,
+    // which does not expose the 'static lifetime.
+    let bytes: &'static [u8] =
+        unsafe { core::mem::transmute::<&[u8], &'static [u8]>(buffer.as_bytes()) };
+    let value = T::deserialize_eps(bytes)?;
+    Ok(OwnedCase {
+        value,
+        _buffer: buffer,
+    })
+}
@@ -0,0 +1,226 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Automatic choice of a compact structure for a list of integers.
+
+[`pack()`] inspects a slice of integers (is it sorted? does it contain
+long runs of equal values? what is the largest value?) and stores it
+in the most compact among the structures of this crate that can represent it,
+returning a [`PackedInts`]. All variants are accessed by index
+through the [`IndexedDict`] trait, so users do not need to know the
+details of each structure:
+
+```rust
+use sux::prelude::*;
+
+# fn main() -> anyhow::Result<()> {
+// Sparse increasing sequences are stored using the Elias–Fano representation
+let values = (0..1000).map(|x| x * 1000).collect::<Vec<_>>();
+let packed = pack(&values)?;
+assert!(matches!(packed, PackedInts::EliasFano(_)));
+assert_eq!(packed.get(10), 10_000);
+
+// Long runs of equal values are run-length encoded
+let values = [vec![3; 1000], vec![1_000_000; 1000]].concat();
+let packed = pack(&values)?;
+assert!(matches!(packed, PackedInts::RunLength(_)));
+assert_eq!(packed.get(1500), 1_000_000);
+# Ok(())
+# }
+```
+
+*/
+
+use crate::prelude::*;
+use anyhow::Result;
+use mem_dbg::*;
+
+/// The Elias–Fano representation with selection structures used by
+/// [`PackedInts`] and [`RunLengthInts`].
+type IndexedEliasFano = EliasFano<SelectZeroFixed2<SelectFixed2>>;
+
+/// Build an [`IndexedEliasFano`] from a nonempty non-decreasing sequence.
+fn elias_fano(
+    values: impl IntoIterator<Item = usize>,
+    n: usize,
+    u: usize,
+) -> Result<IndexedEliasFano> {
+    let mut efb = EliasFanoBuilder::new(n, u);
+    for value in values {
+        efb.push(value)?;
+    }
+    let ef: EliasFano<SelectFixed2> = efb.build().convert_to()?;
+    ef.convert_to()
+}
+
+/// A run-length encoded list of integers.
+///
+/// The list is divided in maximal runs of equal values: the value of each
+/// run is stored in a [`BitFieldVec`], and the starting positions of the runs
+/// in an [`EliasFano`] structure.
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct RunLengthInts {
+    /// The value of each run.
+    values: BitFieldVec,
+    /// The starting position of each run.
+    starts: IndexedEliasFano,
+    /// The number of values.
+    len: usize,
+}
+
+impl RunLengthInts {
+    /// Create a run-length encoded list containing the given values.
+    pub fn new(values: &[usize]) -> Result<Self> {
+        let max = values.iter().copied().max().unwrap_or(0);
+        let mut run_values = BitFieldVec::new(bit_width_of(max), 0);
+        let mut run_starts = vec![];
+        for (i, &value) in values.iter().enumerate() {
+            if i == 0 || values[i - 1] != value {
+                run_values.push(value);
+                run_starts.push(i);
+            }
+        }
+        let starts = if run_starts.is_empty() {
+            // The Elias–Fano representation needs at least one value
+            elias_fano([0], 1, 0)?
+        } else {
+            elias_fano(
+                run_starts.iter().copied(),
+                run_starts.len(),
+                values.len() - 1,
+            )?
+        };
+        Ok(Self {
+            values: run_values,
+            starts,
+            len: values.len(),
+        })
+    }
+
+    /// Return the number of runs.
+    pub fn num_runs(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl IndexedDict for RunLengthInts {
+    type Input = usize;
+    type Output = usize;
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        let (run, _) = self.starts.pred_unchecked::<false>(&index);
+        self.values.get_unchecked(run)
+    }
+}
+
+/// A list of integers stored by [`pack()`] in the most compact
+/// among the available structures.
+///
+/// All variants are accessible by index using [`IndexedDict`].
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub enum PackedInts {
+    /// Values stored in a [`BitFieldVec`] using the minimum bit width
+    /// necessary to represent the largest value.
+    BitFieldVec(BitFieldVec),
+    /// Strictly increasing values stored as the positions of the ones of a bit
+    /// vector, which are retrieved by selection.
+    DenseBitmap(SelectFixed2),
+    /// Non-decreasing values stored using the Elias–Fano representation.
+    EliasFano(IndexedEliasFano),
+    /// Values stored by [run-length encoding](RunLengthInts).
+    RunLength(RunLengthInts),
+}
+
+/// Store the given values in the most compact structure among
+/// those of [`PackedInts`] that can represent them.
+///
+/// Sizes are estimated using [`Representation::size_in_bits`], excluding
+/// selection structures. Ties are broken in favor of the simplest structure, that is,
+/// [`BitFieldVec`](PackedInts::BitFieldVec), then
+/// [`DenseBitmap`](PackedInts::DenseBitmap), [`EliasFano`](PackedInts::EliasFano), and
+/// finally [`RunLength`](PackedInts::RunLength).
+pub fn pack(values: &[usize]) -> Result<PackedInts> {
+    let n = values.len();
+    let max = values.iter().copied().max().unwrap_or(0);
+    let sorted = values.windows(2).all(|w| w[0] <= w[1]);
+    let distinct = values.windows(2).all(|w| w[0] != w[1]);
+    let num_runs = values.windows(2).filter(|w| w[0] != w[1]).count() + (n != 0) as usize;
+
+    let plain_size = Representation::PlainPacked.size_in_bits(max, n);
+    let dense_size = (n != 0 && sorted && distinct)
+        .then(|| Representation::DenseBitmap.size_in_bits(max, n))
+        .flatten();
+    let elias_fano_size = (n != 0 && sorted)
+        .then(|| Representation::EliasFano.size_in_bits(max, n))
+        .flatten();
+    let run_length_size = (n != 0)
+        .then(|| {
+            Representation::PlainPacked
+                .size_in_bits(max, num_runs)?
+                .checked_add(Representation::EliasFano.size_in_bits(n - 1, num_runs)?)
+        })
+        .flatten();
+
+    let best = [plain_size, dense_size, elias_fano_size, run_length_size]
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, size)| size.map(|size| (size, i)))
+        .min()
+        .unwrap()
+        .1;
+
+    Ok(match best {
+        0 => {
+            let mut bit_field_vec = BitFieldVec::new(bit_width_of(max), n);
+            for (i, &value) in values.iter().enumerate() {
+                // SAFETY: i < n and the bit width is sufficient to represent max
+                unsafe { bit_field_vec.set_unchecked(i, value) };
+            }
+            PackedInts::BitFieldVec(bit_field_vec)
+        }
+        1 => {
+            let mut bits: BitVec = BitVec::new(max + 1);
+            for &value in values {
+                bits.set(value, true);
+            }
+            let bits: CountBitVec = bits.into();
+            PackedInts::DenseBitmap(bits.convert_to()?)
+        }
+        2 => PackedInts::EliasFano(elias_fano(values.iter().copied(), n, max)?),
+        _ => PackedInts::RunLength(RunLengthInts::new(values)?),
+    })
+}
+
+impl IndexedDict for PackedInts {
+    type Input = usize;
+    type Output = usize;
+
+    fn len(&self) -> usize {
+        match self {
+            PackedInts::BitFieldVec(v) => v.len(),
+            PackedInts::DenseBitmap(s) => s.count(),
+            PackedInts::EliasFano(ef) => ef.len(),
+            PackedInts::RunLength(r) => r.len(),
+        }
+    }
+
+    unsafe fn get_unchecked(&self, index: usize) -> usize {
+        match self {
+            PackedInts::BitFieldVec(v) => v.get_unchecked(index),
+            PackedInts::DenseBitmap(s) => s.select_unchecked(index),
+            PackedInts::EliasFano(ef) => ef.get_unchecked(index),
+            PackedInts::RunLength(r) => r.get_unchecked(index),
+        }
+    }
+}
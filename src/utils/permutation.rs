@@ -0,0 +1,186 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Compact permutations.
+
+A [`Permutation`] of `0..n` stores its forward mapping in a [`BitFieldVec`]
+using the minimum bit width necessary to represent `n - 1`. The inverse
+mapping is computed (and then cached) the first time
+[`invert`](Permutation::invert) is called:
+
+```rust
+use sux::utils::Permutation;
+
+# fn main() -> anyhow::Result<()> {
+let perm = Permutation::new([2, 0, 3, 1])?;
+assert_eq!(perm.apply(0), 2);
+assert_eq!(perm.invert(2), 0);
+
+let square = perm.compose(&perm);
+assert_eq!(square.apply(0), perm.apply(perm.apply(0)));
+# Ok(())
+# }
+```
+
+*/
+
+use crate::prelude::*;
+use anyhow::{ensure, Result};
+use core::cell::OnceCell;
+use mem_dbg::*;
+
+/// A permutation of `0..n` stored in a [`BitFieldVec`].
+///
+/// Since the inverse permutation is cached using interior mutability,
+/// this structure is not [`Sync`].
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug, Clone, MemDbg, MemSize)]
+pub struct Permutation {
+    forward: BitFieldVec,
+    /// The inverse permutation, if already computed.
+    inverse: OnceCell<BitFieldVec>,
+}
+
+/// Return the bit width necessary to store a permutation of `0..n`.
+fn bit_width(n: usize) -> usize {
+    bit_width_of(n.saturating_sub(1))
+}
+
+/// Check that the given values form a permutation of `0..n`.
+fn check(values: impl Iterator<Item = usize>, n: usize) -> Result<()> {
+    let mut seen: BitVec = BitVec::new(n);
+    for (i, value) in values.enumerate() {
+        ensure!(
+            value < n,
+            "Value {} at index {} is out of range for a permutation of length {}",
+            value,
+            i,
+            n
+        );
+        ensure!(!seen.get(value), "Value {} appears more than once", value);
+        seen.set(value, true);
+    }
+    Ok(())
+}
+
+impl Permutation {
+    /// Create a permutation from the sequence of its values, that is,
+    /// a sequence containing each number in `0..n` exactly once.
+    ///
+    /// This method will return an error if the values do not form
+    /// a permutation.
+    pub fn new(values: impl IntoIterator<Item = usize>) -> Result<Self> {
+        let values = values.into_iter().collect::<Vec<_>>();
+        check(values.iter().copied(), values.len())?;
+        Ok(Self::from_values(values.iter().copied(), values.len()))
+    }
+
+    /// Create the identity permutation of `0..n`.
+    pub fn identity(n: usize) -> Self {
+        Self::from_values(0..n, n)
+    }
+
+    /// Create a permutation from `n` values known to form a permutation.
+    fn from_values(values: impl Iterator<Item = usize>, n: usize) -> Self {
+        let mut forward = BitFieldVec::new(bit_width(n), n);
+        for (i, value) in values.enumerate() {
+            // SAFETY: i < n and the bit width is sufficient to store n - 1
+            unsafe { forward.set_unchecked(i, value) };
+        }
+        Self {
+            forward,
+            inverse: OnceCell::new(),
+        }
+    }
+
+    /// Create a permutation from a [`BitFieldVec`] containing its values,
+    /// reducing the bit width if possible.
+    ///
+    /// This method will return an error if the values do not form
+    /// a permutation.
+    pub fn from_bit_field_vec(values: BitFieldVec) -> Result<Self> {
+        let n = values.len();
+        check(values.iter(), n)?;
+        if values.bit_width() == bit_width(n) {
+            Ok(Self {
+                forward: values,
+                inverse: OnceCell::new(),
+            })
+        } else {
+            Ok(Self::from_values(values.iter(), n))
+        }
+    }
+
+    /// Return the length of the permutation.
+    pub fn len(&self) -> usize {
+        self.forward.len()
+    }
+
+    /// Return true if this is the permutation of the empty set.
+    pub fn is_empty(&self) -> bool {
+        self.forward.len() == 0
+    }
+
+    /// Return the image of `i` under the permutation.
+    ///
+    /// # Panics
+    /// Panics if `i` is out of bounds.
+    pub fn apply(&self, i: usize) -> usize {
+        self.forward.get(i)
+    }
+
+    /// Return the index mapped to `j` by the permutation,
+    /// computing the inverse permutation at the first call.
+    ///
+    /// # Panics
+    /// Panics if `j` is out of bounds.
+    pub fn invert(&self, j: usize) -> usize {
+        self.inverse
+            .get_or_init(|| {
+                let mut inverse = BitFieldVec::new(self.forward.bit_width(), self.len());
+                for (i, j) in self.forward.iter().enumerate() {
+                    // SAFETY: both vectors have the same length and bit width
+                    unsafe { inverse.set_unchecked(j, i) };
+                }
+                inverse
+            })
+            .get(j)
+    }
+
+    /// Return the composition of this permutation with another permutation,
+    /// that is, the permutation mapping `i` to `self.apply(other.apply(i))`.
+    ///
+    /// # Panics
+    /// Panics if the two permutations have different lengths.
+    pub fn compose(&self, other: &Permutation) -> Permutation {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "Cannot compose permutations of different lengths"
+        );
+        Self::from_values(other.iter().map(|j| self.apply(j)), self.len())
+    }
+
+    /// Return an iterator over the values of the permutation.
+    pub fn iter(&self) -> BitFieldVecIterator<'_, usize, Vec<usize>> {
+        self.forward.iter()
+    }
+
+    /// Return the underlying vector containing the values of the permutation.
+    pub fn into_inner(self) -> BitFieldVec {
+        self.forward
+    }
+}
+
+impl AsRef<BitFieldVec> for Permutation {
+    fn as_ref(&self) -> &BitFieldVec {
+        &self.forward
+    }
+}
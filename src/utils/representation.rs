@@ -0,0 +1,87 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Choice of a representation for a set of integers.
+
+A set of `n` integers smaller than or equal to an upper bound `u` can
+be represented in several ways, whose space usage depends on the density `n/u`.
+[`recommend_representation`] returns the [`Representation`] with the
+smallest size, so that users do not need to know the crossover points:
+
+```rust
+use sux::{recommend_representation, Representation};
+
+// Dense sets should be stored as bit vectors
+assert_eq!(recommend_representation(1000, 900), Representation::DenseBitmap);
+// Sparse sets should be stored using the Elias–Fano representation
+assert_eq!(recommend_representation(1 << 40, 1000), Representation::EliasFano);
+```
+
+*/
+
+use crate::prelude::bit_width_of;
+use mem_dbg::*;
+
+/// The possible representations of a set of `n` integers smaller
+/// than or equal to an upper bound `u`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, MemDbg, MemSize)]
+pub enum Representation {
+    /// An [`EliasFano`](crate::dict::EliasFano) structure.
+    EliasFano,
+    /// A [`BitVec`](crate::bits::BitVec) of length `u + 1` in which the
+    /// elements of the set are the positions of the ones.
+    DenseBitmap,
+    /// A [`BitFieldVec`](crate::bits::BitFieldVec) containing the elements of
+    /// the set using the minimum bit width necessary to represent `u`.
+    PlainPacked,
+}
+
+impl Representation {
+    /// Return the number of bits used by this representation to store
+    /// `n` integers smaller than or equal to `u`, excluding constant
+    /// overheads and selection structures, or `None` if the number of bits
+    /// does not fit in a `usize` (e.g., a [`DenseBitmap`](Representation::DenseBitmap)
+    /// with `u` equal to `usize::MAX`).
+    pub fn size_in_bits(&self, u: usize, n: usize) -> Option<usize> {
+        match self {
+            Representation::EliasFano => {
+                // The same number of lower bits used by EliasFanoBuilder
+                let l = crate::dict::elias_fano::num_lower_bits(n, u);
+                n.checked_mul(l)?
+                    .checked_add(n)?
+                    .checked_add(u >> l)?
+                    .checked_add(1)
+            }
+            Representation::DenseBitmap => u.checked_add(1),
+            Representation::PlainPacked => n.checked_mul(bit_width_of(u)),
+        }
+    }
+}
+
+/// Return the representation with the smallest size for a set of `n`
+/// integers smaller than or equal to `u`.
+///
+/// Sizes are computed using [`Representation::size_in_bits`], and
+/// representations whose size does not fit in a `usize` are excluded (if no
+/// size fits, [`PlainPacked`](Representation::PlainPacked) is returned).
+/// Ties are broken in favor of the simplest representation, that is,
+/// [`PlainPacked`](Representation::PlainPacked), then
+/// [`DenseBitmap`](Representation::DenseBitmap), and finally
+/// [`EliasFano`](Representation::EliasFano).
+pub fn recommend_representation(u: usize, n: usize) -> Representation {
+    [
+        Representation::PlainPacked,
+        Representation::DenseBitmap,
+        Representation::EliasFano,
+    ]
+    .into_iter()
+    .filter_map(|r| r.size_in_bits(u, n).map(|size| (size, r)))
+    .min_by_key(|&(size, _)| size)
+    .map_or(Representation::PlainPacked, |(_, r)| r)
+}
@@ -13,6 +13,32 @@ use rand::seq::SliceRandom;
 use rand::Rng;
 use rand::SeedableRng;
 use sux::prelude::*;
+use sux::traits::bit_field_slice::BitFieldSliceApply;
+
+/// Return a mask of `bit_width` ones.
+fn mask<W: Word>(bit_width: usize) -> W {
+    if bit_width == 0 {
+        W::ZERO
+    } else {
+        W::MAX >> (W::BITS - bit_width)
+    }
+}
+
+/// Return a vector of given length and bit width filled with random
+/// values generated using the given seed.
+fn random_bfv<W: Word + CastableFrom<u64>>(
+    seed: u64,
+    len: usize,
+    bit_width: usize,
+) -> BitFieldVec<W> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mask = mask::<W>(bit_width);
+    let mut v = BitFieldVec::<W>::new(bit_width, len);
+    for i in 0..len {
+        v.set(i, W::cast_from(rng.gen::<u64>()) & mask);
+    }
+    v
+}
 
 #[test]
 fn test_bit_field_vec() {
@@ -227,3 +253,1669 @@ fn test_pop() {
     assert_eq!(c.pop(), None);
     assert_eq!(c.pop(), None);
 }
+
+#[test]
+fn test_extend() {
+    use sux::traits::bit_field_slice::BitFieldSlice;
+    use sux::traits::bit_field_slice::BitFieldSliceMut;
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..usize::BITS as usize {
+        let n = 1000;
+        let u = 1_usize << bit_width;
+        let values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+
+        let mut set = BitFieldVec::<usize>::new(bit_width, n);
+        for (i, &v) in values.iter().enumerate() {
+            set.set(i, v);
+        }
+
+        let mut extended = BitFieldVec::<usize>::new(bit_width, 0);
+        extended.extend(values.iter().copied());
+
+        assert_eq!(extended.len(), set.len());
+        for i in 0..n {
+            assert_eq!(extended.get(i), set.get(i));
+        }
+    }
+}
+
+#[test]
+fn test_pow2_widths() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [1, 2, 4, 8, 16, 32, 64] {
+        let n = 1000;
+        let values = (0..n)
+            .map(|_| rng.gen::<usize>() & (usize::MAX >> (64 - bit_width)))
+            .collect::<Vec<_>>();
+        // A power-of-two width (fast path) and a non-power-of-two width
+        // (general path) must store and retrieve the same values.
+        let mut pow2 = BitFieldVec::<usize>::new(bit_width, n);
+        let mut general = BitFieldVec::<usize>::new(bit_width + (bit_width < 64) as usize * 3, n);
+        for (i, &v) in values.iter().enumerate() {
+            pow2.set(i, v);
+            general.set(i, v);
+        }
+        for (i, &v) in values.iter().enumerate() {
+            assert_eq!(pow2.get(i), v);
+            assert_eq!(general.get(i), v);
+        }
+
+        let f = |x: usize| x / 2;
+        pow2.apply_inplace(f);
+        general.apply_inplace(f);
+        for (i, &v) in values.iter().enumerate() {
+            assert_eq!(pow2.get(i), f(v));
+            assert_eq!(general.get(i), f(v));
+        }
+    }
+}
+
+#[test]
+fn test_append_to_file() -> anyhow::Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let tmp_file = std::env::temp_dir().join("test_append_to_file.bin");
+    for bit_width in [0, 1, 3, 7, 8, 13, 32, 63, 64] {
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            usize::MAX >> (64 - bit_width)
+        };
+        let mut expected = BitFieldVec::<usize>::new(bit_width, 0);
+        for _ in 0..5 {
+            expected.push(rng.gen::<usize>() & mask);
+        }
+        expected.store_appendable(&tmp_file)?;
+
+        for chunk_len in [0, 1, 2, 10, 100] {
+            let values = (0..chunk_len)
+                .map(|_| rng.gen::<usize>() & mask)
+                .collect::<Vec<_>>();
+            BitFieldVec::append_to_file(&tmp_file, values.iter().copied())?;
+            for &value in &values {
+                expected.push(value);
+            }
+
+            let loaded = BitFieldVec::load_appendable(&tmp_file)?;
+            assert_eq!(loaded.bit_width(), bit_width);
+            assert_eq!(loaded.len(), expected.len());
+            for i in 0..expected.len() {
+                assert_eq!(loaded.get(i), expected.get(i));
+            }
+        }
+
+        if bit_width < 64 {
+            assert!(BitFieldVec::append_to_file(&tmp_file, [mask + 1]).is_err());
+            assert_eq!(
+                BitFieldVec::load_appendable(&tmp_file)?.len(),
+                expected.len()
+            );
+        }
+    }
+    std::fs::remove_file(tmp_file)?;
+    Ok(())
+}
+
+#[test]
+fn test_appendable_header() -> anyhow::Result<()> {
+    use sux::bits::bit_field_vec::{BitFieldVecReader, APPENDABLE_MAGIC, APPENDABLE_VERSION};
+    let tmp_file = std::env::temp_dir().join("test_appendable_header.bin");
+    let mut v = BitFieldVec::<usize>::new(10, 100);
+    v.set(99, 1);
+    v.store_appendable(&tmp_file)?;
+    let bytes = std::fs::read(&tmp_file)?;
+    assert_eq!(bytes[0..8], APPENDABLE_MAGIC.to_le_bytes());
+    assert_eq!(bytes[8..16], APPENDABLE_VERSION.to_le_bytes());
+
+    let check_corrupted = |offset: usize, value: u64| -> anyhow::Result<()> {
+        let mut corrupted = bytes.clone();
+        corrupted[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        std::fs::write(&tmp_file, &corrupted)?;
+        assert!(BitFieldVec::load_appendable(&tmp_file).is_err());
+        assert!(BitFieldVec::append_to_file(&tmp_file, [1]).is_err());
+        assert!(BitFieldVecReader::new(corrupted.as_slice()).is_err());
+        // The file has not been modified
+        assert_eq!(std::fs::read(&tmp_file)?, corrupted);
+        Ok(())
+    };
+    // Wrong magic cookie
+    check_corrupted(0, u64::from_le_bytes(*b"suxbfvX\0"))?;
+    // Unsupported version
+    check_corrupted(8, APPENDABLE_VERSION + 1)?;
+    // Invalid bit width
+    check_corrupted(16, 65)?;
+    // The length times the bit width overflows
+    check_corrupted(24, u64::MAX / 2)?;
+
+    // A length exceeding the file size is detected before allocating
+    let mut corrupted = bytes.clone();
+    corrupted[24..32].copy_from_slice(&(u64::MAX / 64).to_le_bytes());
+    std::fs::write(&tmp_file, &corrupted)?;
+    assert!(BitFieldVec::load_appendable(&tmp_file).is_err());
+
+    // Appending values that would overflow the number of bits fails
+    let mut corrupted = bytes.clone();
+    corrupted[16..24].copy_from_slice(&64_u64.to_le_bytes());
+    corrupted[24..32].copy_from_slice(&(usize::MAX / 64).to_le_bytes());
+    std::fs::write(&tmp_file, &corrupted)?;
+    assert!(BitFieldVec::append_to_file(&tmp_file, [1]).is_err());
+
+    std::fs::remove_file(tmp_file)?;
+    Ok(())
+}
+
+#[test]
+fn test_quantile_index() {
+    let counts = [3, 0, 0, 5, 1, 0, 7, 2];
+    let mut cumulative = vec![];
+    let mut sum = 0;
+    for c in counts {
+        sum += c;
+        cumulative.push(sum);
+    }
+    let mut hist = BitFieldVec::<usize>::new(5, 0);
+    hist.extend(cumulative.iter().copied());
+
+    for value in 0..=sum + 1 {
+        let expected = cumulative
+            .iter()
+            .position(|&c| c > value)
+            .unwrap_or(cumulative.len());
+        assert_eq!(hist.quantile_index(value), expected);
+    }
+
+    assert_eq!(BitFieldVec::<usize>::new(5, 0).quantile_index(0), 0);
+}
+
+#[test]
+fn test_reinit() {
+    let v = BitFieldVec::<usize>::new_ones(7, 100);
+    let (data, _, _) = v.clone().into_raw_parts();
+    let ptr = data.as_ptr();
+    let mut v = unsafe { BitFieldVec::<usize>::from_raw_parts(data, 7, 100) };
+
+    // 200 values of 3 bits fit in the 11 words of 100 values of 7 bits.
+    v.reinit(3, 200);
+    assert_eq!(v.bit_width(), 3);
+    assert_eq!(v.len(), 200);
+    assert_eq!(v.mask(), 0b111);
+    for i in 0..200 {
+        assert_eq!(v.get(i), 0);
+    }
+    for i in 0..200 {
+        v.set(i, i % 8);
+    }
+    for i in 0..200 {
+        assert_eq!(v.get(i), i % 8);
+    }
+    let (data, _, _) = v.into_raw_parts();
+    assert_eq!(data.as_ptr(), ptr);
+
+    // A larger vector needs a reallocation.
+    let mut v = unsafe { BitFieldVec::<usize>::from_raw_parts(data, 3, 200) };
+    v.reinit(64, 1000);
+    assert_eq!(v.len(), 1000);
+    for i in 0..1000 {
+        assert_eq!(v.get(i), 0);
+    }
+    v.set(999, usize::MAX);
+    assert_eq!(v.get(999), usize::MAX);
+}
+
+#[test]
+fn test_set_unfenced() {
+    use sux::traits::bit_field_slice::AtomicBitFieldSlice;
+
+    for bit_width in 0..=usize::BITS as usize {
+        let n: usize = 1000;
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            usize::MAX >> (usize::BITS as usize - bit_width)
+        };
+        let mut rng = SmallRng::seed_from_u64(0);
+        let values = (0..n)
+            .map(|_| rng.gen::<usize>() & mask)
+            .collect::<Vec<_>>();
+
+        // Single writer
+        let cp = AtomicBitFieldVec::<usize>::new_ones(bit_width, n);
+        let mut indices = (0..n).collect::<Vec<_>>();
+        indices.shuffle(&mut rng);
+        for &i in &indices {
+            unsafe { cp.set_unfenced(i, values[i]) };
+        }
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(cp.get_atomic(i, Ordering::Relaxed), value);
+        }
+
+        // One writer per disjoint range; ranges share boundary words
+        let cp = AtomicBitFieldVec::<usize>::new(bit_width, n);
+        std::thread::scope(|s| {
+            for chunk in (0..n).collect::<Vec<_>>().chunks(7) {
+                let (cp, values) = (&cp, &values);
+                let chunk = chunk.to_vec();
+                s.spawn(move || {
+                    for i in chunk {
+                        unsafe { cp.set_unfenced(i, values[i]) };
+                    }
+                });
+            }
+        });
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(cp.get_atomic(i, Ordering::Relaxed), value);
+        }
+    }
+}
+
+#[test]
+fn test_windows2() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 2, 3, 10, 100, 1000] {
+        for bit_width in [0, 1, 7, 32, 63, 64] {
+            let mut v = BitFieldVec::<usize>::new(bit_width, len);
+            let mask = v.mask();
+            for i in 0..len {
+                v.set(i, rng.gen::<usize>() & mask);
+            }
+            let mut windows = v.windows2();
+            assert_eq!(windows.len(), len.saturating_sub(1));
+            let expected = (0..len.saturating_sub(1))
+                .map(|i| (v.get(i), v.get(i + 1)))
+                .collect::<Vec<_>>();
+            assert_eq!(windows.by_ref().collect::<Vec<_>>(), expected);
+            assert_eq!(windows.next(), None);
+        }
+    }
+}
+
+#[test]
+fn test_unchecked_iter_full_width() {
+    test_unchecked_iter_full_width_param::<u8>();
+    test_unchecked_iter_full_width_param::<u16>();
+    test_unchecked_iter_full_width_param::<u32>();
+    test_unchecked_iter_full_width_param::<u64>();
+    test_unchecked_iter_full_width_param::<usize>();
+}
+
+fn test_unchecked_iter_full_width_param<W: Word + CastableInto<u64> + CastableFrom<u64>>() {
+    // Iterating to the end of a vector whose bit width is W::BITS
+    // used to shift the window by W::BITS
+    let n = 100;
+    let mut rng = SmallRng::seed_from_u64(0);
+    let values = (0..n).map(|_| rng.gen::<u64>().cast()).collect::<Vec<W>>();
+    let mut v = BitFieldVec::<W>::new(W::BITS, n);
+    for (i, &value) in values.iter().enumerate() {
+        v.set(i, value);
+    }
+    for from in 0..n {
+        let mut iter = v.into_unchecked_iter_from(from);
+        for &value in &values[from..] {
+            assert_eq!(unsafe { iter.next_unchecked() }, value);
+        }
+    }
+}
+
+#[test]
+fn test_get_all_checked() {
+    let mut v = BitFieldVec::<usize>::new(10, 100);
+    for i in 0..100 {
+        v.set(i, i * 7);
+    }
+    assert_eq!(v.get_all_checked(&[]), Ok(vec![]));
+    assert_eq!(v.get_all_checked(&[3, 99, 0]), Ok(vec![21, 693, 0]));
+    assert_eq!(v.get_all_checked(&[3, 100, 0, 200]), Err(100));
+    assert_eq!(
+        BitFieldVec::<usize>::new(10, 0).get_all_checked(&[0]),
+        Err(0)
+    );
+}
+
+#[cfg(feature = "panic_free")]
+#[test]
+#[should_panic]
+fn test_panic_free_get() {
+    let v = BitFieldVec::<usize>::new(10, 100);
+    v.get(100);
+}
+
+#[cfg(feature = "panic_free")]
+#[test]
+#[should_panic]
+fn test_panic_free_set() {
+    let mut v = BitFieldVec::<usize>::new(10, 100);
+    v.set(0, 1 << 10);
+}
+
+// Checks must not be compiled out in release builds, with or without
+// the panic_free feature, as safe methods would access memory out of bounds.
+#[cfg(not(debug_assertions))]
+#[test]
+fn test_release_bounds_checks() {
+    use std::panic::catch_unwind;
+    let v = BitFieldVec::<usize>::new(16, 100);
+    assert!(catch_unwind(|| v.get(100)).is_err());
+    assert!(catch_unwind(|| v.get(usize::MAX / 16)).is_err());
+    assert!(catch_unwind(|| v.get_unaligned(100)).is_err());
+    assert!(catch_unwind(|| {
+        let mut v = v.clone();
+        v.set(100, 0)
+    })
+    .is_err());
+    assert!(catch_unwind(|| {
+        let mut v = v.clone();
+        v.set_unaligned(100, 0)
+    })
+    .is_err());
+    let b: BitVec = BitVec::new(100);
+    assert!(catch_unwind(|| b.get(100)).is_err());
+}
+
+#[test]
+fn test_insert() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 5, 31, 63] {
+        let mut v = BitFieldVec::<usize>::new(bit_width, 0);
+        let mut expected = vec![];
+        let mask = v.mask();
+        for _ in 0..500 {
+            let index = rng.gen_range(0..=expected.len());
+            let value = rng.gen::<usize>() & mask;
+            v.insert(index, value);
+            expected.insert(index, value);
+        }
+        assert_eq!(v.len(), expected.len());
+        for (i, &value) in expected.iter().enumerate() {
+            assert_eq!(v.get(i), value);
+        }
+    }
+}
+
+#[test]
+fn test_bit_field_arena() -> anyhow::Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let shapes = [
+        (0, 10),
+        (1, 100),
+        (7, 33),
+        (13, 0),
+        (20, 5),
+        (64, 9),
+        (3, 1000),
+    ];
+    let mut buffer = vec![u64::MAX; BitFieldArena::<u64>::words_for_all(shapes)];
+    let mut arena = BitFieldArena::new(&mut buffer);
+    let mut vecs = vec![];
+    let mut expected = vec![];
+    for (bit_width, len) in shapes {
+        let v = arena.alloc(bit_width, len)?;
+        assert_eq!(v.bit_width(), bit_width);
+        assert_eq!(v.len(), len);
+        assert!((0..len).all(|i| v.get(i) == 0));
+        vecs.push(v);
+        expected.push(vec![0; len]);
+    }
+    assert_eq!(arena.remaining_words(), 0);
+    assert!(arena.alloc(1, 1).is_err());
+
+    // Interleave writes to all vectors, and check that none is overwritten
+    for _ in 0..10_000 {
+        let v = rng.gen_range(0..vecs.len());
+        if vecs[v].len() == 0 {
+            continue;
+        }
+        let index = rng.gen_range(0..vecs[v].len());
+        let value = rng.gen::<u64>()
+            & u64::MAX
+                .checked_shr(64 - vecs[v].bit_width() as u32)
+                .unwrap_or(0);
+        vecs[v].set(index, value);
+        expected[v][index] = value;
+    }
+    for (v, e) in vecs.iter().zip(expected.iter()) {
+        assert!((0..v.len()).all(|i| v.get(i) == e[i]));
+    }
+
+    let mut buffer = [0_u32; 4];
+    let mut arena = BitFieldArena::new(&mut buffer);
+    assert!(arena.alloc(33, 1).is_err());
+    assert!(arena.alloc(32, 5).is_err());
+    assert!(arena.alloc(32, 4).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_bit_field_vec_reader() -> anyhow::Result<()> {
+    use sux::bits::bit_field_vec::BitFieldVecReader;
+    let mut rng = SmallRng::seed_from_u64(0);
+    let tmp_file = std::env::temp_dir().join("test_bit_field_vec_reader.bin");
+    for bit_width in [0, 1, 3, 7, 8, 13, 32, 63, 64] {
+        for len in [0, 1, 10, 1000] {
+            let mut v = BitFieldVec::<usize>::new(bit_width, len);
+            for i in 0..len {
+                v.set(i, rng.gen::<usize>() & v.mask());
+            }
+            v.store_appendable(&tmp_file)?;
+
+            let loaded = BitFieldVec::load_appendable(&tmp_file)?;
+            let reader =
+                BitFieldVecReader::new(std::io::BufReader::new(std::fs::File::open(&tmp_file)?))?;
+            assert_eq!(reader.bit_width(), bit_width);
+            assert_eq!(reader.len(), len);
+            let streamed = reader.collect::<anyhow::Result<Vec<_>>>()?;
+            assert_eq!(
+                streamed,
+                (0..len).map(|i| loaded.get(i)).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    // A truncated stream returns an error and then stops
+    let mut v = BitFieldVec::<usize>::new(10, 100);
+    v.set(99, 1);
+    v.store_appendable(&tmp_file)?;
+    let mut bytes = std::fs::read(&tmp_file)?;
+    bytes.truncate(bytes.len() - 8);
+    let mut reader = BitFieldVecReader::new(bytes.as_slice())?;
+    assert!(reader.by_ref().take(90).all(|value| value.is_ok()));
+    assert!(reader.by_ref().any(|value| value.is_err()));
+    assert!(reader.next().is_none());
+
+    std::fs::remove_file(tmp_file)?;
+    Ok(())
+}
+
+#[test]
+fn test_from_iter() {
+    test_from_iter_param::<u8>();
+    test_from_iter_param::<u16>();
+    test_from_iter_param::<u32>();
+    test_from_iter_param::<u64>();
+    test_from_iter_param::<usize>();
+
+    let v: BitFieldVec = std::iter::empty().collect();
+    assert_eq!(v.len(), 0);
+    assert_eq!(v.bit_width(), 1);
+    let v: BitFieldVec = [0].into_iter().collect();
+    assert_eq!(v.len(), 1);
+    assert_eq!(v.bit_width(), 1);
+    assert_eq!(v.get(0), 0);
+    let v: BitFieldVec<u8> = [0, 0, 0].into_iter().collect();
+    assert_eq!(v.bit_width(), 1);
+}
+
+fn test_from_iter_param<W: Word + CastableFrom<u64>>() {
+    for bit_width in 1..=W::BITS {
+        let mut values = random_bfv::<W>(bit_width as u64, 100, bit_width)
+            .iter()
+            .collect::<Vec<_>>();
+        // Make sure the largest value needs exactly bit_width bits
+        values[17] = W::ONE << (bit_width - 1);
+        let v: BitFieldVec<W> = values.iter().copied().collect();
+        assert_eq!(v.bit_width(), bit_width);
+        assert_eq!(v.len(), values.len());
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(v.get(i), value);
+        }
+    }
+}
+
+#[test]
+fn test_bit_width_of() {
+    assert_eq!(bit_width_of(0), 0);
+    assert_eq!(bit_width_of(1), 1);
+    assert_eq!(bit_width_of(2), 2);
+    assert_eq!(bit_width_of(3), 2);
+    assert_eq!(bit_width_of(255), 8);
+    assert_eq!(bit_width_of(256), 9);
+    assert_eq!(bit_width_of(usize::MAX), usize::BITS as usize);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_iter() {
+    use rayon::prelude::*;
+    test_par_iter_param::<u8>();
+    test_par_iter_param::<u16>();
+    test_par_iter_param::<u32>();
+    test_par_iter_param::<u64>();
+    test_par_iter_param::<usize>();
+
+    let v = BitFieldVec::<u8>::new(0, 1000);
+    assert_eq!(v.par_iter().count(), 1000);
+    assert!(v.par_iter().all(|x| x == 0));
+}
+
+#[cfg(feature = "rayon")]
+fn test_par_iter_param<W: Word + CastableFrom<u64>>() {
+    use rayon::prelude::*;
+    for len in [0, 1, 63, 1000, 100_000] {
+        for bit_width in 1..=W::BITS {
+            let v = random_bfv::<W>(bit_width as u64, len, bit_width);
+            let par = v.par_iter().collect::<Vec<_>>();
+            let seq = v.iter().collect::<Vec<_>>();
+            assert_eq!(par, seq, "len = {}, bit_width = {}", len, bit_width);
+
+            let threshold = mask::<W>(bit_width) >> 1;
+            assert_eq!(
+                v.par_iter().filter(|&x| x > threshold).count(),
+                v.iter().filter(|&x| x > threshold).count()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_as_non_atomic() {
+    use sux::traits::bit_field_slice::AtomicBitFieldSlice;
+
+    let n: usize = 1000;
+    let mut rng = SmallRng::seed_from_u64(0);
+    // Atomic writes are not supported for bit width usize::BITS
+    for bit_width in 0..usize::BITS as usize {
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            usize::MAX >> (usize::BITS as usize - bit_width)
+        };
+        let values = (0..n)
+            .map(|_| rng.gen::<usize>() & mask)
+            .collect::<Vec<_>>();
+
+        let cp = AtomicBitFieldVec::<usize>::new(bit_width, n);
+        std::thread::scope(|s| {
+            for chunk in (0..n).collect::<Vec<_>>().chunks(100) {
+                let (cp, values) = (&cp, &values);
+                let chunk = chunk.to_vec();
+                s.spawn(move || {
+                    for i in chunk {
+                        cp.set_atomic(i, values[i], Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+
+        // SAFETY: all writers have been joined
+        let view = unsafe { cp.as_non_atomic() };
+        assert_eq!(view.len(), n);
+        assert_eq!(view.bit_width(), bit_width);
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(view.get(i), cp.get_atomic(i, Ordering::Relaxed));
+            assert_eq!(view.get(i), value);
+        }
+    }
+}
+
+#[test]
+fn test_extend_from_bit_field_slice() {
+    test_extend_from_bit_field_slice_param::<u8>();
+    test_extend_from_bit_field_slice_param::<u16>();
+    test_extend_from_bit_field_slice_param::<u32>();
+    test_extend_from_bit_field_slice_param::<u64>();
+    test_extend_from_bit_field_slice_param::<usize>();
+
+    // Garbage past the end of the source must not leak
+    let mut other = BitFieldVec::<u64>::new_ones(5, 20);
+    other.pop();
+    let mut v = BitFieldVec::<u64>::new(5, 0);
+    v.extend_from_bit_field_slice(&other);
+    assert_eq!(v.len(), 19);
+    let (data, _, _) = v.into_raw_parts();
+    assert_eq!(data[1] >> (19 * 5 - 64), 0);
+
+    // Plain slices of words have full bit width
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut v = BitFieldVec::<u64>::new(64, 0);
+    let words = (0..100).map(|_| rng.gen::<u64>()).collect::<Vec<_>>();
+    v.extend_from_bit_field_slice(&words);
+    v.extend_from_bit_field_slice(&words.as_slice());
+    assert_eq!(
+        v.iter().collect::<Vec<_>>(),
+        [&words[..], &words[..]].concat()
+    );
+}
+
+fn test_extend_from_bit_field_slice_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=W::BITS {
+        let mut v = BitFieldVec::<W>::new(bit_width, 0);
+        let mut expected = vec![];
+        // The lengths make the end of the vector both aligned and not aligned
+        for len in [0, 64, 1, 100, 63, 64, 7] {
+            let other = random_bfv::<W>(rng.gen(), len, bit_width);
+            v.extend_from_bit_field_slice(&other);
+            expected.extend(other.iter());
+            assert_eq!(v.len(), expected.len());
+            assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+        }
+
+        // Narrower slices are appended value by value
+        let other = BitFieldVec::<W>::new_ones(bit_width / 2, 50);
+        v.extend_from_bit_field_slice(&other);
+        expected.extend(other.iter());
+        assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+    }
+}
+
+#[test]
+#[should_panic(expected = "larger bit width")]
+fn test_extend_from_bit_field_slice_wider() {
+    let mut v = BitFieldVec::<u64>::new(5, 0);
+    v.extend_from_bit_field_slice(&BitFieldVec::<u64>::new(6, 1));
+}
+
+#[test]
+fn test_elements_until_boundary() {
+    for bit_width in 1..=64 {
+        let len = 500;
+        let v = BitFieldVec::<u64>::new(bit_width, len);
+        for start in 0..=len {
+            // Count the elements whose last bit lies in the word of the first one
+            let word = start * bit_width / 64;
+            let expected = (start..len)
+                .take_while(|&i| ((i + 1) * bit_width - 1) / 64 == word)
+                .count();
+            assert_eq!(
+                v.elements_until_boundary(start),
+                expected,
+                "bit_width = {}, start = {}",
+                bit_width,
+                start
+            );
+        }
+    }
+
+    let v = BitFieldVec::<u8>::new(0, 10);
+    assert_eq!(v.elements_until_boundary(3), 7);
+    let v = BitFieldVec::<u8>::new(3, 10);
+    assert_eq!(v.elements_until_boundary(0), 2);
+    // The element of index 2 straddles a word boundary
+    assert_eq!(v.elements_until_boundary(2), 0);
+    assert_eq!(v.elements_until_boundary(3), 2);
+}
+
+#[test]
+fn test_fill_range() {
+    test_fill_range_param::<u8>();
+    test_fill_range_param::<u16>();
+    test_fill_range_param::<u32>();
+    test_fill_range_param::<u64>();
+    test_fill_range_param::<usize>();
+
+    // The whole vector, and an empty range at the end
+    let mut v = BitFieldVec::<u16>::fill(4, 100, 9);
+    v.fill_range(0..100, 0);
+    assert!(v.iter().all(|x| x == 0));
+    v.fill_range(100..100, 5);
+    assert!(v.iter().all(|x| x == 0));
+}
+
+fn test_fill_range_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=W::BITS {
+        let len = 300;
+        for _ in 0..20 {
+            let mut v = random_bfv::<W>(rng.gen(), len, bit_width);
+            let mut expected = v.iter().collect::<Vec<_>>();
+            let start = rng.gen_range(0..=len);
+            let end = rng.gen_range(start..=len);
+            let value = W::cast_from(rng.gen::<u64>()) & mask::<W>(bit_width);
+            v.fill_range(start..end, value);
+            expected[start..end].fill(value);
+            assert_eq!(
+                v.iter().collect::<Vec<_>>(),
+                expected,
+                "bit_width = {}, range = {}..{}",
+                bit_width,
+                start,
+                end
+            );
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Range out of bounds")]
+fn test_fill_range_out_of_bounds() {
+    let mut v = BitFieldVec::<u64>::new(4, 10);
+    v.fill_range(5..11, 1);
+}
+
+#[test]
+fn test_get_range() {
+    test_get_range_param::<u8>();
+    test_get_range_param::<u16>();
+    test_get_range_param::<u32>();
+    test_get_range_param::<u64>();
+    test_get_range_param::<usize>();
+}
+
+fn test_get_range_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=W::BITS {
+        let len = 300;
+        let v = random_bfv::<W>(bit_width as u64, len, bit_width);
+        let mut out = vec![W::MAX; len + 1];
+        for _ in 0..20 {
+            let start = rng.gen_range(0..=len);
+            let end = rng.gen_range(start..=len);
+            out.fill(W::MAX);
+            v.get_range(start..end, &mut out);
+            assert_eq!(
+                out[..end - start],
+                v.iter_range(start, end).collect::<Vec<_>>()
+            );
+            // Elements past the range are not touched
+            assert_eq!(out[end - start], W::MAX);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_get_range_short_buffer() {
+    let v = BitFieldVec::<u64>::new(4, 10);
+    v.get_range(0..5, &mut [0; 4]);
+}
+
+#[test]
+#[should_panic(expected = "Range out of bounds")]
+fn test_get_range_out_of_bounds() {
+    let v = BitFieldVec::<u64>::new(4, 10);
+    v.get_range(8..11, &mut [0; 4]);
+}
+
+#[test]
+fn test_swap() {
+    test_swap_param::<u8>();
+    test_swap_param::<u16>();
+    test_swap_param::<u32>();
+    test_swap_param::<u64>();
+    test_swap_param::<usize>();
+}
+
+fn test_swap_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=W::BITS {
+        let len = 200;
+        let mut v = random_bfv::<W>(bit_width as u64, len, bit_width);
+        let mut expected = v.iter().collect::<Vec<_>>();
+        for _ in 0..1000 {
+            let i = rng.gen_range(0..len);
+            let j = if rng.gen_bool(0.1) {
+                i
+            } else {
+                rng.gen_range(0..len)
+            };
+            v.swap(i, j);
+            expected.swap(i, j);
+        }
+        assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+    }
+
+    // In-place sorting on the packed representation
+    let mut v = random_bfv::<W>(0, 100, 7);
+    for i in 1..100 {
+        let mut j = i;
+        while j > 0 && v.get(j - 1) > v.get(j) {
+            v.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+    assert!(v.windows2().all(|(a, b)| a <= b));
+}
+
+#[test]
+#[should_panic(expected = "Index out of bounds")]
+fn test_swap_out_of_bounds() {
+    let mut v = BitFieldVec::<u64>::new(4, 10);
+    v.swap(3, 10);
+}
+
+#[test]
+fn test_rotate() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 3, 8, 13, 32] {
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            u32::MAX >> (32 - bit_width)
+        };
+        for len in [0, 1, 2, 7, 100] {
+            let mut v = BitFieldVec::<u32>::new(bit_width, len);
+            for i in 0..len {
+                v.set(i, rng.gen::<u32>() & mask);
+            }
+            let mut expected = v.iter().collect::<Vec<_>>();
+
+            v.reverse();
+            expected.reverse();
+            assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+
+            for mid in 0..=len {
+                v.rotate_left(mid);
+                expected.rotate_left(mid);
+                assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+                let k = rng.gen_range(0..=len);
+                v.rotate_right(k);
+                expected.rotate_right(k);
+                assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Rotation out of bounds")]
+fn test_rotate_out_of_bounds() {
+    let mut v = BitFieldVec::<u32>::new(4, 10);
+    v.rotate_left(11);
+}
+
+#[test]
+fn test_binary_search() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 2, 10, 1000] {
+        let mut values = (0..len)
+            .map(|_| rng.gen_range(0..2 * len as u64 + 1))
+            .collect::<Vec<_>>();
+        values.sort();
+        let v: BitFieldVec<u64> = values.iter().copied().collect();
+        for value in 0..2 * len as u64 + 2 {
+            match v.binary_search(value) {
+                Ok(index) => assert_eq!(values[index], value),
+                Err(index) => {
+                    assert_eq!(values.binary_search(&value), Err(index));
+                }
+            }
+        }
+
+        // Search on a derived key, in decreasing order
+        for value in 0..2 * len as u64 + 2 {
+            let key = u64::MAX - value;
+            let result = v.binary_search_by(|x| key.cmp(&(u64::MAX - x)));
+            assert_eq!(result.is_ok(), values.contains(&value));
+            assert_eq!(
+                result.unwrap_or_else(|index| index),
+                v.binary_search(value).unwrap_or_else(|index| index)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_insert_remove() {
+    test_insert_remove_param::<u8>();
+    test_insert_remove_param::<u16>();
+    test_insert_remove_param::<u32>();
+    test_insert_remove_param::<u64>();
+    test_insert_remove_param::<usize>();
+}
+
+fn test_insert_remove_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=W::BITS {
+        let mask = mask::<W>(bit_width);
+        let mut v = BitFieldVec::<W>::new(bit_width, 0);
+        let mut expected = vec![];
+        for _ in 0..1000 {
+            if expected.is_empty() || rng.gen_bool(0.6) {
+                let index = rng.gen_range(0..=expected.len());
+                let value = W::cast_from(rng.gen::<u64>()) & mask;
+                v.insert(index, value);
+                expected.insert(index, value);
+            } else {
+                let index = rng.gen_range(0..expected.len());
+                assert_eq!(v.remove(index), expected.remove(index));
+            }
+            assert_eq!(v.len(), expected.len());
+        }
+        assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+
+        // Insertion at the end behaves like a push
+        let mut w = v.clone();
+        v.insert(v.len(), mask);
+        w.push(mask);
+        assert_eq!(v.iter().collect::<Vec<_>>(), w.iter().collect::<Vec<_>>());
+
+        while !expected.is_empty() {
+            assert_eq!(v.remove(0), expected.remove(0));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Insertion index out of bounds")]
+fn test_insert_out_of_bounds() {
+    let mut v = BitFieldVec::<u64>::new(4, 10);
+    v.insert(11, 1);
+}
+
+#[test]
+#[should_panic(expected = "Removal index out of bounds")]
+fn test_remove_out_of_bounds() {
+    let mut v = BitFieldVec::<u64>::new(4, 10);
+    v.remove(10);
+}
+
+#[test]
+fn test_retain() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 5, 8, 11, 16] {
+        let mask = if bit_width == 0 {
+            0
+        } else {
+            u16::MAX >> (16 - bit_width)
+        };
+        for keep in [0.0, 0.1, 0.5, 1.0] {
+            let values = (0..1000)
+                .map(|_| rng.gen::<u16>() & mask)
+                .collect::<Vec<_>>();
+            let mut v = BitFieldVec::<u16>::new(bit_width, values.len());
+            for (i, &value) in values.iter().enumerate() {
+                v.set(i, value);
+            }
+            let flags = (0..values.len())
+                .map(|_| rng.gen_bool(keep))
+                .collect::<Vec<_>>();
+            let mut flag = flags.iter();
+            v.retain(|_| *flag.next().unwrap());
+            let expected = values
+                .iter()
+                .zip(&flags)
+                .filter_map(|(&value, &kept)| kept.then_some(value))
+                .collect::<Vec<_>>();
+            assert_eq!(v.len(), expected.len());
+            assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+
+            // The vector is still usable
+            v.push(mask);
+            assert_eq!(v.get(expected.len()), mask);
+        }
+    }
+}
+
+#[test]
+fn test_reductions() {
+    test_reductions_param::<u8>();
+    test_reductions_param::<u16>();
+    test_reductions_param::<u32>();
+    test_reductions_param::<u64>();
+    test_reductions_param::<usize>();
+}
+
+fn test_reductions_param<W: Word + CastableFrom<u64>>() {
+    for bit_width in 0..=W::BITS {
+        for len in [0, 1, 100, 1000] {
+            let v = random_bfv::<W>(bit_width as u64, len, bit_width);
+            let values = (0..len).map(|i| v.get(i)).collect::<Vec<_>>();
+            assert_eq!(
+                v.sum(),
+                values.iter().fold(W::ZERO, |sum, &x| sum.wrapping_add(x))
+            );
+            assert_eq!(v.min(), values.iter().copied().min());
+            assert_eq!(v.max(), values.iter().copied().max());
+        }
+    }
+}
+
+#[test]
+fn test_rescale() {
+    test_rescale_param::<u8>();
+    test_rescale_param::<u16>();
+    test_rescale_param::<u32>();
+    test_rescale_param::<u64>();
+    test_rescale_param::<usize>();
+}
+
+fn test_rescale_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for old_bit_width in 0..=W::BITS {
+        for new_bit_width in 0..=W::BITS {
+            let len = 100;
+            // Values must fit in both bit widths
+            let values = random_bfv::<W>(rng.gen(), len, Ord::min(old_bit_width, new_bit_width));
+            let mut v = BitFieldVec::<W>::new(old_bit_width, len);
+            v.copy_from(&values);
+            let expected = v.iter().collect::<Vec<_>>();
+            v.rescale(new_bit_width);
+            assert_eq!(v.bit_width(), new_bit_width);
+            assert_eq!(v.len(), len);
+            assert_eq!(
+                v.iter().collect::<Vec<_>>(),
+                expected,
+                "{} -> {}",
+                old_bit_width,
+                new_bit_width
+            );
+            // The mask has been updated
+            v.push(v.mask());
+            assert_eq!(v.get(len), v.mask());
+            assert_eq!(v.mask().count_ones() as usize, new_bit_width);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "does not fit in 3 bits")]
+fn test_rescale_too_narrow() {
+    let mut v = BitFieldVec::<u64>::new(10, 5);
+    v.set(3, 8);
+    v.rescale(3);
+}
+
+#[test]
+fn test_try_push_set() {
+    let mut v = BitFieldVec::<u16>::new(5, 0);
+    assert!(v.try_push(31).is_ok());
+    let err = v.try_push(32).unwrap_err();
+    assert_eq!(err.to_string(), "Value 32 does not fit in 5 bits");
+    assert_eq!(v.len(), 1);
+    assert_eq!(v.get(0), 31);
+
+    assert!(v.try_set(0, 7).is_ok());
+    assert_eq!(v.get(0), 7);
+    assert!(v.try_set(0, 100).is_err());
+    assert!(v.try_set(1, 0).is_err());
+    assert_eq!(v.get(0), 7);
+
+    let mut v = BitFieldVec::<u16>::new(0, 0);
+    assert!(v.try_push(0).is_ok());
+    assert!(v.try_push(1).is_err());
+    assert_eq!(v.len(), 1);
+
+    let mut words = vec![0_u16; 3];
+    assert!(words.try_set(2, u16::MAX).is_ok());
+    assert!(words.try_set(3, 0).is_err());
+    assert_eq!(words[2], u16::MAX);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() -> anyhow::Result<()> {
+    test_serde_param::<u8>()?;
+    test_serde_param::<u16>()?;
+    test_serde_param::<u32>()?;
+    test_serde_param::<u64>()?;
+    test_serde_param::<usize>()?;
+
+    // Inconsistent data
+    assert!(
+        serde_json::from_str::<BitFieldVec<u64>>(r#"{"bit_width":10,"len":7,"data":[0]}"#).is_err()
+    );
+    assert!(
+        serde_json::from_str::<BitFieldVec<u64>>(r#"{"bit_width":10,"len":7,"data":[0,0,0]}"#)
+            .is_err()
+    );
+    assert!(
+        serde_json::from_str::<BitFieldVec<u8>>(r#"{"bit_width":9,"len":1,"data":[0,0]}"#).is_err()
+    );
+    assert!(
+        serde_json::from_str::<BitFieldVec<u64>>(r#"{"bit_width":10,"len":7,"data":[0,0]}"#)
+            .is_ok()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn test_serde_param<
+    W: Word + CastableFrom<u64> + serde::Serialize + serde::de::DeserializeOwned,
+>() -> anyhow::Result<()> {
+    for bit_width in 0..=W::BITS {
+        for len in [0, 1, 10, 100] {
+            let v = random_bfv::<W>(bit_width as u64, len, bit_width);
+            let json = serde_json::to_string(&v)?;
+            let w: BitFieldVec<W> = serde_json::from_str(&json)?;
+            assert_eq!(w.bit_width(), bit_width);
+            assert_eq!(w.mask(), v.mask());
+            assert_eq!(w.len(), len);
+            assert_eq!(w.iter().collect::<Vec<_>>(), v.iter().collect::<Vec<_>>());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_iter_nth() {
+    test_iter_nth_param::<u8>();
+    test_iter_nth_param::<u16>();
+    test_iter_nth_param::<u32>();
+    test_iter_nth_param::<u64>();
+    test_iter_nth_param::<usize>();
+}
+
+fn test_iter_nth_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 7, 8, 16, 33, 64]
+        .into_iter()
+        .filter(|&b| b <= W::BITS)
+    {
+        let len = 1000;
+        let v = random_bfv::<W>(bit_width as u64, len, bit_width);
+        let values = v.iter().collect::<Vec<_>>();
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..=len);
+            let end = rng.gen_range(start..=len);
+            let mut iter = v.iter_range(start, end);
+            let mut expected = values[start..end].iter().copied();
+            loop {
+                let n = rng.gen_range(0..20);
+                let (x, y) = match rng.gen_range(0..4) {
+                    0 => (iter.nth(n), expected.nth(n)),
+                    1 => (iter.nth_back(n), expected.nth_back(n)),
+                    2 => (iter.next(), expected.next()),
+                    _ => (iter.next_back(), expected.next_back()),
+                };
+                assert_eq!(x, y);
+                assert_eq!(iter.len(), expected.len());
+                if x.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_copy_from() {
+    test_copy_from_param::<u8>();
+    test_copy_from_param::<u16>();
+    test_copy_from_param::<u32>();
+    test_copy_from_param::<u64>();
+    test_copy_from_param::<usize>();
+
+    // Plain slices of words
+    let mut dst = BitFieldVec::<u64>::new(64, 10);
+    dst.copy_from(&[1_u64, 2, 3]);
+    assert_eq!(dst.iter().take(4).collect::<Vec<_>>(), [1, 2, 3, 0]);
+    let mut dst = BitFieldVec::<u64>::new(2, 10);
+    dst.copy_from(&[1_u64, 2, 3]);
+    assert_eq!(dst.iter().take(4).collect::<Vec<_>>(), [1, 2, 3, 0]);
+}
+
+fn test_copy_from_param<W: Word + CastableFrom<u64>>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for dst_bit_width in 0..=W::BITS {
+        let mut dst = BitFieldVec::<W>::new_ones(dst_bit_width, 300);
+        for src_bit_width in [0, 1, 5, 8, 13, 32, 63, 64]
+            .into_iter()
+            .filter(|&b| b <= W::BITS)
+        {
+            // Values always fit in the destination
+            let len = rng.gen_range(0..=300);
+            let values = random_bfv::<W>(rng.gen(), len, Ord::min(dst_bit_width, src_bit_width));
+            let mut src = BitFieldVec::<W>::new(src_bit_width, len);
+            for (i, value) in values.iter().enumerate() {
+                src.set(i, value);
+            }
+            let mut expected = dst.iter().collect::<Vec<_>>();
+            expected[..len].copy_from_slice(&src.iter().collect::<Vec<_>>());
+            dst.copy_from(&src);
+            assert_eq!(dst.iter().collect::<Vec<_>>(), expected);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "does not fit in 2 bits")]
+fn test_copy_from_too_large() {
+    let mut dst = BitFieldVec::<u64>::new(2, 10);
+    dst.copy_from(&[1_u64, 2, 4]);
+}
+
+#[test]
+#[should_panic(expected = "Source is longer")]
+fn test_copy_from_too_long() {
+    let mut dst = BitFieldVec::<u64>::new(2, 2);
+    dst.copy_from(&[1_u64, 2, 3]);
+}
+
+#[test]
+fn test_address_of() {
+    for bit_width in 1..=64 {
+        let v = BitFieldVec::<u64>::new(bit_width, 200);
+        let (data, _, _) = v.clone().into_raw_parts();
+        let base = v.address_of(0);
+        for i in 0..200 {
+            let word_index = i * bit_width / 64;
+            assert_eq!(
+                v.address_of(i),
+                base.wrapping_add(word_index),
+                "bit_width = {}, index = {}",
+                bit_width,
+                i
+            );
+            assert!(word_index < data.len());
+        }
+    }
+}
+
+#[test]
+fn test_set_unaligned() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [1, 2, 4, 8, 9, 10, 12, 16, 17, 32, 57, 58, 60, 64] {
+        let n = 100;
+        let mut v = BitFieldVec::<u64>::new(bit_width, n);
+        let mut expected = vec![0; n];
+        for _ in 0..10 * n {
+            let index = rng.gen_range(0..n);
+            let value = rng.gen::<u64>() & v.mask();
+            v.set_unaligned(index, value);
+            expected[index] = value;
+        }
+        for (i, &value) in expected.iter().enumerate() {
+            assert_eq!(v.get(i), value, "bit_width = {}, index = {}", bit_width, i);
+            assert_eq!(v.get_unaligned(i), value);
+        }
+    }
+    for bit_width in [1, 2, 4, 8, 9, 16] {
+        let n = 100;
+        let mut v = BitFieldVec::<u16>::new(bit_width, n);
+        for i in 0..n {
+            v.set_unaligned(i, (i as u16 * 7) & v.mask());
+        }
+        for i in 0..n {
+            assert_eq!(v.get(i), (i as u16 * 7) & v.mask());
+            assert_eq!(v.get_unaligned(i), v.get(i));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "incompatible with unaligned")]
+fn test_set_unaligned_incompatible() {
+    let mut v = BitFieldVec::<u64>::new(62, 10);
+    v.set_unaligned(0, 1);
+}
+
+#[test]
+fn test_to_vec() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=64 {
+        let n = 200;
+        let mut v = BitFieldVec::<u64>::new(bit_width, n);
+        for i in 0..n {
+            v.set(i, rng.gen::<u64>() & v.mask());
+        }
+        let expected = v.iter().collect::<Vec<_>>();
+        assert_eq!(v.to_vec(), expected);
+        for (start, end) in [(0, 0), (0, n), (17, 150), (n, n), (n - 1, n)] {
+            assert_eq!(v.to_vec_range(start..end), expected[start..end]);
+        }
+    }
+}
+
+#[test]
+fn test_reverse() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=32 {
+        for len in [0, 1, 2, 3, 31, 32, 33, 64, 65, 100] {
+            let mut v = BitFieldVec::<u32>::new(bit_width, len);
+            for i in 0..len {
+                v.set(i, rng.gen::<u32>() & v.mask());
+            }
+            let mut expected = v.iter().collect::<Vec<_>>();
+            v.reverse();
+            expected.reverse();
+            assert_eq!(
+                v.iter().collect::<Vec<_>>(),
+                expected,
+                "bit_width = {}, len = {}",
+                bit_width,
+                len
+            );
+            v.push(v.mask());
+            expected.push(v.mask());
+            assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+        }
+    }
+    for bit_width in [1, 2, 4, 8, 16, 32, 64] {
+        let mut v = BitFieldVec::<u64>::new(bit_width, 1000);
+        for i in 0..1000 {
+            v.set(i, rng.gen::<u64>() & v.mask());
+        }
+        let mut expected = v.iter().collect::<Vec<_>>();
+        v.reverse();
+        expected.reverse();
+        assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+    }
+}
+
+#[test]
+fn test_split_at() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=64 {
+        let n = 100;
+        let mut v = BitFieldVec::<u64>::new(bit_width, n);
+        for i in 0..n {
+            v.set(i, rng.gen::<u64>() & v.mask());
+        }
+        for mid in [0, 1, 7, 50, 99, 100] {
+            let (left, right) = v.split_at(mid);
+            assert_eq!(left.len(), mid);
+            assert_eq!(right.len(), n - mid);
+            assert_eq!(right.bit_offset(), mid * bit_width % 64);
+            for i in 0..mid {
+                assert_eq!(left.get(i), v.get(i));
+            }
+            for i in 0..n - mid {
+                assert_eq!(right.get(i), v.get(mid + i), "bit_width = {}", bit_width);
+            }
+            assert_eq!(
+                right.into_iter().collect::<Vec<_>>(),
+                v.iter_from(mid).collect::<Vec<_>>()
+            );
+            assert_eq!(right.as_bit_field_vec().is_some(), right.bit_offset() == 0);
+
+            let inner = right.len() / 2;
+            let (a, b) = right.split_at(inner);
+            for i in 0..inner {
+                assert_eq!(a.get(i), v.get(mid + i));
+            }
+            for i in 0..b.len() {
+                assert_eq!(b.get(i), v.get(mid + inner + i));
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Split index out of bounds")]
+fn test_split_at_out_of_bounds() {
+    let v = BitFieldVec::<u64>::new(4, 10);
+    v.split_at(11);
+}
+
+#[test]
+fn test_append() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=8 {
+        let mut v = BitFieldVec::<u8>::new(bit_width, 0);
+        let mut expected = vec![];
+        for len in [0, 8, 1, 13, 7, 8, 3] {
+            let mut other = BitFieldVec::<u8>::new(bit_width, 0);
+            for _ in 0..len + 1 {
+                other.push(rng.gen::<u8>() & other.mask());
+            }
+            // Leave stale bits past the end of both vectors
+            other.pop();
+            v.push(v.mask());
+            v.pop();
+            expected.extend(other.iter());
+            v.append(&mut other);
+            assert!(other.is_empty());
+            assert_eq!(v.iter().collect::<Vec<_>>(), expected);
+            other.push(other.mask());
+            assert_eq!(other.iter().collect::<Vec<_>>(), [other.mask()]);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Cannot append")]
+fn test_append_different_bit_width() {
+    let mut v = BitFieldVec::<u64>::new(5, 0);
+    v.append(&mut BitFieldVec::<u64>::new(4, 1));
+}
+
+#[test]
+fn test_position() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=64 {
+        for len in [0, 1, 2, 63, 64, 65, 200] {
+            let mut v = BitFieldVec::<u64>::new(bit_width, len);
+            // Few distinct values, so that most of them appear
+            let values = (0..8)
+                .map(|_| rng.gen::<u64>() & v.mask())
+                .collect::<Vec<_>>();
+            for i in 0..len {
+                v.set(i, values[rng.gen_range(0..values.len())]);
+            }
+            let expected = v.iter().collect::<Vec<_>>();
+            for value in values.iter().copied().chain([0, v.mask(), 1 & v.mask()]) {
+                assert_eq!(
+                    v.position(value),
+                    expected.iter().position(|&x| x == value),
+                    "bit_width = {}, len = {}, value = {}",
+                    bit_width,
+                    len,
+                    value
+                );
+                assert_eq!(v.contains(value), expected.contains(&value));
+            }
+            if bit_width < 64 {
+                assert_eq!(v.position(v.mask() + 1), None);
+            }
+        }
+    }
+    // Values past the end of the vector must not be found
+    let mut v = BitFieldVec::<u8>::new(4, 3);
+    v.push(7);
+    v.pop();
+    assert_eq!(v.position(7), None);
+    assert_eq!(v.position(0), Some(0));
+}
+
+#[test]
+fn test_fetch_add() {
+    use sux::traits::bit_field_slice::AtomicBitFieldSlice;
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=64 {
+        let n = 100;
+        let v = AtomicBitFieldVec::<u64>::new(bit_width, n);
+        let mask = v.mask();
+        let mut expected = vec![0_u64; n];
+        for _ in 0..1000 {
+            let i = rng.gen_range(0..n);
+            let delta = rng.gen::<u64>();
+            assert_eq!(v.fetch_add(i, delta, Ordering::AcqRel), expected[i]);
+            expected[i] = expected[i].wrapping_add(delta) & mask;
+        }
+        for (i, &value) in expected.iter().enumerate() {
+            assert_eq!(v.get_atomic(i, Ordering::Relaxed), value);
+        }
+    }
+
+    // Concurrent histogram counting
+    let counts = AtomicBitFieldVec::<u64>::new(16, 37);
+    std::thread::scope(|s| {
+        for t in 0..8 {
+            let counts = &counts;
+            s.spawn(move || {
+                for i in 0..1000 {
+                    counts.fetch_add((i * (t + 1)) % 37, 1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    let mut expected = vec![0; 37];
+    for t in 0..8 {
+        for i in 0..1000 {
+            expected[(i * (t + 1)) % 37] += 1;
+        }
+    }
+    for (i, &count) in expected.iter().enumerate() {
+        assert_eq!(counts.get_atomic(i, Ordering::Relaxed), count);
+    }
+}
+
+#[test]
+fn test_compare_exchange() {
+    use sux::traits::bit_field_slice::AtomicBitFieldSlice;
+
+    let ord = Ordering::Relaxed;
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in [0, 1, 2, 4, 8, 16, 32, 64] {
+        let n = 100;
+        let v = AtomicBitFieldVec::<u64>::new(bit_width, n);
+        let mask = v.mask();
+        let mut expected = vec![0_u64; n];
+        for _ in 0..1000 {
+            let i = rng.gen_range(0..n);
+            // Guess right about half of the time
+            let current = if rng.gen() {
+                expected[i]
+            } else {
+                rng.gen::<u64>() & mask
+            };
+            let new = rng.gen::<u64>() & mask;
+            let result = v.compare_exchange(i, current, new, ord, ord);
+            if current == expected[i] {
+                assert_eq!(result, Ok(current));
+                expected[i] = new;
+            } else {
+                assert_eq!(result, Err(expected[i]));
+            }
+        }
+        for (i, &value) in expected.iter().enumerate() {
+            assert_eq!(v.get_atomic(i, ord), value);
+        }
+    }
+
+    // Concurrent claims of slots: each slot is claimed exactly once
+    let slots = AtomicBitFieldVec::<u64>::new(4, 1000);
+    let claimed = std::thread::scope(|s| {
+        let handles = (1..=8)
+            .map(|t| {
+                let slots = &slots;
+                s.spawn(move || {
+                    (0..1000)
+                        .filter(|&i| slots.compare_exchange(i, 0, t, ord, ord).is_ok())
+                        .count()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .sum::<usize>()
+    });
+    assert_eq!(claimed, 1000);
+    assert!((0..1000).all(|i| slots.get_atomic(i, ord) != 0));
+}
+
+#[test]
+#[should_panic(expected = "crosses a word boundary")]
+fn test_compare_exchange_crossing() {
+    let v = AtomicBitFieldVec::<u64>::new(3, 30);
+    let _ = v.compare_exchange(21, 0, 1, Ordering::Relaxed, Ordering::Relaxed);
+}
+
+#[test]
+fn test_fetch_max_min() {
+    use sux::traits::bit_field_slice::AtomicBitFieldSlice;
+
+    let ord = Ordering::Relaxed;
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=32 {
+        let n = 100;
+        let v = AtomicBitFieldVec::<u32>::new(bit_width, n);
+        let mask = v.mask();
+        let mut expected = vec![0_u32; n];
+        for _ in 0..1000 {
+            let i = rng.gen_range(0..n);
+            let value = rng.gen::<u32>() & mask;
+            if rng.gen() {
+                assert_eq!(v.fetch_max(i, value, ord), expected[i]);
+                expected[i] = expected[i].max(value);
+            } else {
+                assert_eq!(v.fetch_min(i, value, ord), expected[i]);
+                expected[i] = expected[i].min(value);
+            }
+        }
+        for (i, &value) in expected.iter().enumerate() {
+            assert_eq!(v.get_atomic(i, ord), value);
+        }
+    }
+
+    // Concurrent high-watermark
+    let marks = AtomicBitFieldVec::<u32>::new(16, 10);
+    std::thread::scope(|s| {
+        for t in 0..8 {
+            let marks = &marks;
+            s.spawn(move || {
+                for i in 0..1000 {
+                    marks.fetch_max(i % 10, (i * 8 + t) as u32, ord);
+                }
+            });
+        }
+    });
+    for i in 0..10 {
+        assert_eq!(marks.get_atomic(i, ord), ((990 + i) * 8 + 7) as u32);
+    }
+}
+
+#[test]
+#[should_panic(expected = "does not fit in")]
+fn test_fetch_max_too_large() {
+    let v = AtomicBitFieldVec::<u32>::new(4, 10);
+    v.fetch_max(0, 16, Ordering::Relaxed);
+}
+
+#[test]
+fn test_fill_atomic() {
+    use sux::traits::bit_field_slice::AtomicBitFieldSlice;
+
+    let ord = Ordering::Relaxed;
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=64 {
+        for len in [0, 1, 10, 64, 100] {
+            let mut v = AtomicBitFieldVec::<u64>::new(bit_width, len);
+            let value = rng.gen::<u64>() & v.mask();
+            v.fill_atomic(value, ord);
+            assert!((0..len).all(|i| v.get_atomic(i, ord) == value));
+            v.reset_with(ord);
+            assert!((0..len).all(|i| v.get_atomic(i, ord) == 0));
+            v.fill_atomic(v.mask(), ord);
+            v.reset_atomic(ord);
+            assert!((0..len).all(|i| v.get_atomic(i, ord) == 0));
+        }
+    }
+}
+
+#[test]
+fn test_atomic_iter() {
+    use sux::traits::bit_field_slice::AtomicBitFieldSlice;
+
+    let ord = Ordering::Relaxed;
+    let mut rng = SmallRng::seed_from_u64(0);
+    for bit_width in 0..=64 {
+        for len in [0, 1, 2, 63, 64, 65, 200] {
+            let v = AtomicBitFieldVec::<u64>::new(bit_width, len);
+            let values = (0..len)
+                .map(|_| rng.gen::<u64>() & v.mask())
+                .collect::<Vec<_>>();
+            // Differently from set_atomic, fetch_max supports the full bit width
+            for (i, &value) in values.iter().enumerate() {
+                v.fetch_max(i, value, ord);
+            }
+            let iter = v.iter(ord);
+            assert_eq!(iter.len(), len);
+            assert_eq!(iter.collect::<Vec<_>>(), values);
+            assert!((0..len).all(|i| v.get_atomic(i, ord) == values[i]));
+        }
+    }
+
+    // Concurrent reads of counters being incremented: values never decrease
+    let counts = AtomicBitFieldVec::<u64>::new(16, 100);
+    std::thread::scope(|s| {
+        let counts = &counts;
+        s.spawn(move || {
+            for i in 0..10_000 {
+                counts.fetch_add(i % 100, 1, ord);
+            }
+        });
+        s.spawn(move || {
+            let mut last = vec![0; 100];
+            for _ in 0..100 {
+                for (i, value) in counts.iter(Ordering::Acquire).enumerate() {
+                    assert!(value >= last[i]);
+                    last[i] = value;
+                }
+            }
+        });
+    });
+    assert!(counts.iter(ord).all(|value| value == 100));
+}
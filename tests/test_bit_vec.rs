@@ -5,13 +5,14 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
+use common_traits::SelectInWord;
 use core::sync::atomic::Ordering;
 use epserde::prelude::*;
 use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
 use rand::{RngCore, SeedableRng};
-use sux::bits::bit_vec::BitVec;
-use sux::prelude::AtomicBitVec;
+use sux::bits::bit_vec::{BitVec, Lsb0, Msb0};
+use sux::prelude::{AtomicBitVec, BitCount, BitLength, CountBitVec, Select, SelectZero, Word};
 
 #[test]
 fn test_bit_vec() {
@@ -240,9 +241,420 @@ fn test_epserde() {
     b.serialize(&mut file).unwrap();
     drop(file);
 
-    let c = <BitVec<Vec<usize>>>::mmap(&tmp_file, epserde::deser::Flags::empty()).unwrap();
+    let c = <BitVec>::mmap(&tmp_file, epserde::deser::Flags::empty()).unwrap();
 
     for i in 0..200 {
         assert_eq!(b.get(i), c.get(i));
     }
 }
+
+fn test_word<W: Word + SelectInWord>() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 31, 32, 33, 64, 65, 100, 1000, 10000] {
+        let bits = (0..len)
+            .map(|_| rng.next_u64() % 3 == 0)
+            .collect::<Vec<_>>();
+        let mut b = BitVec::<W>::with_value(len, false);
+        for (i, &bit) in bits.iter().enumerate() {
+            b.set(i, bit);
+        }
+        assert_eq!(b.into_iter().collect::<Vec<_>>(), bits);
+        assert_eq!(b.count_ones(), bits.iter().filter(|&&bit| bit).count());
+        let ones = BitVec::<W>::with_value(len, true);
+        assert_eq!(ones.count_ones(), len);
+        assert_eq!(
+            ones.as_ref(),
+            (!BitVec::<W>::with_value(len, false)).as_ref()
+        );
+
+        let b: CountBitVec<W> = b.into();
+        let ones = bits
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &bit)| bit.then_some(i))
+            .collect::<Vec<_>>();
+        let zeros = bits
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &bit)| (!bit).then_some(i))
+            .collect::<Vec<_>>();
+        assert_eq!(b.count(), ones.len());
+        for (rank, &pos) in ones.iter().enumerate() {
+            assert_eq!(b.select(rank), Some(pos));
+        }
+        assert_eq!(b.select(ones.len()), None);
+        for (rank, &pos) in zeros.iter().enumerate() {
+            assert_eq!(b.select_zero(rank), Some(pos));
+        }
+        assert_eq!(b.select_zero(zeros.len()), None);
+    }
+}
+
+#[test]
+fn test_words() {
+    test_word::<u32>();
+    test_word::<u64>();
+}
+
+#[test]
+fn test_bit_order() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0_usize, 1, 10, 64, 65, 100, 1000] {
+        let words = (0..len.div_ceil(64))
+            .map(|_| rng.next_u64())
+            .collect::<Vec<_>>();
+        let lsb = unsafe { BitVec::<u64, _, Lsb0>::from_raw_parts(words.clone(), len) };
+        let msb = unsafe { BitVec::<u64, _, Msb0>::from_raw_parts(words.clone(), len) };
+        // The same bit, numbered in the two orders; we need all bits of the words
+        let reverse = |i: usize| i / 64 * 64 + 63 - i % 64;
+        let lsb_words =
+            unsafe { BitVec::<u64, _, Lsb0>::from_raw_parts(words.clone(), words.len() * 64) };
+
+        for i in 0..len {
+            assert_eq!(msb.get(i), lsb_words.get(reverse(i)));
+            assert_eq!(msb[i], (words[i / 64] >> (63 - i % 64)) & 1 != 0);
+        }
+        assert!(msb.into_iter().eq((0..len).map(|i| msb.get(i))));
+
+        let lsb_ones = lsb.iter_ones().collect::<Vec<_>>();
+        assert_eq!(lsb_ones, (0..len).filter(|&i| lsb[i]).collect::<Vec<_>>());
+        let msb_ones = msb.iter_ones().collect::<Vec<_>>();
+        assert_eq!(msb_ones, (0..len).filter(|&i| msb[i]).collect::<Vec<_>>());
+
+        // Setting bits in MSB-first order and reading them back
+        let mut built = BitVec::<u64, _, Msb0>::with_value(len, false);
+        for &i in &msb_ones {
+            built.set(i, true);
+        }
+        assert!(built.iter_ones().eq(msb_ones.iter().copied()));
+        let pushed = (0..len).map(|i| msb[i]).collect::<BitVec<u64, _, Msb0>>();
+        assert!(pushed.iter_ones().eq(msb_ones.iter().copied()));
+
+        let mut filled = BitVec::<u64, _, Msb0>::with_value(len, false);
+        filled.fill(true);
+        assert_eq!(filled.count_ones(), len);
+        filled.flip();
+        assert_eq!(filled.count_ones(), 0);
+        filled.flip();
+        assert_eq!(filled.iter_ones().count(), len);
+    }
+}
+
+#[test]
+fn test_changed_positions() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0_usize, 1, 10, 64, 65, 100, 1000] {
+        let a = (0..len)
+            .map(|_| rng.next_u32() % 2 == 0)
+            .collect::<BitVec>();
+        let b = (0..len)
+            .map(|_| rng.next_u32() % 2 == 0)
+            .collect::<BitVec>();
+        let expected = (0..len).filter(|&i| a[i] != b[i]).collect::<Vec<_>>();
+        assert_eq!(a.changed_positions(&b).collect::<Vec<_>>(), expected);
+        assert_eq!(b.changed_positions(&a).collect::<Vec<_>>(), expected);
+        assert_eq!(a.changed_positions(&a).count(), 0);
+
+        let a = (0..len)
+            .map(|_| rng.next_u32() % 2 == 0)
+            .collect::<BitVec<u16, _, Msb0>>();
+        let b = (0..len)
+            .map(|_| rng.next_u32() % 2 == 0)
+            .collect::<BitVec<u16, _, Msb0>>();
+        let expected = (0..len).filter(|&i| a[i] != b[i]).collect::<Vec<_>>();
+        assert_eq!(a.changed_positions(&b).collect::<Vec<_>>(), expected);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_changed_positions_different_lengths() {
+    let a = BitVec::new(10);
+    let b = BitVec::new(11);
+    a.changed_positions(&b).count();
+}
+
+#[test]
+fn test_balanced_split_points() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 100, 1000, 10_000] {
+        for density in [0, 1, 50, 100] {
+            let bits: CountBitVec = (0..len)
+                .map(|_| (rng.next_u64() % 100) < density)
+                .collect::<BitVec>()
+                .into();
+            let count = bits.count();
+            for parts in [1, 2, 3, 7, 64, 200] {
+                let points = bits.balanced_split_points(parts);
+                assert_eq!(points.len(), parts - 1);
+                let mut bounds = vec![0];
+                bounds.extend(&points);
+                bounds.push(len);
+                for segment in bounds.windows(2) {
+                    assert!(segment[0] <= segment[1]);
+                    let ones = (segment[0]..segment[1]).filter(|&i| bits.get(i)).count();
+                    assert!(ones == count / parts || ones == count.div_ceil(parts));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_push_runs() {
+    fn check<O: sux::bits::bit_vec::BitOrder>() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut bits = BitVec::<u32, Vec<u32>, O>::with_value(0, false);
+        let mut expected = vec![];
+        for _ in 0..1000 {
+            let n = (rng.next_u64() % 100) as usize;
+            match rng.next_u64() % 3 {
+                0 => {
+                    bits.push_ones(n);
+                    expected.resize(expected.len() + n, true);
+                }
+                1 => {
+                    bits.push_zeros(n);
+                    expected.resize(expected.len() + n, false);
+                }
+                _ => {
+                    let b = n > 50;
+                    bits.push(b);
+                    expected.push(b);
+                }
+            }
+            assert_eq!(bits.len(), expected.len());
+        }
+        for (i, &b) in expected.iter().enumerate() {
+            assert_eq!(bits.get(i), b, "index {}", i);
+        }
+
+        // Runs after a truncation must not expose stale bits
+        bits.resize(expected.len() / 2, false);
+        expected.truncate(expected.len() / 2);
+        bits.push_zeros(100);
+        expected.resize(expected.len() + 100, false);
+        bits.push(true);
+        expected.push(true);
+        assert_eq!(bits.len(), expected.len());
+        for (i, &b) in expected.iter().enumerate() {
+            assert_eq!(bits.get(i), b, "index {}", i);
+        }
+    }
+    check::<Lsb0>();
+    check::<Msb0>();
+}
+
+#[test]
+fn test_bit_ops() {
+    fn check<O: sux::bits::bit_vec::BitOrder>() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for len in [0, 1, 63, 64, 65, 1000] {
+            let a_bits = (0..len)
+                .map(|_| rng.next_u32() % 2 == 0)
+                .collect::<Vec<_>>();
+            let b_bits = (0..len)
+                .map(|_| rng.next_u32() % 3 == 0)
+                .collect::<Vec<_>>();
+            let mut a = BitVec::<usize, Vec<usize>, O>::with_value(len, false);
+            let mut b = BitVec::<usize, Vec<usize>, O>::with_value(len, false);
+            for i in 0..len {
+                a.set(i, a_bits[i]);
+                b.set(i, b_bits[i]);
+            }
+
+            let and = &a & &b;
+            let or = &a | &b;
+            let xor = a.clone() ^ &b;
+            let not = !&a;
+            let mut and_assign = a.clone();
+            and_assign &= &b;
+            let mut or_assign = a.clone();
+            or_assign |= &b;
+            let mut xor_assign = a.clone();
+            xor_assign ^= &b;
+            for i in 0..len {
+                assert_eq!(and.get(i), a_bits[i] & b_bits[i], "index {}", i);
+                assert_eq!(or.get(i), a_bits[i] | b_bits[i], "index {}", i);
+                assert_eq!(xor.get(i), a_bits[i] ^ b_bits[i], "index {}", i);
+                assert_eq!(not.get(i), !a_bits[i], "index {}", i);
+                assert_eq!(and_assign.get(i), and.get(i));
+                assert_eq!(or_assign.get(i), or.get(i));
+                assert_eq!(xor_assign.get(i), xor.get(i));
+            }
+            assert_eq!(and.len(), len);
+            assert_eq!(not.count_ones(), len - a.count_ones());
+            assert_eq!((!a).count_ones(), not.count_ones());
+        }
+    }
+    check::<Lsb0>();
+    check::<Msb0>();
+}
+
+#[test]
+fn test_count_bit_ops() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 64, 65, 1000] {
+        let a: CountBitVec = (0..len)
+            .map(|_| rng.next_u32() % 2 == 0)
+            .collect::<BitVec>()
+            .into();
+        let b: CountBitVec = (0..len)
+            .map(|_| rng.next_u32() % 5 == 0)
+            .collect::<BitVec>()
+            .into();
+        let and = &a & &b;
+        let or = &a | &b;
+        let xor = &a ^ &b;
+        let not = !&a;
+        for (result, expected) in [
+            (&and, (0..len).filter(|&i| a.get(i) & b.get(i)).count()),
+            (&or, (0..len).filter(|&i| a.get(i) | b.get(i)).count()),
+            (&xor, (0..len).filter(|&i| a.get(i) ^ b.get(i)).count()),
+            (&not, (0..len).filter(|&i| !a.get(i)).count()),
+        ] {
+            assert_eq!(result.count(), expected);
+            assert_eq!(BitLength::len(result), len);
+        }
+        let mut c = a.clone();
+        c |= &b;
+        assert_eq!(c.count(), or.count());
+        assert_eq!((!a).count(), not.count());
+    }
+}
+
+#[test]
+#[should_panic(expected = "The bit vectors have different lengths")]
+fn test_bit_ops_different_lengths() {
+    let mut a: BitVec = BitVec::new(10);
+    a &= &BitVec::new(11);
+}
+
+#[test]
+fn test_count_ones_in_range() {
+    fn check<O: sux::bits::bit_vec::BitOrder>() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let len = 300;
+        let bits_vec = (0..len)
+            .map(|_| rng.next_u32() % 3 == 0)
+            .collect::<Vec<_>>();
+        let mut bits = BitVec::<usize, Vec<usize>, O>::with_value(len, false);
+        for (i, &b) in bits_vec.iter().enumerate() {
+            bits.set(i, b);
+        }
+        for start in 0..=len {
+            for end in start..=len {
+                let expected = bits_vec[start..end].iter().filter(|&&b| b).count();
+                assert_eq!(
+                    bits.count_ones_in_range(start..end),
+                    expected,
+                    "range {}..{}",
+                    start,
+                    end
+                );
+            }
+        }
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 10..5;
+        assert_eq!(bits.count_ones_in_range(reversed), 0);
+    }
+    check::<Lsb0>();
+    check::<Msb0>();
+
+    // A range ending on the last word boundary must not read past the end
+    let bits: BitVec<u64, _> = unsafe { BitVec::from_raw_parts(vec![u64::MAX; 2], 128) };
+    assert_eq!(bits.count_ones_in_range(0..128), 128);
+    assert_eq!(bits.count_ones_in_range(64..128), 64);
+    assert_eq!(bits.count_ones_in_range(128..128), 0);
+    let count: CountBitVec<u64, _> = bits.into();
+    assert_eq!(count.count_ones_in_range(1..127), 126);
+}
+
+#[test]
+#[should_panic(expected = "Range end out of bounds")]
+fn test_count_ones_in_range_out_of_bounds() {
+    let bits: BitVec = BitVec::new(10);
+    bits.count_ones_in_range(5..11);
+}
+
+#[test]
+fn test_next_prev_set() {
+    fn check<O: sux::bits::bit_vec::BitOrder>() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for len in [0, 1, 63, 64, 65, 1000] {
+            for modulus in [1, 2, 50, 1000] {
+                let bits_vec = (0..len)
+                    .map(|_| rng.next_u32() % modulus == 0)
+                    .collect::<Vec<_>>();
+                let mut bits = BitVec::<usize, Vec<usize>, O>::with_value(len, false);
+                for (i, &b) in bits_vec.iter().enumerate() {
+                    bits.set(i, b);
+                }
+                for from in 0..len + 2 {
+                    let next = (from..len).find(|&i| bits_vec[i]);
+                    let prev = (0..=from.min(len.saturating_sub(1)))
+                        .rev()
+                        .find(|&i| i < len && bits_vec[i]);
+                    assert_eq!(bits.next_set(from), next, "from: {}", from);
+                    assert_eq!(bits.prev_set(from), prev, "from: {}", from);
+                }
+            }
+        }
+    }
+    check::<Lsb0>();
+    check::<Msb0>();
+
+    // Ones beyond the length must be ignored
+    let bits: BitVec<u64, _> = unsafe { BitVec::from_raw_parts(vec![1 << 40], 10) };
+    assert_eq!(bits.next_set(0), None);
+    assert_eq!(bits.prev_set(100), None);
+}
+
+#[test]
+fn test_from_bytes() -> anyhow::Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut bytes = vec![0_u8; 104];
+    rng.fill_bytes(&mut bytes);
+    for len in [0, 1, 7, 8, 9, 63, 64, 65, 127, 128, 799, 800] {
+        // Check also byte slices that are not aligned to words
+        for offset in [0, 1, 3] {
+            let bytes = &bytes[offset..];
+            let bits = BitVec::<u64>::from_bytes(bytes, len)?;
+            assert_eq!(bits.len(), len);
+            for i in 0..len {
+                assert_eq!(
+                    bits.get(i),
+                    bytes[i / 8] & (1 << (i % 8)) != 0,
+                    "index {}",
+                    i
+                );
+            }
+            // Bits beyond the length must be zero
+            let count = (0..len).filter(|&i| bits.get(i)).count();
+            assert_eq!(bits.count_ones(), count);
+
+            let bits = BitVec::<u16>::from_bytes(bytes, len)?;
+            assert_eq!(bits.count_ones(), count);
+
+            // With Msb0, bits are indexed from the most significant bit of each byte
+            let bits = BitVec::<u64, Vec<u64>, Msb0>::from_bytes(bytes, len)?;
+            assert_eq!(bits.len(), len);
+            for i in 0..len {
+                assert_eq!(
+                    bits.get(i),
+                    bytes[i / 8] & (0x80 >> (i % 8)) != 0,
+                    "index {}",
+                    i
+                );
+            }
+            let count = (0..len).filter(|&i| bits.get(i)).count();
+            assert_eq!(bits.count_ones(), count);
+            let bits = BitVec::<u16, Vec<u16>, Msb0>::from_bytes(bytes, len)?;
+            assert_eq!(bits.count_ones(), count);
+        }
+    }
+    let bits = BitVec::<u64, Vec<u64>, Msb0>::from_bytes(&[0xff, 0xff], 10)?;
+    assert_eq!(bits.count_ones(), 10);
+    assert!(BitVec::<u64>::from_bytes(&[0; 3], 25).is_err());
+    Ok(())
+}
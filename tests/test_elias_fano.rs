@@ -218,3 +218,818 @@ fn test_epserde() -> Result<()> {
     }
     Ok(())
 }
+
+#[test]
+fn test_space_overhead() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1000, 1_000_000), (10_000, 1 << 20), (100_000, 1 << 30)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for value in values.iter() {
+            efb.push(*value)?;
+        }
+        let ef = efb.build();
+        let overhead = ef.space_overhead();
+        assert!(overhead.is_finite());
+        assert!(overhead > 1.0, "{}", overhead);
+        assert!(overhead < 1.5, "{}", overhead);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_with_low_high_bits() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(100, 1000), (100, 100), (1000, 100)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for value in values.iter() {
+            efb.push(*value)?;
+        }
+        let ef = efb.build();
+
+        // Store the lower bits on disk and map them back
+        let (_, _, _, low_bits, high_bits) = ef.clone().into_raw_parts();
+        let tmp_file = std::env::temp_dir().join("test_with_low_bits.bin");
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+        low_bits.serialize(&mut file)?;
+        drop(file);
+        let mapped = <BitFieldVec>::mmap(&tmp_file, epserde::deser::Flags::empty())?;
+
+        let ef = ef.with_low_bits((*mapped).clone())?;
+        let ef: EliasFano<SelectFixed1, _> = ef.with_high_bits(SelectFixed1::new(high_bits, n))?;
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), *v);
+        }
+
+        assert!(ef
+            .clone()
+            .with_low_bits(BitFieldVec::<usize>::new(0, n + 1))
+            .is_err());
+        assert!(ef.with_high_bits(BitVec::new(1).with_count(0)).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rev_iter() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [
+        (1, 10),
+        (10, 1000),
+        (100, 1000),
+        (100, 100),
+        (1000, 100),
+        (1000, 10),
+        (1000, 1 << 40),
+    ] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for value in values.iter() {
+            efb.push(*value)?;
+        }
+        let ef: EliasFano<SelectFixed1> = efb.build().convert_to()?;
+
+        let mut rev = values.clone();
+        rev.reverse();
+        assert_eq!(ef.iter().rev().collect::<Vec<_>>(), rev);
+        for from in [0, 1, n / 2, n - 1, n] {
+            assert_eq!(
+                ef.into_iter_from(from).rev().collect::<Vec<_>>(),
+                rev[..n - from]
+            );
+        }
+
+        // Mixed forward and backward iteration
+        let mut iter = ef.iter();
+        let (mut i, mut j) = (0, n);
+        while i < j {
+            if rng.gen_bool(0.5) {
+                assert_eq!(iter.next(), Some(values[i]));
+                i += 1;
+            } else {
+                j -= 1;
+                assert_eq!(iter.next_back(), Some(values[j]));
+            }
+            assert_eq!(iter.len(), j - i);
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn test_from_bit_vec_chunks() {
+    use rayon::prelude::*;
+    use sux::dict::elias_fano::EliasFanoConcurrentBuilder;
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (len, density) in [(100, 0.5), (1000, 0.1), (10000, 0.01), (100000, 0.5)] {
+        let mut bit_vec = BitVec::new(len);
+        bit_vec.set(0, true);
+        for i in 1..len {
+            bit_vec.set(i, rng.gen_bool(density));
+        }
+        let ef = EliasFano::from_bit_vec(&bit_vec);
+        assert_eq!(ef.len(), bit_vec.count_ones());
+        let ones = (0..len).filter(|&i| bit_vec.get(i)).collect::<Vec<_>>();
+        assert_eq!(ef.iter().collect::<Vec<_>>(), ones);
+
+        let words = bit_vec.as_ref();
+        for chunk_size in [1, 3, 64] {
+            let mut chunks = vec![];
+            let mut rank = 0;
+            for start in (0..words.len()).step_by(chunk_size) {
+                let end = (start + chunk_size).min(words.len());
+                chunks.push((start..end, rank));
+                rank += words[start..end]
+                    .iter()
+                    .map(|w| w.count_ones() as usize)
+                    .sum::<usize>();
+            }
+            let efb = EliasFanoConcurrentBuilder::new(ef.len(), len);
+            chunks.into_par_iter().for_each(|(words, rank)| unsafe {
+                efb.set_from_bit_vec_chunk(&bit_vec, words, rank, Ordering::Relaxed)
+            });
+            let par_ef = efb.build();
+            assert_eq!(par_ef.len(), ef.len());
+            assert_eq!(par_ef.iter().collect::<Vec<_>>(), ones);
+        }
+    }
+}
+
+#[test]
+fn test_bit_field_vec_round_trip() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [
+        (1, 0),
+        (10, 1000),
+        (100, 1000),
+        (100, 100),
+        (1000, 100),
+        (1000, 1 << 40),
+    ] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+        }
+        let ef = efb.build();
+
+        let bit_field_vec = ef.to_bit_field_vec();
+        assert_eq!(bit_field_vec.len(), n);
+        assert_eq!(
+            bit_field_vec.bit_width(),
+            u.checked_ilog2().map_or(0, |l| l + 1) as usize
+        );
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(bit_field_vec.get(i), value);
+        }
+
+        let ef = EliasFano::from_bit_field_vec(&bit_field_vec, u)?;
+        assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+    }
+
+    let mut bit_field_vec = BitFieldVec::<usize>::new(4, 2);
+    bit_field_vec.set(0, 2);
+    bit_field_vec.set(1, 1);
+    assert!(EliasFano::from_bit_field_vec(&bit_field_vec, 10).is_err());
+    assert!(EliasFano::from_bit_field_vec(&bit_field_vec, 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_iter_fold() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [
+        (1, 10),
+        (10, 1000),
+        (100, 100),
+        (1000, 100),
+        (1000, 10),
+        (10000, 1 << 30),
+    ] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+        }
+        let ef: EliasFano<SelectFixed1> = efb.build().convert_to()?;
+
+        let expected = (0..n).map(|i| ef.get(i)).collect::<Vec<_>>();
+        assert_eq!(expected, values);
+        let mut folded = vec![];
+        ef.iter().for_each(|x| folded.push(x));
+        assert_eq!(folded, expected);
+        assert_eq!(ef.iter().sum::<usize>(), expected.iter().sum::<usize>());
+
+        for from in [0, n / 3, n - 1, n] {
+            let folded = ef.into_iter_from(from).fold(vec![], |mut v, x| {
+                v.push(x);
+                v
+            });
+            assert_eq!(folded, expected[from..]);
+        }
+
+        // Mixed external and internal iteration, in both directions
+        let mut iter = ef.iter();
+        let front = iter.next();
+        let back = iter.next_back();
+        let mut folded = vec![];
+        iter.for_each(|x| folded.push(x));
+        assert_eq!(front, expected.first().copied());
+        if n > 1 {
+            assert_eq!(back, expected.last().copied());
+            assert_eq!(folded, expected[1..n - 1]);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_locate() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 10), (10, 1000), (100, 100), (1000, 100), (1000, 10)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+        }
+        let ef: EliasFano<SelectFixed1> = efb.build().convert_to()?;
+        let ef: EliasFano<SelectZeroFixed1<SelectFixed1>> = ef.convert_to()?;
+
+        for value in 0..u + 10 {
+            let result = ef.locate(value);
+            // Vec::binary_search might return any of the duplicates
+            assert_eq!(result.is_ok(), values.binary_search(&value).is_ok());
+            let rank = values.partition_point(|&x| x < value);
+            if result.is_ok() {
+                assert_eq!(result, Ok(rank), "value {}", value);
+            } else {
+                assert_eq!(result, Err(rank), "value {}", value);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_gap_histogram() -> Result<()> {
+    // Gaps: 0, 1, 2, 3, 4, 100
+    let values = [5, 5, 6, 8, 11, 15, 115];
+    let mut efb = EliasFanoBuilder::new(values.len(), 115);
+    for &value in &values {
+        efb.push(value)?;
+    }
+    let ef: EliasFano<SelectFixed1> = efb.build().convert_to()?;
+    assert_eq!(ef.gap_histogram(8), vec![1, 1, 2, 1, 0, 0, 0, 1, 0]);
+    // Gaps of bit length at least 3 are counted in the last bucket
+    assert_eq!(ef.gap_histogram(3), vec![1, 1, 2, 2]);
+    assert_eq!(ef.gap_histogram(0), vec![6]);
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 10), (100, 1000), (1000, 100), (10000, 1 << 30)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+        }
+        let ef: EliasFano<SelectFixed1> = efb.build().convert_to()?;
+        let histogram = ef.gap_histogram(64);
+        assert_eq!(histogram.iter().sum::<usize>(), n - 1);
+        let mut expected = vec![0; 65];
+        for w in values.windows(2) {
+            expected[(usize::BITS - (w[1] - w[0]).leading_zeros()) as usize] += 1;
+        }
+        assert_eq!(histogram, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_diff() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n1, n2, u) in [
+        (1, 1, 10),
+        (10, 100, 1000),
+        (100, 100, 150),
+        (1000, 10, 1 << 20),
+    ] {
+        let mut sets = vec![];
+        let mut efs = vec![];
+        for n in [n1, n2] {
+            let set = (0..n)
+                .map(|_| rng.gen_range(0..u))
+                .collect::<std::collections::BTreeSet<_>>();
+            let mut efb = EliasFanoBuilder::new(set.len(), u);
+            for &value in &set {
+                efb.push(value)?;
+            }
+            let ef: EliasFano<SelectFixed1> = efb.build().convert_to()?;
+            sets.push(set);
+            efs.push(ef);
+        }
+
+        let (added, removed) = efs[0].diff(&efs[1]);
+        assert_eq!(
+            added,
+            sets[1].difference(&sets[0]).copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            removed,
+            sets[0].difference(&sets[1]).copied().collect::<Vec<_>>()
+        );
+        let (added, removed) = efs[1].diff(&efs[0]);
+        assert_eq!(
+            added,
+            sets[0].difference(&sets[1]).copied().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            removed,
+            sets[1].difference(&sets[0]).copied().collect::<Vec<_>>()
+        );
+        assert_eq!(efs[0].diff(&efs[0]), (vec![], vec![]));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_build_with_select() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 10), (256, 1000), (1000, 100), (10_000, 1 << 30)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+        values.sort();
+
+        let mut efb = EliasFanoBuilder::with_select(n, u);
+        let mut plain = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+            plain.push(value)?;
+        }
+        let ef_attached: EliasFano<SelectFixed1> = efb.clone().build().convert_to()?;
+        let ef = efb.build_with_select();
+        let ef_plain = plain.build_with_select();
+
+        for (i, &v) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), v);
+        }
+        let (.., high_bits) = ef.into_raw_parts();
+        let (.., attached_high_bits) = ef_attached.into_raw_parts();
+        let (.., plain_high_bits) = ef_plain.into_raw_parts();
+        for rank in 0..n {
+            assert_eq!(high_bits.select(rank), attached_high_bits.select(rank));
+            assert_eq!(high_bits.select(rank), plain_high_bits.select(rank));
+        }
+        assert_eq!(high_bits.select(n), None);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_interleaved() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [
+        (1, 0),
+        (1, 1 << 40),
+        (63, 1000),
+        (64, 64),
+        (1000, 100),
+        (1000, 1 << 62),
+        (10_000, 10_000),
+    ] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+        }
+        let ef: EliasFano<SelectFixed2> = efb.clone().build().convert_to()?;
+        let ef: EliasFano<SelectZeroFixed2<SelectFixed2>> = ef.convert_to()?;
+        let ief = efb.build_interleaved();
+
+        assert_eq!(ief.len(), n);
+        assert!(ief.iter().eq(values.iter().copied()));
+        for i in 0..n {
+            assert_eq!(ief.get(i), ef.get(i));
+        }
+        for _ in 0..1000 {
+            let value = rng.gen_range(0..=u);
+            assert_eq!(ief.succ(&value), ef.succ(&value));
+            assert_eq!(ief.succ_strict(&value), ef.succ_strict(&value));
+            assert_eq!(ief.pred(&value), ef.pred(&value));
+            assert_eq!(ief.pred_strict(&value), ef.pred_strict(&value));
+            assert_eq!(ief.contains(&value), values.binary_search(&value).is_ok());
+        }
+        for &value in &values {
+            let index = ief.index_of(&value).unwrap();
+            assert_eq!(ief.get(index), value);
+            assert_eq!(index, values.partition_point(|&x| x < value));
+        }
+
+        let tmp_file = std::env::temp_dir().join("test_interleaved_ef.bin");
+        ief.store(&tmp_file)?;
+        let mapped = <InterleavedEliasFano>::mmap(&tmp_file, Flags::empty())?;
+        assert!(mapped.iter().eq(values.iter().copied()));
+        std::fs::remove_file(&tmp_file)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pred_interpolated() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [
+        (1, 0),
+        (1, 100),
+        (100, 1000),
+        (10_000, 1 << 20),
+        (1000, 100),
+    ] {
+        for clustered in [false, true] {
+            let mut values = (0..n)
+                .map(|_| {
+                    if clustered && rng.gen_bool(0.9) {
+                        // Most values are packed in the last tenth of the range
+                        rng.gen_range(u - u / 10..=u)
+                    } else {
+                        rng.gen_range(0..=u)
+                    }
+                })
+                .collect::<Vec<_>>();
+            values.sort();
+
+            let mut efb = EliasFanoBuilder::new(n, u);
+            for &value in &values {
+                efb.push(value)?;
+            }
+            let ef: EliasFano<SelectFixed2> = efb.build().convert_to()?;
+            let ef: EliasFano<SelectZeroFixed2<SelectFixed2>> = ef.convert_to()?;
+
+            for value in (0..=u)
+                .step_by((u / 1000).max(1))
+                .chain(values.iter().copied())
+            {
+                assert_eq!(
+                    ef.pred_interpolated(value),
+                    ef.pred(&value),
+                    "value {}",
+                    value
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_lower_bits() -> Result<()> {
+    // Near powers of two floating-point logarithms are easily off by one
+    for (n, u, l) in [
+        (1, 1, 0),
+        (3, 8, 1),
+        (8, 8, 0),
+        (8, 15, 0),
+        (8, 16, 1),
+        (3, (1 << 48) - 1, 46),
+        (3, 3 << 47, 47),
+        (1, usize::MAX, usize::BITS as usize - 1),
+        (10, 5, 0),
+    ] {
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for i in 0..n {
+            efb.push(u / n * i)?;
+        }
+        let ef = efb.build();
+        assert_eq!(ef.clone().into_raw_parts().2, l, "n = {}, u = {}", n, u);
+        for i in 0..n {
+            assert_eq!(ef.get(i), u / n * i);
+        }
+    }
+
+    // An empty sequence
+    let ef = EliasFanoBuilder::new(0, 100).build();
+    assert_eq!(ef.len(), 0);
+    assert_eq!(EliasFano::<BitVec>::estimate_size(100, 0), 0);
+    Ok(())
+}
+
+#[test]
+fn test_pad_to() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 1), (10, 1000), (1000, 1000), (1000, 1 << 40)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        for pad in [1, 4, 8, 13] {
+            let mut efb = EliasFanoBuilder::with_select(n, u).pad_to(pad);
+            let mut plain = EliasFanoBuilder::new(n, u);
+            for &value in &values {
+                efb.push(value)?;
+                plain.push(value)?;
+            }
+            let ef: EliasFano<SelectFixed1> = efb.clone().build_with_select();
+            let plain: EliasFano<SelectFixed1> = plain.build_with_select();
+            assert_eq!(ef.len(), n);
+            for (i, &value) in values.iter().enumerate() {
+                assert_eq!(ef.get(i), value);
+            }
+            let (_, _, _, low_bits, high_bits) = ef.into_raw_parts();
+            let (_, _, _, plain_low_bits, plain_high_bits) = plain.into_raw_parts();
+            let (low_data, _, low_len) = low_bits.into_raw_parts();
+            let (plain_low_data, _, _) = plain_low_bits.into_raw_parts();
+            assert_eq!(low_len, n);
+            assert_eq!(low_data.len() % pad, 0);
+            assert_eq!(low_data.len(), plain_low_data.len().next_multiple_of(pad));
+            let high_words = AsRef::<[usize]>::as_ref(&high_bits).len();
+            let plain_high_words = AsRef::<[usize]>::as_ref(&plain_high_bits).len();
+            assert_eq!(high_words % pad, 0);
+            assert_eq!(high_words, plain_high_words.next_multiple_of(pad));
+
+            let ef: EliasFano<SelectFixed2> = efb.build().convert_to()?;
+            let ef: EliasFano<SelectZeroFixed2<SelectFixed2>> = ef.convert_to()?;
+            for (i, &value) in values.iter().enumerate() {
+                assert_eq!(
+                    ef.succ(&value),
+                    Some((values.partition_point(|&x| x < value), value))
+                );
+                assert_eq!(ef.pred(&value).map(|(_, v)| v), Some(value));
+                assert_eq!(ef.get(i), value);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pred_succ_out_of_range() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 2), (10, 100), (1000, 1000), (1000, 1 << 40)] {
+        for pad in [1, 4] {
+            let mut values = (0..n).map(|_| rng.gen_range(1..u)).collect::<Vec<_>>();
+            values.sort();
+            let mut efb = EliasFanoBuilder::new(n, u).pad_to(pad);
+            for &value in &values {
+                efb.push(value)?;
+            }
+            let ef: EliasFano<SelectFixed1> = efb.build().convert_to()?;
+            let ef: EliasFano<SelectZeroFixed1<SelectFixed1>> = ef.convert_to()?;
+            let (first, last) = (values[0], values[n - 1]);
+            let last_index = values.partition_point(|&x| x <= last) - 1;
+            for value in [last, last + 1, u, u + 1, 2 * u, usize::MAX] {
+                assert_eq!(ef.pred(&value), Some((last_index, last)));
+                if value > last {
+                    assert_eq!(ef.pred_strict(&value), Some((last_index, last)));
+                    assert_eq!(ef.succ(&value), None);
+                }
+            }
+            assert_eq!(ef.pred(&(first - 1)), None);
+            assert_eq!(ef.succ(&0), Some((0, first)));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rank() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 1), (10, 20), (100, 1000), (1000, 100), (1000, 1 << 20)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+        }
+        let ef: EliasFano<SelectFixed1> = efb.build().convert_to()?;
+        let ef: EliasFano<SelectZeroFixed1<SelectFixed1>> = ef.convert_to()?;
+        for &value in &values {
+            assert_eq!(ef.rank(value), values.partition_point(|&x| x <= value));
+        }
+        for _ in 0..1000 {
+            let value = rng.gen_range(0..=u + 10);
+            assert_eq!(ef.rank(value), values.partition_point(|&x| x <= value));
+        }
+        assert_eq!(ef.rank(usize::MAX), n);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_from_sorted() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 2, 10, 1000] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..10_000)).collect::<Vec<_>>();
+        values.sort();
+        let ef = EliasFano::from_sorted_iter(values.iter().copied())?;
+        assert_eq!(ef.len(), n);
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), value);
+        }
+        let (u, _, _, _, _) = ef.into_raw_parts();
+        assert_eq!(u, values.last().copied().unwrap_or(0));
+        let ef = EliasFano::from_sorted_slice(&values)?;
+        assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+    }
+    assert!(EliasFano::from_sorted_slice(&[1, 3, 2]).is_err());
+    assert!(EliasFano::from_sorted_iter([5, 0]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_with_select_index() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(0, 0), (1, 10), (10_000, 100), (10_000, 1 << 30)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+        }
+        let ef = efb.build().with_select_index();
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), value);
+        }
+        assert_eq!(ef.iter().collect::<Vec<_>>(), values);
+        let ef: EliasFano<SelectZeroFixed2<SelectFixed2>> = ef.convert_to()?;
+        for &value in &values {
+            assert_eq!(ef.succ(&value).map(|(_, v)| v), Some(value));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_select_zero() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 10), (100, 1000), (1000, 100)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let ef = EliasFano::from_sorted_slice(&values)?;
+        let u = *values.last().unwrap();
+        let ef: EliasFano<SelectZeroFixed1> = ef.convert_to()?;
+        let (_, _, l, _, _) = ef.clone().into_raw_parts();
+        for rank in 0..=u >> l {
+            let pos = ef.select_zero(rank).unwrap();
+            assert_eq!(pos - rank, values.partition_point(|&x| x < (rank + 1) << l));
+        }
+        assert_eq!(ef.select_zero((u >> l) + 1), None);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_contains_duplicates() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 1), (100, 10), (1000, 1000), (1000, 1 << 30)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for &value in &values {
+            efb.push(value)?;
+        }
+        let ef: EliasFano<SelectFixed1> = efb.build().convert_to()?;
+        let ef: EliasFano<SelectZeroFixed1<SelectFixed1>> = ef.convert_to()?;
+        for value in values
+            .iter()
+            .copied()
+            .chain((0..1000).map(|_| rng.gen_range(0..=u)))
+            .chain([u, u + 1, usize::MAX])
+        {
+            let expected = values.binary_search(&value).is_ok();
+            assert_eq!(ef.contains(&value), expected);
+            assert_eq!(
+                ef.index_of(&value),
+                expected.then(|| values.partition_point(|&x| x < value))
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn test_from_sorted_slice_par() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(0, 0), (1, 10), (1000, 100), (100_000, 1 << 40)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let (u, n, l, low_bits, high_bits) =
+            EliasFano::from_sorted_slice(&values)?.into_raw_parts();
+        let (par_u, par_n, par_l, par_low_bits, par_high_bits) =
+            EliasFano::from_sorted_slice_par(&values)?.into_raw_parts();
+        assert_eq!((u, n, l), (par_u, par_n, par_l));
+        assert_eq!(low_bits.into_raw_parts(), par_low_bits.into_raw_parts());
+        assert_eq!(
+            AsRef::<[usize]>::as_ref(&high_bits),
+            AsRef::<[usize]>::as_ref(&par_high_bits)
+        );
+    }
+    assert!(EliasFano::from_sorted_slice_par(&[1, 2, 3, 2, 5]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_epserde_queries() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 1), (100, 1000), (10_000, 1000), (10_000, 1 << 40)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let ef = EliasFano::from_sorted_slice(&values)?.with_select_index();
+        let ef: EliasFano<SelectZeroFixed2<SelectFixed2>> = ef.convert_to()?;
+
+        let tmp_file = std::env::temp_dir().join("test_epserde_queries.bin");
+        ef.store(&tmp_file)?;
+        let mapped = <EliasFano<SelectZeroFixed2<SelectFixed2>>>::mmap(&tmp_file, Flags::empty())?;
+        let loaded = <EliasFano<SelectZeroFixed2<SelectFixed2>>>::load_mem(&tmp_file)?;
+
+        assert_eq!(mapped.len(), ef.len());
+        assert_eq!(loaded.len(), ef.len());
+        assert_eq!(mapped.iter().collect::<Vec<_>>(), values);
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(mapped.get(i), value);
+            assert_eq!(loaded.get(i), value);
+        }
+        for _ in 0..1000 {
+            let value = rng.gen_range(0..=u + 1);
+            assert_eq!(mapped.succ(&value), ef.succ(&value));
+            assert_eq!(mapped.pred(&value), ef.pred(&value));
+            assert_eq!(mapped.rank(value), ef.rank(value));
+            assert_eq!(mapped.contains(&value), ef.contains(&value));
+            assert_eq!(loaded.succ(&value), ef.succ(&value));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_get_batch() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(1, 1), (100, 1000), (10_000, 1000), (10_000, 1 << 40)] {
+        let mut values = (0..n).map(|_| rng.gen_range(0..=u)).collect::<Vec<_>>();
+        values.sort();
+        let ef: EliasFano<SelectFixed1> = EliasFano::from_sorted_slice(&values)?.convert_to()?;
+
+        // Sorted indices with both small and large gaps, and unsorted indices
+        let mut sorted = (0..n).filter(|_| rng.gen_bool(0.1)).collect::<Vec<_>>();
+        sorted.extend((0..n).step_by(997));
+        sorted.sort();
+        let unsorted = (0..1000).map(|_| rng.gen_range(0..n)).collect::<Vec<_>>();
+        for indices in [&sorted, &unsorted, &(0..n).collect::<Vec<_>>()] {
+            let mut out = vec![0; indices.len() + 1];
+            ef.get_batch(indices, &mut out);
+            let expected = indices.iter().map(|&i| values[i]).collect::<Vec<_>>();
+            assert_eq!(out[..indices.len()], expected);
+        }
+
+        for (start, end) in [(0, 0), (0, n), (n / 3, n / 2), (n, n)] {
+            assert_eq!(
+                ef.get_range(start..end).collect::<Vec<_>>(),
+                values[start..end]
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "Index out of bounds")]
+fn test_get_batch_out_of_bounds() {
+    let ef = EliasFano::from_sorted_slice(&[1, 2, 3]).unwrap();
+    let ef: EliasFano<SelectFixed1> = ef.convert_to().unwrap();
+    ef.get_batch(&[0, 3], &mut [0; 2]);
+}
+
+#[test]
+fn test_epserde_header() -> Result<()> {
+    let ef: EliasFano = EliasFano::from_sorted_slice(&[0, 3, 3, 10, 1000])?;
+    let mut bytes = vec![];
+    ef.serialize(&mut bytes)?;
+
+    // The header is checked on deserialization
+    let loaded = <EliasFano>::deserialize_full(&mut std::io::Cursor::new(&bytes))?;
+    assert_eq!(loaded.iter().collect::<Vec<_>>(), vec![0, 3, 3, 10, 1000]);
+
+    // Data written with the opposite endianness is rejected
+    let mut reversed = bytes.clone();
+    reversed[..8].reverse();
+    assert!(matches!(
+        <EliasFano>::deserialize_full(&mut std::io::Cursor::new(&reversed)),
+        Err(epserde::deser::Error::EndiannessError)
+    ));
+
+    // Unknown headers are rejected
+    let mut corrupted = bytes.clone();
+    corrupted[..8].copy_from_slice(b"notmagic");
+    assert!(matches!(
+        <EliasFano>::deserialize_full(&mut std::io::Cursor::new(&corrupted)),
+        Err(epserde::deser::Error::MagicCookieError(_))
+    ));
+    Ok(())
+}
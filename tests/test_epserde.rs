@@ -0,0 +1,382 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use epserde::prelude::*;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::path::PathBuf;
+use sux::prelude::*;
+
+/// Serialize a structure to a temporary file and return the file path.
+fn store<T: Serialize>(value: &T, name: &str) -> Result<PathBuf> {
+    let tmp_file = std::env::temp_dir().join(name);
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&tmp_file)?);
+    value.serialize(&mut file)?;
+    Ok(tmp_file)
+}
+
+#[test]
+fn test_mmap_family() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let n = 1000;
+    let u = 10000;
+
+    let mut bit_vec = BitVec::<u32>::with_value(u, false);
+    for _ in 0..n {
+        bit_vec.set(rng.gen_range(0..u), true);
+    }
+    let tmp_file = store(&bit_vec, "test_mmap_bit_vec.bin")?;
+    let mapped = <BitVec<u32>>::mmap(&tmp_file, Flags::empty())?;
+    for i in 0..u {
+        assert_eq!(mapped.get(i), bit_vec.get(i));
+    }
+
+    let mut bit_vec = BitVec::new(u);
+    for _ in 0..n {
+        bit_vec.set(rng.gen_range(0..u), true);
+    }
+    let count_bit_vec: CountBitVec = bit_vec.into();
+    let tmp_file = store(&count_bit_vec, "test_mmap_count_bit_vec.bin")?;
+    let mapped = <CountBitVec>::mmap(&tmp_file, Flags::empty())?;
+    assert_eq!(mapped.count(), count_bit_vec.count());
+    for i in 0..count_bit_vec.count() {
+        assert_eq!(mapped.select(i), count_bit_vec.select(i));
+    }
+
+    let mut bit_field_vec = BitFieldVec::<u16>::new(11, n);
+    for i in 0..n {
+        bit_field_vec.set(i, rng.gen_range(0..1 << 11));
+    }
+    let tmp_file = store(&bit_field_vec, "test_mmap_bit_field_vec.bin")?;
+    let mapped = <BitFieldVec<u16>>::mmap(&tmp_file, Flags::empty())?;
+    assert_eq!(mapped.bit_width(), 11);
+    for i in 0..n {
+        assert_eq!(mapped.get(i), bit_field_vec.get(i));
+    }
+
+    let mut values = (0..n).map(|_| rng.gen_range(0..u)).collect::<Vec<_>>();
+    values.sort();
+    let mut efb = EliasFanoBuilder::new(n, u);
+    for &value in &values {
+        efb.push(value)?;
+    }
+    let ef: EliasFano<SelectFixed2> = efb.build().convert_to()?;
+    let tmp_file = store(&ef, "test_mmap_elias_fano.bin")?;
+    let mapped = <EliasFano<SelectFixed2>>::mmap(&tmp_file, Flags::empty())?;
+    for (i, &value) in values.iter().enumerate() {
+        assert_eq!(mapped.get(i), value);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_map_bit_field_vec() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut bit_field_vec = BitFieldVec::<u16>::new(11, 1000);
+    for i in 0..1000 {
+        bit_field_vec.set(i, rng.gen_range(0..1 << 11));
+    }
+    let tmp_file = store(&bit_field_vec, "test_map_bit_field_vec.bin")?;
+    let mapped = map_bit_field_vec::<u16>(&tmp_file, Flags::empty())?;
+    assert_eq!(mapped.len(), 1000);
+    assert_eq!(mapped.bit_width(), 11);
+    for i in 0..1000 {
+        assert_eq!(mapped.get(i), bit_field_vec.get(i));
+    }
+    // Wrong word type
+    assert!(map_bit_field_vec::<u32>(&tmp_file, Flags::empty()).is_err());
+
+    // Not enough words for the declared length
+    let broken = unsafe { BitFieldVec::<usize>::from_raw_parts(vec![0; 2], 10, 100) };
+    let tmp_file = store(&broken, "test_map_bit_field_vec_broken.bin")?;
+    assert!(map_bit_field_vec::<usize>(&tmp_file, Flags::empty()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_map_bit_field_vec_mut() -> Result<()> {
+    use std::sync::atomic::Ordering;
+    use sux::traits::bit_field_slice::AtomicHelper;
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut bit_field_vec = BitFieldVec::<u64>::new(11, 1000);
+    for i in 0..1000 {
+        bit_field_vec.set(i, rng.gen_range(0..1 << 11));
+    }
+    let tmp_file = store(&bit_field_vec, "test_map_bit_field_vec_mut.bin")?;
+
+    let mut mapped = unsafe { map_bit_field_vec_mut::<u64>(&tmp_file)? };
+    assert_eq!(mapped.get().len(), 1000);
+    for i in 0..1000 {
+        assert_eq!(mapped.get().get(i), bit_field_vec.get(i));
+    }
+    for i in (0..1000).step_by(3) {
+        let value = rng.gen_range(0..1 << 11);
+        mapped.get_mut().set(i, value);
+        bit_field_vec.set(i, value);
+    }
+    mapped.flush()?;
+    {
+        let atomic = mapped.as_atomic();
+        for i in (1..1000).step_by(3) {
+            atomic.set(i, 42, Ordering::Relaxed);
+            bit_field_vec.set(i, 42);
+        }
+    }
+    // Modifications are flushed on drop
+    drop(mapped);
+
+    let loaded = <BitFieldVec<u64>>::load_full(&tmp_file)?;
+    for i in 0..1000 {
+        assert_eq!(loaded.get(i), bit_field_vec.get(i));
+    }
+
+    // Wrong word type
+    assert!(unsafe { map_bit_field_vec_mut::<u32>(&tmp_file) }.is_err());
+    // Missing file
+    assert!(unsafe {
+        map_bit_field_vec_mut::<u64>(
+            std::env::temp_dir().join("test_map_bit_field_vec_mut_missing"),
+        )
+    }
+    .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_map_bit_vec_mut() -> Result<()> {
+    use std::sync::atomic::Ordering;
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut bit_vec = BitVec::new(1000);
+    for i in 0..1000 {
+        bit_vec.set(i, rng.gen_bool(0.5));
+    }
+    let tmp_file = store(&bit_vec, "test_map_bit_vec_mut.bin")?;
+
+    let mut mapped = unsafe { map_bit_vec_mut::<usize, Lsb0>(&tmp_file)? };
+    assert_eq!(mapped.get().len(), 1000);
+    for i in 0..1000 {
+        assert_eq!(mapped.get()[i], bit_vec[i]);
+    }
+    for i in (0..1000).step_by(3) {
+        let bit = rng.gen_bool(0.5);
+        mapped.get_mut().set(i, bit);
+        bit_vec.set(i, bit);
+    }
+    mapped.flush()?;
+    {
+        let atomic = mapped.as_atomic();
+        for i in (1..1000).step_by(3) {
+            atomic.set(i, true, Ordering::Relaxed);
+            bit_vec.set(i, true);
+        }
+    }
+    // Modifications are flushed on drop
+    drop(mapped);
+
+    let loaded = <BitVec>::load_full(&tmp_file)?;
+    assert_eq!(loaded.as_ref(), bit_vec.as_ref());
+
+    // Other word types and bit orders
+    let mut msb = BitVec::<u16, _, Msb0>::with_value(100, false);
+    msb.set(7, true);
+    let tmp_file = store(&msb, "test_map_bit_vec_mut_msb.bin")?;
+    let mut mapped = unsafe { map_bit_vec_mut::<u16, Msb0>(&tmp_file)? };
+    mapped.get_mut().set(99, true);
+    drop(mapped);
+    let loaded = <BitVec<u16, Vec<u16>, Msb0>>::load_full(&tmp_file)?;
+    assert_eq!(loaded.iter_ones().collect::<Vec<_>>(), vec![7, 99]);
+
+    // Wrong word type and bit order
+    assert!(unsafe { map_bit_vec_mut::<u32, Msb0>(&tmp_file) }.is_err());
+    assert!(unsafe { map_bit_vec_mut::<u16, Lsb0>(&tmp_file) }.is_err());
+
+    // Not enough words for the declared length
+    let broken = unsafe { BitVec::<usize>::from_raw_parts(vec![0; 2], 1000) };
+    let tmp_file = store(&broken, "test_map_bit_vec_mut_broken.bin")?;
+    assert!(unsafe { map_bit_vec_mut::<usize, Lsb0>(&tmp_file) }.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_misaligned_u128() -> Result<()> {
+    let mut bit_field_vec = BitFieldVec::<u128>::new(100, 10);
+    bit_field_vec.set(3, 1 << 99);
+    let mut bytes = std::io::Cursor::new(vec![]);
+    bit_field_vec.serialize(&mut bytes)?;
+    let bytes = bytes.into_inner();
+
+    // A buffer of u128 is suitably aligned for all supported word types
+    let mut aligned = vec![0_u128; bytes.len() / 16 + 2];
+    let buffer = unsafe { aligned.align_to_mut::<u8>().1 };
+    buffer[..bytes.len()].copy_from_slice(&bytes);
+    let deser = <BitFieldVec<u128>>::deserialize_eps(buffer)?;
+    assert_eq!(deser.get(3), 1 << 99);
+
+    // Shifting the data by one byte must result in an error, and not in UB
+    buffer.copy_within(0..bytes.len(), 1);
+    assert!(<BitFieldVec<u128>>::deserialize_eps(&buffer[1..]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_checked() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut values = (0..10_000)
+        .map(|_| rng.gen_range(0..1_000_000))
+        .collect::<Vec<_>>();
+    values.sort();
+    let ef: EliasFano = EliasFano::from_sorted_slice(&values)?;
+    let tmp_file = std::env::temp_dir().join("test_checked.bin");
+    store_checked(&ef, &tmp_file)?;
+
+    verify_checked(&tmp_file)?;
+    let full = load_full_checked::<EliasFano>(&tmp_file)?;
+    let mem = load_mem_checked::<EliasFano>(&tmp_file)?;
+    let mapped = mmap_checked::<EliasFano>(&tmp_file, Flags::empty())?;
+    // The standard methods ignore the trailer
+    let plain = <EliasFano>::mmap(&tmp_file, Flags::empty())?;
+    for (i, &value) in values.iter().enumerate() {
+        assert_eq!(full.get(i), value);
+        assert_eq!(mem.get(i), value);
+        assert_eq!(mapped.get(i), value);
+        assert_eq!(plain.get(i), value);
+    }
+
+    let bytes = std::fs::read(&tmp_file)?;
+    let broken_file = std::env::temp_dir().join("test_checked_broken.bin");
+
+    // Truncated file
+    for len in [0, 10, bytes.len() / 2, bytes.len() - 1] {
+        std::fs::write(&broken_file, &bytes[..len])?;
+        assert!(verify_checked(&broken_file).is_err());
+        assert!(mmap_checked::<EliasFano>(&broken_file, Flags::empty()).is_err());
+    }
+
+    // Corrupted payload
+    let mut corrupted = bytes.clone();
+    corrupted[bytes.len() / 2] ^= 1;
+    std::fs::write(&broken_file, &corrupted)?;
+    assert!(load_full_checked::<EliasFano>(&broken_file).is_err());
+
+    // Corrupted payload length
+    let mut corrupted = bytes.clone();
+    let len_pos = bytes.len() - 16;
+    corrupted[len_pos] ^= 1;
+    std::fs::write(&broken_file, &corrupted)?;
+    assert!(load_mem_checked::<EliasFano>(&broken_file).is_err());
+
+    // File without trailer
+    let plain_file = store(&ef, "test_checked_plain.bin")?;
+    assert!(verify_checked(&plain_file).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_advise() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut values = (0..100_000)
+        .map(|_| rng.gen_range(0..1 << 30))
+        .collect::<Vec<_>>();
+    values.sort();
+    let ef: EliasFano = EliasFano::from_sorted_slice(&values)?;
+    let tmp_file = store(&ef, "test_advise.bin")?;
+
+    for advice in [
+        Advice::Normal,
+        Advice::Random,
+        Advice::Sequential,
+        Advice::WillNeed,
+    ] {
+        let mapped = mmap_with_advice::<EliasFano>(&tmp_file, Flags::empty(), advice)?;
+        mapped.advise(Advice::WillNeed)?;
+        for i in (0..values.len()).step_by(101) {
+            assert_eq!(mapped.get(i), values[i]);
+        }
+        // Memory allocated on the heap can be advised, too
+        let loaded = <EliasFano>::load_mem(&tmp_file)?;
+        loaded.advise(advice)?;
+        ef.advise(advice)?;
+    }
+
+    let bit_field_vec = BitFieldVec::<u16>::new(11, 0);
+    bit_field_vec.advise(Advice::Random)?;
+    let bit_vec: BitVec = BitVec::new(1000);
+    bit_vec.advise(Advice::Sequential)?;
+    Ok(())
+}
+
+#[test]
+fn test_from_bytes() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut values = (0..10_000)
+        .map(|_| rng.gen_range(0..1_000_000))
+        .collect::<Vec<_>>();
+    values.sort();
+    let ef: EliasFano = EliasFano::from_sorted_slice(&values)?;
+    let mut bytes = vec![];
+    ef.serialize(&mut bytes)?;
+
+    // Borrowing an aligned buffer
+    let mut aligned = vec![0_u128; bytes.len().div_ceil(16)];
+    let aligned_bytes =
+        unsafe { std::slice::from_raw_parts_mut(aligned.as_mut_ptr() as *mut u8, bytes.len()) };
+    aligned_bytes.copy_from_slice(&bytes);
+    let borrowed = deserialize_from_bytes::<EliasFano>(aligned_bytes)?;
+    for (i, &value) in values.iter().enumerate() {
+        assert_eq!(borrowed.get(i), value);
+    }
+
+    // Owning the buffer
+    let owned = from_owned_bytes::<EliasFano>(bytes)?;
+    for (i, &value) in values.iter().enumerate() {
+        assert_eq!(owned.uncase().get(i), value);
+    }
+
+    // Invalid data
+    assert!(from_owned_bytes::<EliasFano>(vec![0; 100]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_compressed() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut bfv = BitFieldVec::<usize>::new(20, 100_000);
+    for i in 0..bfv.len() {
+        // Skewed values compress well
+        bfv.set(
+            i,
+            if rng.gen_range(0..10) == 0 {
+                rng.gen_range(0..1 << 20)
+            } else {
+                0
+            },
+        );
+    }
+    let tmp_file = std::env::temp_dir().join("test_compressed.bin");
+    store_compressed(&bfv, &tmp_file)?;
+    let plain_file = store(&bfv, "test_compressed_plain.bin")?;
+    assert!(std::fs::metadata(&tmp_file)?.len() < std::fs::metadata(&plain_file)?.len());
+
+    let case = load_compressed::<BitFieldVec<usize>>(&tmp_file)?;
+    let loaded = case.uncase();
+    let full = load_full_compressed::<BitFieldVec<usize>>(&tmp_file)?;
+    assert_eq!(loaded.len(), bfv.len());
+    assert_eq!(full.len(), bfv.len());
+    for i in 0..bfv.len() {
+        assert_eq!(loaded.get(i), bfv.get(i));
+        assert_eq!(full.get(i), bfv.get(i));
+    }
+
+    // Compressed and uncompressed files are not interchangeable
+    assert!(<BitFieldVec<usize>>::load_mem(&tmp_file).is_err());
+    assert!(load_compressed::<BitFieldVec<usize>>(&plain_file).is_err());
+    assert!(load_full_compressed::<BitFieldVec<usize>>(&plain_file).is_err());
+    Ok(())
+}
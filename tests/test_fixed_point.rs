@@ -0,0 +1,60 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+#[test]
+fn test_fixed_point_round_trip() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let n = 1000;
+    for bit_width in [1, 5, 16, 32] {
+        for v in [
+            FixedPointBitFieldVec::<u64>::with_shift(bit_width, n, bit_width as u32 / 2),
+            FixedPointBitFieldVec::<u64>::new(bit_width, n, 10.0),
+            FixedPointBitFieldVec::<u64>::new(bit_width, n, 0.3),
+        ] {
+            let mut v = v;
+            let values = (0..n)
+                .map(|_| rng.gen_range(0.0..=v.max_f64()))
+                .collect::<Vec<_>>();
+            for (i, &x) in values.iter().enumerate() {
+                v.set_f64(i, x);
+            }
+            for (i, &x) in values.iter().enumerate() {
+                assert!((v.get_f64(i) - x).abs() <= v.step() / 2.0);
+            }
+            assert!(v.iter_f64().eq((0..n).map(|i| v.get_f64(i))));
+        }
+    }
+}
+
+#[test]
+fn test_fixed_point_clamp() {
+    let mut v = FixedPointBitFieldVec::<u16>::new(10, 4, 100.0);
+    assert_eq!(v.max_f64(), 1023.0 / 100.0);
+    v.set_f64(0, -3.0);
+    v.set_f64(1, 1E10);
+    v.set_f64(2, f64::NAN);
+    v.set_f64(3, f64::INFINITY);
+    assert_eq!(v.get_f64(0), 0.0);
+    assert_eq!(v.get_f64(1), v.max_f64());
+    assert_eq!(v.get_f64(2), 0.0);
+    assert_eq!(v.get_f64(3), v.max_f64());
+
+    let mut v = FixedPointBitFieldVec::<u64>::with_shift(64, 1, 0);
+    v.set_f64(0, 1E30);
+    assert_eq!(v.as_ref().get(0), u64::MAX);
+}
+
+#[test]
+#[should_panic]
+fn test_fixed_point_zero_scale() {
+    FixedPointBitFieldVec::<usize>::new(8, 1, 0.0);
+}
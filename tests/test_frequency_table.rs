@@ -0,0 +1,55 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use sux::prelude::*;
+
+#[test]
+fn test_frequency_table() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (max_key, n) in [(0, 10), (10, 0), (10, 1000), (1000, 100), (100, 100_000)] {
+        let keys = (0..n)
+            .map(|_| rng.gen_range(0..=max_key))
+            .collect::<Vec<_>>();
+        let mut expected = HashMap::new();
+        for &key in &keys {
+            *expected.entry(key).or_insert(0) += 1;
+        }
+
+        let table = FrequencyTable::from_keys(max_key, keys.iter().copied())?;
+        assert_eq!(table.len(), max_key + 1);
+        for key in 0..=max_key {
+            assert_eq!(table.get(key), expected.get(&key).copied().unwrap_or(0));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_frequency_table_widen() -> Result<()> {
+    // A filter hides the size hint, so counters start with one bit
+    let keys = (0..1000).filter(|_| true).map(|i| i % 3);
+    let table = FrequencyTable::from_keys(3, keys)?;
+    assert_eq!(table.bit_width(), 9);
+    assert_eq!(
+        [table.get(0), table.get(1), table.get(2), table.get(3)],
+        [334, 333, 333, 0]
+    );
+
+    let mut table = FrequencyTable::new(1, 1);
+    table.increment(0)?;
+    assert_eq!(table.bit_width(), 1);
+    table.increment(0)?;
+    assert_eq!(table.bit_width(), 2);
+    assert_eq!(table.get(0), 2);
+    assert!(table.increment(2).is_err());
+    Ok(())
+}
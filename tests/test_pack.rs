@@ -0,0 +1,96 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+fn check(values: &[usize], packed: &PackedInts) {
+    assert_eq!(packed.len(), values.len());
+    for (i, &value) in values.iter().enumerate() {
+        assert_eq!(packed.get(i), value, "index {}", i);
+    }
+}
+
+#[test]
+fn test_pack() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    // Unsorted values with no runs
+    let values = (0..1000)
+        .map(|_| rng.gen_range(0..1000))
+        .collect::<Vec<_>>();
+    let packed = pack(&values)?;
+    assert!(matches!(packed, PackedInts::BitFieldVec(_)));
+    check(&values, &packed);
+
+    // Dense strictly increasing values
+    let values = (0..10_000)
+        .filter(|_| rng.gen_bool(0.8))
+        .collect::<Vec<_>>();
+    let packed = pack(&values)?;
+    assert!(matches!(packed, PackedInts::DenseBitmap(_)));
+    check(&values, &packed);
+
+    // Sparse non-decreasing values
+    let mut values = (0..1000)
+        .map(|_| rng.gen_range(0..1 << 40))
+        .collect::<Vec<_>>();
+    values.sort();
+    values[1] = values[0];
+    let packed = pack(&values)?;
+    assert!(matches!(packed, PackedInts::EliasFano(_)));
+    check(&values, &packed);
+
+    // Long runs of unsorted values
+    let values = (0..100)
+        .flat_map(|_| {
+            let value = rng.gen_range(0..1 << 30);
+            std::iter::repeat_n(value, rng.gen_range(1..200))
+        })
+        .collect::<Vec<_>>();
+    let packed = pack(&values)?;
+    assert!(matches!(packed, PackedInts::RunLength(_)));
+    check(&values, &packed);
+
+    // Degenerate inputs
+    for values in [vec![], vec![0], vec![0; 1000], vec![5]] {
+        check(&values, &pack(&values)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_run_length_ints() -> Result<()> {
+    let values = [1, 1, 1, 0, 0, 7, 1, 1];
+    let rle = RunLengthInts::new(&values)?;
+    assert_eq!(rle.num_runs(), 4);
+    for (i, &value) in values.iter().enumerate() {
+        assert_eq!(rle.get(i), value);
+    }
+    assert_eq!(RunLengthInts::new(&[])?.len(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_pack_max() -> Result<()> {
+    // A dense bitmap of length usize::MAX + 1 cannot be built
+    for values in [
+        vec![usize::MAX],
+        vec![0, usize::MAX],
+        vec![1, 2, 3, usize::MAX - 1, usize::MAX],
+        vec![usize::MAX, 0, usize::MAX],
+        vec![usize::MAX; 100],
+    ] {
+        let packed = pack(&values)?;
+        assert!(!matches!(packed, PackedInts::DenseBitmap(_)));
+        check(&values, &packed);
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+#[test]
+fn test_permutation() -> Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 2, 10, 1000, 1025] {
+        let mut values = (0..n).collect::<Vec<_>>();
+        values.shuffle(&mut rng);
+        let perm = Permutation::new(values.iter().copied())?;
+        assert_eq!(perm.len(), n);
+        assert_eq!(
+            perm.as_ref().bit_width(),
+            (usize::BITS - n.saturating_sub(1).leading_zeros()) as usize
+        );
+        for (i, &v) in values.iter().enumerate() {
+            assert_eq!(perm.apply(i), v);
+            assert_eq!(perm.invert(perm.apply(i)), i);
+            assert_eq!(perm.apply(perm.invert(i)), i);
+        }
+        assert!(perm.iter().eq(values.iter().copied()));
+
+        let mut other = (0..n).collect::<Vec<_>>();
+        other.shuffle(&mut rng);
+        let other = Permutation::from_bit_field_vec({
+            let mut v = BitFieldVec::with_capacity(usize::BITS as usize - 1, n);
+            v.extend(other);
+            v
+        })?;
+        let composed = perm.compose(&other);
+        for i in 0..n {
+            assert_eq!(composed.apply(i), perm.apply(other.apply(i)));
+            assert_eq!(composed.invert(i), other.invert(perm.invert(i)));
+        }
+        assert!(perm
+            .compose(&Permutation::identity(n))
+            .iter()
+            .eq(perm.iter()));
+        assert!(Permutation::identity(n)
+            .compose(&perm)
+            .iter()
+            .eq(perm.iter()));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_permutation_invalid() {
+    assert!(Permutation::new([0, 2]).is_err());
+    assert!(Permutation::new([1, 0, 1]).is_err());
+}
@@ -0,0 +1,90 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use sux::prelude::*;
+use sux::{recommend_representation, Representation};
+
+#[test]
+fn test_recommend_representation() {
+    // Full and almost full sets
+    assert_eq!(
+        recommend_representation(999, 1000),
+        Representation::DenseBitmap
+    );
+    assert_eq!(
+        recommend_representation(1 << 20, 1 << 19),
+        Representation::DenseBitmap
+    );
+    // Very sparse sets
+    assert_eq!(
+        recommend_representation(1 << 40, 1000),
+        Representation::EliasFano
+    );
+    assert_eq!(
+        recommend_representation(usize::MAX >> 1, 1 << 20),
+        Representation::EliasFano
+    );
+    // Tiny sets
+    assert_eq!(
+        recommend_representation(1 << 40, 1),
+        Representation::PlainPacked
+    );
+    assert_eq!(
+        recommend_representation(1 << 40, 0),
+        Representation::PlainPacked
+    );
+}
+
+#[test]
+fn test_size_in_bits() -> anyhow::Result<()> {
+    for (u, n) in [
+        (1000, 10),
+        (1000, 100),
+        (100, 100),
+        (100, 1000),
+        (1 << 30, 1000),
+    ] {
+        let mut efb = EliasFanoBuilder::new(n, u);
+        for i in 0..n {
+            efb.push(i * u / n)?;
+        }
+        let (_, _, l, low_bits, high_bits) = efb.build().into_raw_parts();
+        assert_eq!(
+            Representation::EliasFano.size_in_bits(u, n),
+            Some(low_bits.len() * l + BitLength::len(&high_bits))
+        );
+        assert_eq!(
+            Representation::PlainPacked.size_in_bits(u, n),
+            Some(n * BitFieldVec::<usize>::from_slice(&vec![u])?.bit_width())
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_max_upper_bound() {
+    // A bit vector of length usize::MAX + 1 cannot be represented
+    assert_eq!(
+        Representation::DenseBitmap.size_in_bits(usize::MAX, 1),
+        None
+    );
+    assert_eq!(Representation::EliasFano.size_in_bits(usize::MAX, 0), None);
+    assert_eq!(
+        Representation::PlainPacked.size_in_bits(usize::MAX, 10),
+        Some(10 * usize::BITS as usize)
+    );
+    for n in [0, 1, 10, 1000] {
+        assert_ne!(
+            recommend_representation(usize::MAX, n),
+            Representation::DenseBitmap
+        );
+    }
+    assert_eq!(
+        recommend_representation(usize::MAX, 1000),
+        Representation::EliasFano
+    );
+}
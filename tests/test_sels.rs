@@ -223,3 +223,215 @@ fn test_select_zero_fixed2_slow() {
         assert_eq!(simple.select_zero(zeros + 1), None);
     }
 }
+
+#[test]
+fn test_lazy_rank() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 1000, 100_000] {
+        let bitvec = (0..len).map(|_| rng.gen_bool(0.3)).collect::<BitVec>();
+        let mut ranks = Vec::with_capacity(len + 1);
+        let mut rank = 0;
+        for i in 0..len {
+            ranks.push(rank);
+            rank += bitvec[i] as usize;
+        }
+        ranks.push(rank);
+
+        // Small blocks to test memoization across several blocks
+        let lazy = LazyRank::<_, 2>::new(bitvec.clone());
+        assert_eq!(lazy.num_cached_blocks(), 1);
+        for pos in (0..=len).rev() {
+            assert_eq!(lazy.rank(pos), ranks[pos], "pos: {}", pos);
+        }
+        assert_eq!(lazy.rank(len + 1), ranks[len]);
+
+        let mut lazy = LazyRank::<_>::new(bitvec);
+        for pos in (0..=len).step_by(97) {
+            assert_eq!(lazy.rank(pos), ranks[pos], "pos: {}", pos);
+        }
+        lazy.clear_cache();
+        assert_eq!(lazy.num_cached_blocks(), 1);
+    }
+}
+
+#[test]
+fn test_lazy_rank_cache() {
+    let bitvec = (0..1 << 20).map(|i| i % 3 == 0).collect::<BitVec>();
+    let lazy = LazyRank::<_, 4>::new(bitvec);
+    let rank = lazy.rank(500_000);
+    let cached = lazy.num_cached_blocks();
+    assert!(cached > 1);
+    // Querying the same region must hit the cache
+    assert_eq!(lazy.rank(500_000), rank);
+    assert_eq!(lazy.rank(500_001), rank + (500_000 % 3 == 0) as usize);
+    assert_eq!(lazy.num_cached_blocks(), cached);
+    // Querying an earlier region must not compute new counters
+    lazy.rank(1000);
+    assert_eq!(lazy.num_cached_blocks(), cached);
+}
+
+#[test]
+fn test_rank9() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 63, 64, 65, 511, 512, 513, 1000, 4096, 100_000] {
+        for density in [0.0, 0.3, 1.0] {
+            let bitvec = (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>();
+            let rank9 = Rank9::new(bitvec.clone());
+            let mut rank = 0;
+            for pos in 0..len {
+                assert_eq!(rank9.rank(pos), rank, "pos: {}", pos);
+                rank += bitvec[pos] as usize;
+            }
+            assert_eq!(rank9.rank(len), rank);
+            assert_eq!(rank9.rank(len + 1), rank);
+            assert_eq!(rank9.into_inner().as_ref(), bitvec.as_ref());
+        }
+    }
+}
+
+fn check_select_darray<const LOG2_ONES_PER_BLOCK: usize, const LOG2_ONES_PER_SUB: usize>(
+    bitvec: &BitVec,
+) -> usize {
+    let ones = bitvec.iter_ones().collect::<Vec<_>>();
+    let count = ones.len();
+    let select = SelectDArray::<_, LOG2_ONES_PER_BLOCK, LOG2_ONES_PER_SUB>::new(
+        bitvec.clone().with_count(count),
+    );
+    for (rank, &pos) in ones.iter().enumerate() {
+        assert_eq!(select.select(rank), Some(pos), "rank: {}", rank);
+    }
+    assert_eq!(select.select(count), None);
+    select.num_sparse_blocks()
+}
+
+#[test]
+fn test_select_darray() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 64, 1000, 100_000] {
+        for density in [0.0, 0.01, 0.5, 0.99, 1.0] {
+            let bitvec = (0..len).map(|_| rng.gen_bool(density)).collect::<BitVec>();
+            check_select_darray::<10, 5>(&bitvec);
+            check_select_darray::<4, 2>(&bitvec);
+        }
+    }
+}
+
+#[test]
+fn test_select_darray_clustered() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    // Dense runs of ones separated by long gaps
+    let len = 2_000_000;
+    let mut bitvec = BitVec::new(len);
+    let mut pos = 0;
+    while pos < len {
+        let run = rng.gen_range(1..5000);
+        for i in pos..(pos + run).min(len) {
+            bitvec.set(i, rng.gen_bool(0.9));
+        }
+        pos += run + rng.gen_range(0..200_000);
+    }
+    assert!(check_select_darray::<10, 5>(&bitvec) > 0);
+
+    // Very sparse: every block is sparse
+    let bitvec = (0..len).map(|i| i % 100_000 == 7).collect::<BitVec>();
+    assert_eq!(check_select_darray::<2, 1>(&bitvec), 5);
+    // Very dense: no block is sparse
+    let bitvec = (0..len).map(|_| true).collect::<BitVec>();
+    assert_eq!(check_select_darray::<10, 5>(&bitvec), 0);
+}
+
+#[test]
+fn test_with_select() -> anyhow::Result<()> {
+    let bitvec = (0..10_000).map(|i| i % 3 == 1).collect::<BitVec>();
+    let select = bitvec.clone().with_select();
+    for rank in 0..select.count() {
+        assert_eq!(select.select(rank), Some(3 * rank + 1));
+    }
+    let bits: CountBitVec = select.convert_to()?;
+    assert_eq!(bits.count(), 3333);
+    Ok(())
+}
+
+fn check_rank_zero<R: RankZero>(rank: &R, bits: &[bool]) {
+    let mut ones = 0;
+    for (pos, &bit) in bits.iter().enumerate() {
+        assert_eq!(rank.rank(pos), ones, "pos: {}", pos);
+        assert_eq!(rank.rank_zero(pos), pos - ones, "pos: {}", pos);
+        ones += bit as usize;
+    }
+    assert_eq!(rank.rank(bits.len()), ones);
+    assert_eq!(rank.rank_zero(bits.len()), bits.len() - ones);
+}
+
+fn check_select_zero<S: SelectZero>(select: &S, bits: &[bool]) {
+    let zeros = bits
+        .iter()
+        .enumerate()
+        .filter(|(_, &bit)| !bit)
+        .map(|(pos, _)| pos)
+        .collect::<Vec<_>>();
+    for (rank, &pos) in zeros.iter().enumerate() {
+        assert_eq!(select.select_zero(rank), Some(pos), "rank: {}", rank);
+    }
+    assert_eq!(select.select_zero(zeros.len()), None);
+}
+
+#[test]
+fn test_rank_zero_select_zero() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 64, 1000, 10_000] {
+        let bits = (0..len).map(|_| rng.gen_bool(0.3)).collect::<Vec<_>>();
+        let bitvec = bits.iter().copied().collect::<BitVec>();
+        let count_bitvec: CountBitVec = bitvec.clone().into();
+
+        check_rank_zero(&bitvec, &bits);
+        check_select_zero(&bitvec, &bits);
+        check_rank_zero(&count_bitvec, &bits);
+        check_select_zero(&count_bitvec, &bits);
+
+        let rank9 = Rank9::new(count_bitvec.clone());
+        check_rank_zero(&rank9, &bits);
+        check_select_zero(&rank9, &bits);
+
+        let lazy = LazyRank::<_>::new(count_bitvec.clone());
+        check_rank_zero(&lazy, &bits);
+        check_select_zero(&lazy, &bits);
+
+        let select: SelectFixed2<_> = SelectFixed2::new(Rank9::new(count_bitvec.clone()));
+        check_rank_zero(&select, &bits);
+        let select_zero: SelectZeroFixed2<_> = SelectZeroFixed2::new(select);
+        check_rank_zero(&select_zero, &bits);
+        check_select_zero(&select_zero, &bits);
+    }
+}
+
+#[test]
+fn test_check_rank_select() -> anyhow::Result<()> {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for len in [0, 1, 64, 1000, 100_000] {
+        let bitvec: CountBitVec = (0..len)
+            .map(|_| rng.gen_bool(0.3))
+            .collect::<BitVec>()
+            .into();
+
+        let lazy = LazyRank::<_>::new(bitvec.clone());
+        lazy.check_rank_select()?;
+        let select: SelectFixed1<_> = lazy.convert_to()?;
+        select.check_rank_select()?;
+
+        // Corrupt the number of ones
+        let (data, len, count) = bitvec.clone().into_raw_parts();
+        let corrupted =
+            LazyRank::<_>::new(unsafe { CountBitVec::from_raw_parts(data, len, count + 1) });
+        assert!(corrupted.check_rank_select().is_err());
+
+        // Corrupt the inventory of the selection structure
+        if count > 256 {
+            let inventory = vec![0; count.div_ceil(256)];
+            let corrupted: SelectFixed1<_> =
+                unsafe { SelectFixed1::from_raw_parts(LazyRank::<_>::new(bitvec), inventory) };
+            assert!(corrupted.check_rank_select().is_err());
+        }
+    }
+    Ok(())
+}
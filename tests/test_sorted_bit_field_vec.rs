@@ -0,0 +1,66 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use std::collections::BTreeSet;
+use sux::prelude::*;
+
+#[test]
+fn test_sorted_bit_field_vec() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for (n, u) in [(0, 10), (10, 10), (100, 1000), (1000, 1 << 40)] {
+        let mut set = SortedBitFieldVec::new();
+        let mut expected = BTreeSet::new();
+        for _ in 0..n {
+            let value = rng.gen_range(0..u);
+            assert_eq!(set.insert(value), expected.insert(value));
+        }
+        assert_eq!(set.len(), expected.len());
+        assert!(set.iter().eq(expected.iter().copied()));
+        assert_eq!(
+            set.as_ref().bit_width(),
+            expected
+                .last()
+                .map_or(0, |&max| (usize::BITS - max.leading_zeros()) as usize)
+        );
+
+        for value in (0..100).map(|_| rng.gen_range(0..u + 10)) {
+            assert_eq!(set.rank(value), expected.range(..value).count());
+            assert_eq!(set.contains(&value), expected.contains(&value));
+            assert_eq!(
+                set.index_of(&value),
+                expected
+                    .contains(&value)
+                    .then(|| expected.range(..value).count())
+            );
+            assert_eq!(
+                set.succ(&value).map(|(_, v)| v),
+                expected.range(value..).next().copied()
+            );
+            assert_eq!(
+                set.succ_strict(&value).map(|(_, v)| v),
+                expected.range(value + 1..).next().copied()
+            );
+            assert_eq!(
+                set.pred(&value).map(|(_, v)| v),
+                expected.range(..=value).next_back().copied()
+            );
+            assert_eq!(
+                set.pred_strict(&value).map(|(_, v)| v),
+                expected.range(..value).next_back().copied()
+            );
+            if let Some((index, v)) = set.succ(&value) {
+                assert_eq!(set.get(index), v);
+            }
+            if let Some((index, v)) = set.pred(&value) {
+                assert_eq!(set.get(index), v);
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sux::prelude::*;
+
+#[test]
+fn test_var_len_array() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for n in [0, 1, 10, 1000] {
+        let strings = (0..n)
+            .map(|_| {
+                // Lengths include zero to test empty strings
+                let len = rng.gen_range(0..20);
+                (0..len)
+                    .map(|_| rng.gen_range(b'a'..=b'z') as char)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut builder = VarLenArrayBuilder::new();
+        builder.extend(&strings);
+        let array = builder.build();
+
+        assert_eq!(array.len(), n);
+        assert_eq!(array.is_empty(), n == 0);
+        for (i, string) in strings.iter().enumerate() {
+            assert_eq!(array.get(i), string.as_bytes());
+        }
+        assert!(array.iter().eq(strings.iter().map(|s| s.as_bytes())));
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_var_len_array_out_of_bounds() {
+    let mut builder = VarLenArrayBuilder::new();
+    builder.push("");
+    builder.build().get(1);
+}
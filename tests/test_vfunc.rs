@@ -7,7 +7,7 @@
 
 use dsi_progress_logger::*;
 use epserde::prelude::*;
-use sux::{func::VFunc, prelude::VFuncBuilder, utils::FromIntoIterator};
+use sux::{func::VFunc, prelude::*, utils::FromIntoIterator};
 
 #[test]
 fn test_func() -> anyhow::Result<()> {
@@ -48,3 +48,24 @@ fn test_dup_key() {
         )
         .is_err());
 }
+
+#[test]
+fn test_tighten() -> anyhow::Result<()> {
+    let mut pl = ProgressLogger::default();
+
+    for n in [10_usize, 100, 1000, 100000] {
+        let mut func = VFuncBuilder::default().build(
+            FromIntoIterator::from(0..n),
+            FromIntoIterator::from(0_usize..),
+            &mut pl,
+        )?;
+        let bit_width = (n - 1).ilog2() as usize + 1;
+        assert_eq!(func.bit_width(), bit_width);
+
+        // Functions built by VFuncBuilder are already tight
+        func.tighten();
+        assert_eq!(func.bit_width(), bit_width);
+    }
+
+    Ok(())
+}